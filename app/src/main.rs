@@ -25,28 +25,43 @@ async fn main() -> anyhow::Result<()> {
     init_tracing(&config.logging.level, &config.logging.output);
     info!("smart_switcher starting");
 
-    let runtime = Runtime::new(config_path, config);
+    let runtime = Runtime::new(config_path, config)?;
+    info!(layouts = ?runtime.layouts.names().collect::<Vec<_>>(), "keyboard layouts loaded");
     let ctx = ModuleContext {
         bus: runtime.bus.clone(),
         platform: runtime.platform.clone(),
+        layouts: runtime.layouts.clone(),
     };
 
-    #[cfg(target_os = "windows")]
     let (mut keyboard_hook_controller, mut keyboard_forward_join) = {
-        let should_start_hook = runtime.config.layout_switcher.enabled
-            && is_module_loaded(&runtime.config, "layout_switcher");
-
-        if should_start_hook {
-            let hook = runtime
-                .platform
-                .start_keyboard_hook()
-                .context("start keyboard hook")?;
+        // Нажатия нужны обоим модулям: декодер шины собирает из них слова и строки.
+        // Смену фокуса hook присылает туда же, в порядке с нажатиями.
+        let should_start_hook = (runtime.config.layout_switcher.enabled
+            && is_module_loaded(&runtime.config, "layout_switcher"))
+            || (runtime.config.spell_checker.enabled
+                && is_module_loaded(&runtime.config, "spell_checker"));
+
+        // Без hook (NullBackend, нет прав на /dev/input) приложение работает дальше, модули просто
+        // не получают нажатий.
+        let hook = if should_start_hook {
+            match runtime.platform.start_keyboard_hook() {
+                Ok(hook) => Some(hook),
+                Err(e) => {
+                    warn!(error = %e, "keyboard hook is unavailable, typing is not tracked");
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        if let Some(hook) = hook {
             let (controller, events_rx) = hook.into_parts();
 
             let bus = runtime.bus.clone();
             let forward = std::thread::spawn(move || {
                 for ev in events_rx {
-                    bus.send(AppEvent::Keyboard(ev));
+                    bus.send(ev);
                 }
             });
 
@@ -99,14 +114,11 @@ async fn main() -> anyhow::Result<()> {
         handle.join().await?;
     }
 
-    #[cfg(target_os = "windows")]
-    {
-        if let Some(controller) = keyboard_hook_controller.take() {
-            controller.stop();
-        }
-        if let Some(forward) = keyboard_forward_join.take() {
-            let _ = forward.join();
-        }
+    if let Some(controller) = keyboard_hook_controller.take() {
+        controller.stop();
+    }
+    if let Some(forward) = keyboard_forward_join.take() {
+        let _ = forward.join();
     }
 
     info!("smart_switcher stopped");
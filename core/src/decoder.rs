@@ -0,0 +1,699 @@
+// Общий разбор нажатий: буфер строки для окна в фокусе и события уровня текста
+// (`AppEvent::WordCommitted`, `LineCommitted` и правки набираемого слова).
+// Модули не следят сами ни за модификаторами, ни за Backspace, ни за раскладкой.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+
+use smart_switcher_layouts::LayoutTables;
+use smart_switcher_platform::Platform;
+use smart_switcher_shared_types::config::InputConfig;
+use smart_switcher_shared_types::{AppEvent, Key, KeyOrigin, KeyboardEvent, LayoutId};
+use tracing::{debug, warn};
+
+// Дальше строку не помним: для проверки хватает последних слов.
+const MAX_LINE_WORDS: usize = 64;
+// Строки стольких последних окон; у окон, где давно не были, строку забываем.
+const MAX_WINDOW_BUFFERS: usize = 32;
+
+// Законченное слово строки и разделители, набранные после него.
+#[derive(Debug, Clone)]
+struct LineWord {
+    raw_keys: String,
+    text: String,
+    delimiters: String,
+}
+
+#[derive(Debug, Default)]
+struct LineBuffer {
+    // Разделители в начале строки, до первого слова.
+    lead: String,
+    words: Vec<LineWord>,
+    // Набираемое слово: клавиши по US QWERTY (`Key::us_char`).
+    current: String,
+}
+
+impl LineBuffer {
+    fn text(&self) -> String {
+        let mut text = self.lead.clone();
+        for word in &self.words {
+            text.push_str(&word.text);
+            text.push_str(&word.delimiters);
+        }
+        text
+    }
+
+    fn push_word(&mut self, word: LineWord) {
+        if self.words.len() == MAX_LINE_WORDS {
+            self.words.remove(0);
+            self.lead.clear();
+        }
+        self.words.push(word);
+    }
+
+    fn clear(&mut self) {
+        *self = LineBuffer::default();
+    }
+
+    fn is_empty(&self) -> bool {
+        self.lead.is_empty() && self.words.is_empty() && self.current.is_empty()
+    }
+}
+
+// Что декодер знает о клавиатуре сверх `Modifiers` события.
+#[derive(Debug, Default)]
+struct KeyboardState {
+    // Правый Alt: в раскладках с AltGr он печатает символы, а не сочетания.
+    alt_gr: bool,
+    caps_lock: bool,
+}
+
+impl KeyboardState {
+    fn update(&mut self, ev: &KeyboardEvent) {
+        match ev.key {
+            Key::AltRight => self.alt_gr = ev.is_key_down,
+            Key::CapsLock if ev.is_key_down => self.caps_lock = !self.caps_lock,
+            // Включён ли CapsLock при запуске, неизвестно — сверяемся с напечатанной буквой:
+            // бэкенды с `text` (Windows, X11) учитывают в нём CapsLock.
+            key if ev.is_key_down && key.is_letter() => {
+                if let Some(ch) = ev.text.filter(|ch| ch.is_lowercase() || ch.is_uppercase()) {
+                    self.caps_lock = ch.is_uppercase() != ev.modifiers.shift;
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+pub struct KeystrokeDecoder {
+    platform: Platform,
+    layouts: Arc<LayoutTables>,
+    // Нажатия других программ сбрасывают буфер, а не набирают текст.
+    reset_on_other_injections: bool,
+    state: KeyboardState,
+    // Буферы по окнам (`window_id` из `FocusChanged`): вернувшись в окно, продолжаем его строку.
+    buffers: HashMap<u64, LineBuffer>,
+    // Окна в порядке фокуса, последнее — текущее; по нему выбываем давние буферы.
+    recent: VecDeque<u64>,
+    // 0 — окно, о котором бэкенд не сообщал (до первой смены фокуса, evdev, Wayland).
+    focus: u64,
+}
+
+impl KeystrokeDecoder {
+    pub fn new(platform: Platform, layouts: Arc<LayoutTables>, config: &InputConfig) -> Self {
+        let reset_on_other_injections = match config.other_injections.to_lowercase().as_str() {
+            "reset" => true,
+            "process" => false,
+            other => {
+                warn!(value = %other, "unsupported input.other_injections, using \"process\"");
+                false
+            }
+        };
+        Self {
+            platform,
+            layouts,
+            reset_on_other_injections,
+            state: KeyboardState::default(),
+            buffers: HashMap::new(),
+            recent: VecDeque::from([0]),
+            focus: 0,
+        }
+    }
+
+    // События текста, которые следуют из `event`; остальные события декодер только учитывает.
+    pub fn decode(&mut self, event: &AppEvent) -> Vec<AppEvent> {
+        match event {
+            AppEvent::Keyboard(ev) => self.key(ev),
+            AppEvent::TextReplaced => {
+                self.buffer().clear();
+                Vec::new()
+            }
+            AppEvent::FocusChanged { window_id, .. } => {
+                self.focus_window(*window_id);
+                Vec::new()
+            }
+            // Клик мог прийти в другое окно раньше его `FocusChanged` — забываем все строки.
+            AppEvent::PointerActivity => {
+                self.buffers.clear();
+                Vec::new()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    fn key(&mut self, ev: &KeyboardEvent) -> Vec<AppEvent> {
+        match ev.origin {
+            // Свою вставку модуль уже учёл (`TextReplaced`), иначе исправление исправляло бы само себя.
+            KeyOrigin::OwnInjection => return Vec::new(),
+            KeyOrigin::OtherInjection if self.reset_on_other_injections => {
+                return if ev.is_key_down && !is_inert(ev.key) {
+                    self.reset()
+                } else {
+                    Vec::new()
+                };
+            }
+            KeyOrigin::Physical | KeyOrigin::OtherInjection => {}
+        }
+
+        self.state.update(ev);
+        if !ev.is_key_down || is_inert(ev.key) {
+            return Vec::new();
+        }
+
+        // AltGr печатает символы третьего уровня (ż, ß, €, @); бэкенд без `text` не скажет какие.
+        if self.state.alt_gr {
+            return match ev.text.filter(|ch| !ch.is_control()) {
+                Some(ch) if ch.is_alphabetic() && !ch.is_ascii() => self.word_char(ch),
+                Some(ch) => self.delimiter(ch),
+                None if ev.key.us_char(false).is_some() => self.reset(),
+                None => Vec::new(),
+            };
+        }
+        // Alt+клавиша — сочетание, а не текст.
+        if ev.modifiers.alt {
+            return Vec::new();
+        }
+        if ev.modifiers.ctrl || ev.modifiers.meta {
+            return self.shortcut(ev);
+        }
+
+        let shift = ev.modifiers.shift;
+        match ev.key {
+            Key::Backspace => vec![self.backspace()],
+            Key::Enter | Key::NumpadEnter => self.enter(),
+            // Shift+Insert вставляет из буфера обмена.
+            Key::Insert if shift => self.reset(),
+            // Delete стирает за курсором: текст перед ним тот же.
+            Key::Delete | Key::Insert => Vec::new(),
+            key if is_word_key(key) => {
+                // CapsLock меняет регистр только букв.
+                let shift = shift ^ (self.state.caps_lock && key.is_letter());
+                match key.us_char(shift) {
+                    Some(ch) => self.word_char(ch),
+                    None => Vec::new(),
+                }
+            }
+            key if key.us_char(shift).is_some() => {
+                let delimiter = ev
+                    .text
+                    .or_else(|| {
+                        let layout = self.platform.active_layout().ok()?;
+                        self.layouts.find(&layout)?.char_for(key, shift)
+                    })
+                    .or_else(|| key.us_char(shift))
+                    .unwrap_or(' ');
+                self.delimiter(delimiter)
+            }
+            // Стрелки, Home/End, PageUp/PageDown, Tab: курсор ушёл, что перед ним — неизвестно.
+            _ => self.reset(),
+        }
+    }
+
+    // Ctrl/Super + клавиша: текст не печатается, но может поменяться.
+    fn shortcut(&mut self, ev: &KeyboardEvent) -> Vec<AppEvent> {
+        match ev.key {
+            Key::Backspace if ev.modifiers.ctrl && !ev.modifiers.meta => self.erase_word(),
+            // Копирование и удаление за курсором текст перед ним не трогают.
+            Key::C | Key::Insert | Key::Delete => Vec::new(),
+            // Ctrl+V, Ctrl+Z, Ctrl+A, Ctrl+стрелки и сочетания с Super.
+            _ => self.reset(),
+        }
+    }
+
+    fn word_char(&mut self, ch: char) -> Vec<AppEvent> {
+        let buffer = self.buffer();
+        buffer.current.push(ch);
+        vec![AppEvent::WordEdited {
+            raw_keys: buffer.current.clone(),
+        }]
+    }
+
+    fn backspace(&mut self) -> AppEvent {
+        let buffer = self.buffer();
+        if buffer.current.pop().is_some() {
+            return AppEvent::WordEdited {
+                raw_keys: buffer.current.clone(),
+            };
+        }
+
+        match buffer.words.last_mut() {
+            Some(word) => {
+                word.delimiters.pop();
+                if !word.delimiters.is_empty() {
+                    return AppEvent::CharErased;
+                }
+                // Стёрт последний разделитель — курсор снова в конце слова.
+                let word = buffer.words.pop().expect("last word");
+                buffer.current = word.raw_keys.clone();
+                AppEvent::WordReopened {
+                    raw_keys: word.raw_keys,
+                }
+            }
+            None => {
+                buffer.lead.pop();
+                AppEvent::CharErased
+            }
+        }
+    }
+
+    // Ctrl+Backspace стирает до начала слова, а сразу за пробелами — пробелы и слово перед ними.
+    // Как он обходится со знаками препинания, зависит от приложения, — тогда буфер сбрасываем.
+    // Стёртое отдаём теми же событиями, что и серию Backspace.
+    fn erase_word(&mut self) -> Vec<AppEvent> {
+        let buffer = self.buffer();
+        if !buffer.current.is_empty() {
+            buffer.current.clear();
+            return vec![AppEvent::WordEdited {
+                raw_keys: String::new(),
+            }];
+        }
+
+        match buffer.words.pop() {
+            Some(word) if word.delimiters.chars().all(char::is_whitespace) => {
+                let mut events = vec![AppEvent::CharErased; word.delimiters.chars().count() - 1];
+                events.push(AppEvent::WordReopened {
+                    raw_keys: word.raw_keys,
+                });
+                events.push(AppEvent::WordEdited {
+                    raw_keys: String::new(),
+                });
+                events
+            }
+            _ => self.reset(),
+        }
+    }
+
+    fn delimiter(&mut self, delimiter: char) -> Vec<AppEvent> {
+        if self.buffer().current.is_empty() {
+            let buffer = self.buffer();
+            match buffer.words.last_mut() {
+                Some(word) => word.delimiters.push(delimiter),
+                None => buffer.lead.push(delimiter),
+            }
+            return vec![AppEvent::DelimiterTyped { delimiter }];
+        }
+
+        match self.platform.active_layout() {
+            Ok(layout) => vec![self.commit_word(layout, delimiter)],
+            Err(e) => {
+                debug!(error = %e, "decoder: active layout unknown, buffer dropped");
+                self.reset()
+            }
+        }
+    }
+
+    fn reset(&mut self) -> Vec<AppEvent> {
+        self.buffer().clear();
+        vec![AppEvent::BufferReset]
+    }
+
+    fn enter(&mut self) -> Vec<AppEvent> {
+        let mut events = Vec::new();
+        if !self.buffer().current.is_empty() {
+            match self.platform.active_layout() {
+                Ok(layout) => events.push(self.commit_word(layout, '\n')),
+                Err(e) => {
+                    debug!(error = %e, "decoder: active layout unknown, word dropped");
+                    self.buffer().current.clear();
+                }
+            }
+        }
+
+        let buffer = self.buffer();
+        let text = buffer.text();
+        buffer.clear();
+        events.push(AppEvent::LineCommitted {
+            text: text.trim_end_matches('\n').to_string(),
+        });
+        events
+    }
+
+    fn commit_word(&mut self, layout: LayoutId, delimiter: char) -> AppEvent {
+        let raw_keys = std::mem::take(&mut self.buffer().current);
+        let text = match (self.layouts.get("us"), self.layouts.find(&layout)) {
+            (Some(us), Some(table)) => us.convert(&raw_keys, table),
+            _ => raw_keys.clone(),
+        };
+        self.buffer().push_word(LineWord {
+            raw_keys: raw_keys.clone(),
+            text: text.clone(),
+            delimiters: delimiter.to_string(),
+        });
+        AppEvent::WordCommitted {
+            raw_keys,
+            text,
+            layout,
+            delimiter,
+        }
+    }
+
+    // Пустой буфер окна не храним, а непустых — не больше MAX_WINDOW_BUFFERS:
+    // закрытые окна не копятся.
+    fn focus_window(&mut self, window_id: u64) {
+        if self
+            .buffers
+            .get(&self.focus)
+            .is_some_and(LineBuffer::is_empty)
+        {
+            self.buffers.remove(&self.focus);
+        }
+        self.focus = window_id;
+
+        self.recent.retain(|&id| id != window_id);
+        self.recent.push_back(window_id);
+        while self.recent.len() > MAX_WINDOW_BUFFERS {
+            if let Some(oldest) = self.recent.pop_front() {
+                self.buffers.remove(&oldest);
+            }
+        }
+    }
+
+    fn buffer(&mut self) -> &mut LineBuffer {
+        self.buffers.entry(self.focus).or_default()
+    }
+}
+
+// Клавиши, которые в ЙЦУКЕН печатают буквы: A–Z и `;` `'` `[` `]` `` ` `` `,` `.` (ж э х ъ ё б ю).
+fn is_word_key(key: Key) -> bool {
+    key.is_letter()
+        || matches!(
+            key,
+            Key::Semicolon
+                | Key::Quote
+                | Key::BracketLeft
+                | Key::BracketRight
+                | Key::Backquote
+                | Key::Comma
+                | Key::Period
+        )
+}
+
+// Клавиши, которые не печатают и не двигают курсор.
+fn is_inert(key: Key) -> bool {
+    key.is_modifier()
+        || matches!(
+            key,
+            Key::CapsLock | Key::NumLock | Key::ScrollLock | Key::Pause | Key::PrintScreen
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use smart_switcher_platform::FakePlatform;
+    use smart_switcher_shared_types::Modifiers;
+
+    use super::*;
+
+    fn decoder(fake: &FakePlatform) -> KeystrokeDecoder {
+        KeystrokeDecoder::new(
+            fake.platform(),
+            Arc::new(LayoutTables::builtin()),
+            &InputConfig::default(),
+        )
+    }
+
+    fn decode_text(decoder: &mut KeystrokeDecoder, fake: &FakePlatform, text: &str) -> Vec<AppEvent> {
+        fake.type_text(text)
+            .into_iter()
+            .flat_map(|ev| decoder.decode(&AppEvent::Keyboard(ev)))
+            .collect()
+    }
+
+    fn press(key: Key, modifiers: Modifiers, text: Option<char>) -> AppEvent {
+        AppEvent::Keyboard(KeyboardEvent {
+            key,
+            modifiers,
+            text,
+            is_key_down: true,
+            origin: KeyOrigin::Physical,
+        })
+    }
+
+    fn ctrl() -> Modifiers {
+        Modifiers {
+            ctrl: true,
+            ..Modifiers::default()
+        }
+    }
+
+    fn committed(events: &[AppEvent]) -> Vec<(String, String, char)> {
+        events
+            .iter()
+            .filter_map(|event| match event {
+                AppEvent::WordCommitted {
+                    raw_keys,
+                    text,
+                    delimiter,
+                    ..
+                } => Some((raw_keys.clone(), text.clone(), *delimiter)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn commits_words_and_lines_in_the_active_layout() {
+        let fake = FakePlatform::new();
+        let mut decoder = decoder(&fake);
+        fake.set_active_layout(&LayoutId::from_xkb("ru"));
+
+        let events = decode_text(&mut decoder, &fake, "Привет, мир 2\n");
+
+        assert_eq!(
+            committed(&events),
+            vec![
+                // Запятая в ЙЦУКЕН на клавише `/` — разделитель, а не буква.
+                ("Ghbdtn".to_string(), "Привет".to_string(), ','),
+                ("vbh".to_string(), "мир".to_string(), ' '),
+            ]
+        );
+        assert!(matches!(
+            events.last(),
+            Some(AppEvent::LineCommitted { text }) if text == "Привет, мир 2"
+        ));
+    }
+
+    #[test]
+    fn backspace_reopens_the_previous_word() {
+        let fake = FakePlatform::new();
+        let mut decoder = decoder(&fake);
+
+        let events = decode_text(&mut decoder, &fake, "ab  \u{8}\u{8}\u{8}c \u{8}\u{8}\u{8}\u{8}");
+
+        let edits: Vec<String> = events
+            .iter()
+            .map(|event| match event {
+                AppEvent::WordEdited { raw_keys } => format!("edit {raw_keys}"),
+                AppEvent::WordReopened { raw_keys } => format!("reopen {raw_keys}"),
+                AppEvent::WordCommitted { raw_keys, .. } => format!("commit {raw_keys}"),
+                AppEvent::DelimiterTyped { .. } => "delimiter".to_string(),
+                AppEvent::CharErased => "erased".to_string(),
+                other => format!("{other:?}"),
+            })
+            .collect();
+        assert_eq!(
+            edits,
+            vec![
+                "edit a", "edit ab", "commit ab", "delimiter", "erased", "reopen ab",
+                "edit a", "edit ac", "commit ac", "reopen ac", "edit a", "edit ",
+                // Текст до начала буфера декодеру неизвестен.
+                "erased",
+            ]
+        );
+    }
+
+    #[test]
+    fn navigation_resets_and_alt_shortcuts_are_ignored() {
+        let fake = FakePlatform::new();
+        let mut decoder = decoder(&fake);
+        decode_text(&mut decoder, &fake, "hello wor");
+
+        let events = decoder.decode(&press(Key::ArrowLeft, Modifiers::default(), None));
+        assert!(matches!(events[..], [AppEvent::BufferReset]));
+
+        let mut alt_f = fake.type_text("f").remove(0);
+        alt_f.modifiers.alt = true;
+        assert!(decoder.decode(&AppEvent::Keyboard(alt_f)).is_empty());
+
+        let events = decode_text(&mut decoder, &fake, "ld\n");
+        assert!(matches!(
+            events.last(),
+            Some(AppEvent::LineCommitted { text }) if text == "ld"
+        ));
+    }
+
+    #[test]
+    fn shortcuts_are_not_typed_and_ctrl_backspace_erases_a_word() {
+        let fake = FakePlatform::new();
+        let mut decoder = decoder(&fake);
+        decode_text(&mut decoder, &fake, "one two  thr");
+
+        assert!(decoder.decode(&press(Key::C, ctrl(), None)).is_empty());
+        // Ctrl+Backspace посреди слова — до его начала.
+        let events = decoder.decode(&press(Key::Backspace, ctrl(), None));
+        assert!(matches!(&events[..], [AppEvent::WordEdited { raw_keys }] if raw_keys.is_empty()));
+        // Сразу за пробелами — пробелы и слово перед ними.
+        let events = decoder.decode(&press(Key::Backspace, ctrl(), None));
+        assert!(matches!(
+            &events[..],
+            [
+                AppEvent::CharErased,
+                AppEvent::WordReopened { raw_keys },
+                AppEvent::WordEdited { raw_keys: edited },
+            ] if raw_keys == "two" && edited.is_empty()
+        ));
+        let events = decode_text(&mut decoder, &fake, "\n");
+        assert!(matches!(events.last(), Some(AppEvent::LineCommitted { text }) if text == "one "));
+
+        decode_text(&mut decoder, &fake, "wor");
+        let events = decoder.decode(&press(Key::V, ctrl(), None));
+        assert!(matches!(events[..], [AppEvent::BufferReset]));
+    }
+
+    #[test]
+    fn caps_lock_and_alt_gr_characters() {
+        let fake = FakePlatform::new();
+        let mut decoder = decoder(&fake);
+
+        decoder.decode(&press(Key::CapsLock, Modifiers::default(), None));
+        let events = decoder.decode(&press(Key::G, Modifiers::default(), None));
+        assert!(matches!(&events[..], [AppEvent::WordEdited { raw_keys }] if raw_keys == "G"));
+
+        // Бэкенд напечатал строчную — значит, CapsLock на самом деле выключен.
+        let events = decoder.decode(&press(Key::H, Modifiers::default(), Some('h')));
+        assert!(matches!(&events[..], [AppEvent::WordEdited { raw_keys }] if raw_keys == "Gh"));
+
+        // AltGr: буква третьего уровня входит в слово, символ — разделитель.
+        let alt_gr = Modifiers {
+            ctrl: true,
+            alt: true,
+            ..Modifiers::default()
+        };
+        decoder.decode(&press(Key::AltRight, alt_gr, None));
+        let events = decoder.decode(&press(Key::Z, alt_gr, Some('ż')));
+        assert!(matches!(&events[..], [AppEvent::WordEdited { raw_keys }] if raw_keys == "Ghż"));
+        let events = decoder.decode(&press(Key::Q, alt_gr, Some('@')));
+        assert!(matches!(&events[..], [AppEvent::WordCommitted { delimiter: '@', .. }]));
+    }
+
+    #[test]
+    fn caps_lock_reported_by_the_backend_keeps_its_case() {
+        let fake = FakePlatform::new();
+        let mut decoder = decoder(&fake);
+        fake.set_active_layout(&LayoutId::from_xkb("ru"));
+
+        let mut events: Vec<AppEvent> = fake
+            .toggle_caps_lock()
+            .into_iter()
+            .flat_map(|ev| decoder.decode(&AppEvent::Keyboard(ev)))
+            .collect();
+        // Shift при CapsLock даёт строчную букву.
+        events.extend(decode_text(&mut decoder, &fake, "ПРИВЕТ мИР "));
+
+        assert_eq!(
+            committed(&events),
+            vec![
+                ("GHBDTN".to_string(), "ПРИВЕТ".to_string(), ' '),
+                ("vBH".to_string(), "мИР".to_string(), ' '),
+            ]
+        );
+    }
+
+    #[test]
+    fn own_injections_are_skipped_and_others_follow_the_config() {
+        let fake = FakePlatform::new();
+        let injected = |origin| {
+            fake.type_text("a")
+                .into_iter()
+                .map(|ev| AppEvent::Keyboard(KeyboardEvent { origin, ..ev }))
+                .collect::<Vec<_>>()
+        };
+
+        let mut decoder = decoder(&fake);
+        for event in injected(KeyOrigin::OwnInjection) {
+            assert!(decoder.decode(&event).is_empty());
+        }
+        let events: Vec<AppEvent> = injected(KeyOrigin::OtherInjection)
+            .iter()
+            .flat_map(|event| decoder.decode(event))
+            .collect();
+        assert!(matches!(&events[..], [AppEvent::WordEdited { raw_keys }] if raw_keys == "a"));
+
+        let config = InputConfig {
+            other_injections: "reset".to_string(),
+        };
+        let mut decoder = KeystrokeDecoder::new(fake.platform(), Arc::new(LayoutTables::builtin()), &config);
+        let events: Vec<AppEvent> = injected(KeyOrigin::OtherInjection)
+            .iter()
+            .flat_map(|event| decoder.decode(event))
+            .collect();
+        assert!(matches!(events[..], [AppEvent::BufferReset]));
+    }
+
+    #[test]
+    fn each_window_has_its_own_line() {
+        let fake = FakePlatform::new();
+        let mut decoder = decoder(&fake);
+
+        decoder.decode(&fake.set_focus(Some("code"), "main.rs"));
+        decode_text(&mut decoder, &fake, "let ghb");
+        decoder.decode(&fake.set_focus(Some("firefox"), "Search"));
+        let events = decode_text(&mut decoder, &fake, "hi\n");
+        assert!(matches!(events.last(), Some(AppEvent::LineCommitted { text }) if text == "hi"));
+
+        // Вернулись — слово дописывается к своему началу.
+        decoder.decode(&fake.set_focus(Some("code"), "main.rs"));
+        let events = decode_text(&mut decoder, &fake, "dtn\n");
+        assert_eq!(
+            committed(&events),
+            vec![("ghbdtn".to_string(), "ghbdtn".to_string(), '\n')]
+        );
+        assert!(matches!(
+            events.last(),
+            Some(AppEvent::LineCommitted { text }) if text == "let ghbdtn"
+        ));
+    }
+
+    #[test]
+    fn only_recent_windows_keep_their_line() {
+        let fake = FakePlatform::new();
+        let mut decoder = decoder(&fake);
+
+        for window in 0..=MAX_WINDOW_BUFFERS {
+            decoder.decode(&fake.set_focus(Some("term"), &format!("tab {window}")));
+            decode_text(&mut decoder, &fake, "ghb");
+        }
+        assert_eq!(decoder.buffers.len(), MAX_WINDOW_BUFFERS);
+
+        // Самое давнее окно выбыло; возврат в него вытесняет следующее, остальные помнят строку.
+        decoder.decode(&fake.set_focus(Some("term"), "tab 0"));
+        let events = decode_text(&mut decoder, &fake, "dtn\n");
+        assert_eq!(committed(&events), vec![("dtn".to_string(), "dtn".to_string(), '\n')]);
+
+        decoder.decode(&fake.set_focus(Some("term"), "tab 2"));
+        let events = decode_text(&mut decoder, &fake, "dtn\n");
+        assert_eq!(
+            committed(&events),
+            vec![("ghbdtn".to_string(), "ghbdtn".to_string(), '\n')]
+        );
+    }
+
+    #[test]
+    fn click_drops_the_line_of_every_window() {
+        let fake = FakePlatform::new();
+        let mut decoder = decoder(&fake);
+
+        decoder.decode(&fake.set_focus(Some("code"), "main.rs"));
+        decode_text(&mut decoder, &fake, "let ghb");
+        decoder.decode(&fake.set_focus(Some("firefox"), "Search"));
+        decode_text(&mut decoder, &fake, "hi ");
+        decoder.decode(&fake.click());
+
+        let events = decode_text(&mut decoder, &fake, "there\n");
+        assert!(matches!(events.last(), Some(AppEvent::LineCommitted { text }) if text == "there"));
+
+        decoder.decode(&fake.set_focus(Some("code"), "main.rs"));
+        let events = decode_text(&mut decoder, &fake, "dtn\n");
+        assert_eq!(committed(&events), vec![("dtn".to_string(), "dtn".to_string(), '\n')]);
+    }
+}
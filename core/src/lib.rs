@@ -1,23 +1,43 @@
 use std::{
     collections::HashSet,
     path::{Path, PathBuf},
+    sync::{Arc, Mutex, PoisonError},
 };
 
 use anyhow::Context;
 use async_trait::async_trait;
+use smart_switcher_layouts::LayoutTables;
 use smart_switcher_platform::Platform;
 use smart_switcher_shared_types::{AppEvent, Config};
 use tokio::sync::broadcast;
+use tracing::warn;
+
+mod decoder;
+
+pub use decoder::KeystrokeDecoder;
 
 #[derive(Clone)]
 pub struct EventBus {
     sender: broadcast::Sender<AppEvent>,
+    // Через декодер проходит каждое событие: его события текста отправляются сразу
+    // за нажатием, из которого получились, и порядок у всех подписчиков один.
+    decoder: Option<Arc<Mutex<KeystrokeDecoder>>>,
 }
 
 impl EventBus {
     pub fn new(capacity: usize) -> Self {
         let (sender, _) = broadcast::channel(capacity);
-        Self { sender }
+        Self {
+            sender,
+            decoder: None,
+        }
+    }
+
+    pub fn with_decoder(capacity: usize, decoder: KeystrokeDecoder) -> Self {
+        Self {
+            decoder: Some(Arc::new(Mutex::new(decoder))),
+            ..Self::new(capacity)
+        }
     }
 
     pub fn subscribe(&self) -> broadcast::Receiver<AppEvent> {
@@ -25,7 +45,16 @@ impl EventBus {
     }
 
     pub fn send(&self, event: AppEvent) {
+        let Some(decoder) = &self.decoder else {
+            let _ = self.sender.send(event);
+            return;
+        };
+        let mut decoder = decoder.lock().unwrap_or_else(PoisonError::into_inner);
+        let decoded = decoder.decode(&event);
         let _ = self.sender.send(event);
+        for event in decoded {
+            let _ = self.sender.send(event);
+        }
     }
 }
 
@@ -33,6 +62,8 @@ impl EventBus {
 pub struct ModuleContext {
     pub bus: EventBus,
     pub platform: Platform,
+    // Одни таблицы раскладок на все модули.
+    pub layouts: Arc<LayoutTables>,
 }
 
 #[derive(Debug)]
@@ -78,15 +109,39 @@ pub struct Runtime {
     pub config: Config,
     pub bus: EventBus,
     pub platform: Platform,
+    pub layouts: Arc<LayoutTables>,
 }
 
 impl Runtime {
-    pub fn new(config_path: PathBuf, config: Config) -> Self {
-        Self {
+    pub fn new(config_path: PathBuf, config: Config) -> anyhow::Result<Self> {
+        let platform = Platform::new();
+        let layouts = Arc::new(load_layouts(&config, &platform)?);
+        let decoder = KeystrokeDecoder::new(platform.clone(), layouts.clone(), &config.input);
+        Ok(Self {
             config_path,
             config,
-            bus: EventBus::new(256),
-            platform: Platform::new(),
+            bus: EventBus::with_decoder(256, decoder),
+            platform,
+            layouts,
+        })
+    }
+}
+
+// Встроенные таблицы, поверх — системный XKB для установленных раскладок, поверх — файлы пользователя.
+fn load_layouts(config: &Config, platform: &Platform) -> anyhow::Result<LayoutTables> {
+    let mut layouts = LayoutTables::builtin();
+
+    let symbols_dir = Path::new(&config.layouts.xkb_symbols_dir);
+    if !config.layouts.xkb_symbols_dir.is_empty() && symbols_dir.is_dir() {
+        for layout in platform.list_layouts().unwrap_or_default() {
+            if let Err(e) = layouts.add_xkb(symbols_dir, &layout) {
+                warn!(layout = %layout, error = %e, "xkb layout table not loaded, using built-in");
+            }
         }
     }
+
+    layouts
+        .add_dir(&config.layouts.dir)
+        .context("load keyboard layouts")?;
+    Ok(layouts)
 }
@@ -0,0 +1,166 @@
+// Личные исключения пользователя: что никогда не исправлять и что исправлять всегда.
+// Слово хранится так, как оно выглядело на экране до исправления ("ghbdtn", "руддщ"),
+// в нижнем регистре — поэтому правило не срабатывает в обратную сторону.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+// Самые частые короткие слова, которые словари и модель не решают уверенно:
+// yt→не, yf→на, bp→из, jn→от, pf→за, dc→во, lj→до, dctv→всем, tcnm→есть.
+const SEED_ALWAYS: &[&str] = &["yt", "yf", "bp", "jn", "pf", "dc", "lj", "dctv", "tcnm"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Exception {
+    // Пользователь отменил исправление этого слова.
+    Never,
+    // Пользователь сам исправил это слово.
+    Always,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+struct Rules {
+    never: BTreeSet<String>,
+    always: BTreeSet<String>,
+}
+
+impl Rules {
+    fn get(&self, word: &str) -> Option<Exception> {
+        if self.never.contains(word) {
+            Some(Exception::Never)
+        } else if self.always.contains(word) {
+            Some(Exception::Always)
+        } else {
+            None
+        }
+    }
+
+    fn insert(&mut self, word: String, exception: Exception) -> bool {
+        let (to, from) = match exception {
+            Exception::Never => (&mut self.never, &mut self.always),
+            Exception::Always => (&mut self.always, &mut self.never),
+        };
+        let removed = from.remove(&word);
+        to.insert(word) || removed
+    }
+
+    fn is_empty(&self) -> bool {
+        self.never.is_empty() && self.always.is_empty()
+    }
+}
+
+// Общие правила и правила отдельных приложений (имя процесса в нижнем регистре);
+// правило приложения важнее общего.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Exceptions {
+    #[serde(flatten)]
+    global: Rules,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    apps: BTreeMap<String, Rules>,
+}
+
+impl Exceptions {
+    // Исключения нового пользователя: бывший встроенный allowlist коротких слов.
+    pub fn seeded() -> Exceptions {
+        let mut exceptions = Exceptions::default();
+        for word in SEED_ALWAYS {
+            exceptions.learn(word, None, Exception::Always);
+        }
+        exceptions
+    }
+
+    // Файла нет — исключения по умолчанию; битый файл — ошибка, чтобы не затереть его при записи.
+    pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Exceptions> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(Exceptions::seeded());
+        }
+        let source = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read exceptions: {}", path.display()))?;
+        toml::from_str(&source)
+            .with_context(|| format!("failed to parse exceptions: {}", path.display()))
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("failed to create dir: {}", dir.display()))?;
+        }
+        let source = toml::to_string(self).context("failed to serialize exceptions")?;
+        std::fs::write(path, source)
+            .with_context(|| format!("failed to write exceptions: {}", path.display()))
+    }
+
+    // `word` — как слово выглядит на экране; `app` — имя процесса, если правила по приложениям включены.
+    pub fn get(&self, word: &str, app: Option<&str>) -> Option<Exception> {
+        let word = word.to_lowercase();
+        app.and_then(|app| self.apps.get(&app.to_lowercase()))
+            .and_then(|rules| rules.get(&word))
+            .or_else(|| self.global.get(&word))
+    }
+
+    // Запоминает правило (заменяя противоположное). false — такое правило уже было.
+    pub fn learn(&mut self, word: &str, app: Option<&str>, exception: Exception) -> bool {
+        let word = word.to_lowercase();
+        let Some(app) = app else {
+            return self.global.insert(word, exception);
+        };
+        let rules = self.apps.entry(app.to_lowercase()).or_default();
+        let changed = rules.insert(word, exception);
+        if rules.is_empty() {
+            self.apps.remove(&app.to_lowercase());
+        }
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seeded_with_short_words() {
+        let exceptions = Exceptions::seeded();
+        for word in ["yt", "YT", "yf", "bp", "jn", "pf", "dc", "lj", "dctv", "TCNM"] {
+            assert_eq!(exceptions.get(word, None), Some(Exception::Always), "{word}");
+        }
+        assert_eq!(exceptions.get("http", None), None);
+        assert_eq!(exceptions.get("ab", Some("code")), None);
+    }
+
+    #[test]
+    fn app_rules_override_global_ones() {
+        let mut exceptions = Exceptions::seeded();
+        assert!(exceptions.learn("yt", Some("Code.exe"), Exception::Never));
+        assert!(!exceptions.learn("yt", Some("code.exe"), Exception::Never));
+
+        assert_eq!(exceptions.get("yt", Some("code.exe")), Some(Exception::Never));
+        assert_eq!(exceptions.get("yt", Some("firefox")), Some(Exception::Always));
+        assert_eq!(exceptions.get("yt", None), Some(Exception::Always));
+
+        // Противоположное правило заменяет прежнее.
+        assert!(exceptions.learn("yt", None, Exception::Never));
+        assert_eq!(exceptions.get("yt", Some("firefox")), Some(Exception::Never));
+    }
+
+    #[test]
+    fn round_trips_through_toml() {
+        let mut exceptions = Exceptions::seeded();
+        exceptions.learn("ghbdtn", None, Exception::Never);
+        exceptions.learn("руддщ", Some("telegram"), Exception::Always);
+
+        let source = toml::to_string(&exceptions).unwrap();
+        assert!(source.contains("[apps.telegram]"), "{source}");
+        let parsed: Exceptions = toml::from_str(&source).unwrap();
+        assert_eq!(parsed, exceptions);
+
+        let parsed: Exceptions = toml::from_str("never = [\"ghbdtn\"]").unwrap();
+        assert_eq!(parsed.get("ghbdtn", None), Some(Exception::Never));
+        assert_eq!(parsed.get("yt", None), None);
+    }
+}
@@ -0,0 +1,607 @@
+// Решение «набрано ли слово не в той раскладке» без платформы и рантайма:
+// на вход — клавиши слова и активная раскладка, на выход — `Decision` с причинами.
+
+mod exceptions;
+
+use std::sync::Arc;
+
+use anyhow::Context;
+use smart_switcher_language::{Dictionary, NgramModel};
+use smart_switcher_layouts::{KeyboardLayout, LayoutTables};
+use smart_switcher_shared_types::{LayoutId, Script};
+
+pub use exceptions::{Exception, Exceptions};
+
+// Одна клавиша — не слово: "b" → "и", "z" → "я" по ошибке исправлять нельзя.
+const MIN_WORD_KEYS: usize = 2;
+
+// Где набрано слово.
+#[derive(Debug, Clone)]
+pub struct DetectionContext {
+    pub active: LayoutId,
+    // Установленные раскладки, среди них ищется целевая.
+    pub installed: Vec<LayoutId>,
+    // Процесс окна в фокусе — для исключений отдельных приложений; None — только общие.
+    pub app: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Keep,
+    Correct,
+}
+
+// Почему принято решение; причины идут в порядке проверок.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Reason {
+    SkippedTooShort,
+    // Акроним или camelCase.
+    SkippedFilter,
+    // В слове есть клавиши, которые в ЙЦУКЕН не печатают буквы.
+    SkippedNotLayoutKeys,
+    SkippedNotEnglishSpelling,
+    SkippedUnknownScript,
+    // Слово проверяется заново как часть фразы (`Detector::evaluate_in_phrase`).
+    InPhrase,
+    // Личное исключение пользователя (`Exceptions`), словари и модель не спрашиваются.
+    ExceptionNever,
+    ExceptionAlways,
+    // Слово известно в раскладке, в которой набрано.
+    DictionaryKeep { language: &'static str },
+    // Известна конвертация слова в другую раскладку.
+    DictionaryEnToRu,
+    DictionaryRuToEn,
+    // Перевес n-граммной модели в сторону исправления, в натах.
+    ModelEnToRu { margin: f64 },
+    ModelRuToEn { margin: f64 },
+    BelowConfidence { confidence: f64 },
+    // Текст исправляется, но переключиться некуда — раскладку пользователь переключит сам.
+    NoTargetLayout,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Decision {
+    pub action: Action,
+    // Раскладка, на которую переключиться после исправления.
+    pub target: Option<LayoutId>,
+    // Что слово показывает на экране сейчас, в активной раскладке.
+    pub original: String,
+    // Чем заменить набранное слово при `Action::Correct`.
+    pub replacement: String,
+    // Перевес модели в сторону исправления (в натах); 0 — если до модели не дошло.
+    pub confidence: f64,
+    pub reasons: Vec<Reason>,
+}
+
+impl Decision {
+    fn skip(reason: Reason) -> Decision {
+        Decision {
+            action: Action::Keep,
+            target: None,
+            original: String::new(),
+            replacement: String::new(),
+            confidence: 0.0,
+            reasons: vec![reason],
+        }
+    }
+}
+
+// Насколько строго судить слово.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Scope {
+    Word,
+    // Следующее слово уже набрано не в той раскладке: хватает одной клавиши,
+    // а слово из обоих словарей исправляется. Порог модели тот же — иначе "http" → "реез".
+    Phrase,
+}
+
+pub struct Detector {
+    tables: Arc<LayoutTables>,
+    // Слово хранится клавишами по US QWERTY (`Key::us_char`), из него и конвертируем.
+    us: KeyboardLayout,
+    ru: KeyboardLayout,
+    confidence: f64,
+    exceptions: Exceptions,
+}
+
+impl Detector {
+    // `confidence` — насколько модель должна предпочесть другую раскладку для слова вне словарей.
+    pub fn new(tables: Arc<LayoutTables>, confidence: f64) -> anyhow::Result<Detector> {
+        let us = tables.get("us").cloned().context("keyboard layout table 'us' is missing")?;
+        let ru = tables.get("ru").cloned().context("keyboard layout table 'ru' is missing")?;
+        Ok(Detector {
+            tables,
+            us,
+            ru,
+            confidence,
+            exceptions: Exceptions::seeded(),
+        })
+    }
+
+    pub fn exceptions(&self) -> &Exceptions {
+        &self.exceptions
+    }
+
+    // Загруженные из файла или выученные исключения пользователя.
+    pub fn exceptions_mut(&mut self) -> &mut Exceptions {
+        &mut self.exceptions
+    }
+
+    // `word` — клавиши слова как символы US QWERTY с учётом Shift ("ghbdtn", ":ehyfk").
+    pub fn evaluate(&self, word: &str, context: &DetectionContext) -> Decision {
+        self.evaluate_scoped(word, context, Scope::Word)
+    }
+
+    // Слово, набранное в той же раскладке прямо перед исправляемым: одно слово оно могло
+    // и проскочить ("z ghbdtn" — "я привет"), поэтому сомнения решаются в пользу исправления.
+    // Исключения пользователя по-прежнему главнее.
+    pub fn evaluate_in_phrase(&self, word: &str, context: &DetectionContext) -> Decision {
+        let mut decision = self.evaluate_scoped(word, context, Scope::Phrase);
+        decision.reasons.insert(0, Reason::InPhrase);
+        decision
+    }
+
+    fn evaluate_scoped(&self, word: &str, context: &DetectionContext, scope: Scope) -> Decision {
+        let min_keys = match scope {
+            Scope::Word => MIN_WORD_KEYS,
+            Scope::Phrase => 1,
+        };
+        if word.chars().count() < min_keys {
+            return Decision::skip(Reason::SkippedTooShort);
+        }
+        // Консервативный фильтр: не трогаем акронимы и camelCase.
+        // "Ghbdtn" в начале предложения — не camelCase, его исправляем.
+        if is_all_upper_ascii(word) || is_camel_case_ascii(word) {
+            return Decision::skip(Reason::SkippedFilter);
+        }
+        if !is_ascii_layout_keys(word) {
+            return Decision::skip(Reason::SkippedNotLayoutKeys);
+        }
+
+        match context.active.script {
+            Script::Latin => self.evaluate_en_to_ru(word, context, scope),
+            Script::Cyrillic => self.evaluate_ru_to_en(word, context, scope),
+            Script::Other => Decision::skip(Reason::SkippedUnknownScript),
+        }
+    }
+
+    fn evaluate_en_to_ru(&self, typed: &str, context: &DetectionContext, scope: Scope) -> Decision {
+        let target = find_target_layout(&context.installed, Script::Cyrillic, "ru");
+        let target_table = target
+            .as_ref()
+            .and_then(|target| self.tables.find(target))
+            .unwrap_or(&self.ru);
+        let converted = self.us.convert(typed, target_table);
+        let margin = ru_over_en_margin(typed, &converted);
+        let screen_table = self.tables.find(&context.active).unwrap_or(&self.us);
+        let original = self.us.convert(typed, screen_table);
+
+        let mut reasons = Vec::new();
+        let correct = if let Some(exception) = self.exception(&original, context) {
+            exception_reason(exception, &mut reasons)
+        } else if Dictionary::english().contains(english_core(typed))
+            && !(scope == Scope::Phrase && Dictionary::russian().contains(&converted))
+        {
+            reasons.push(Reason::DictionaryKeep { language: "en" });
+            false
+        } else if Dictionary::russian().contains(&converted) {
+            reasons.push(Reason::DictionaryEnToRu);
+            true
+        } else {
+            // Слова нет в словарях (имена, сленг, словоформы) — решает n-граммная модель.
+            reasons.push(Reason::ModelEnToRu { margin });
+            self.passes_confidence(margin, &mut reasons)
+        };
+
+        self.decide(correct, target, original, converted, margin, reasons)
+    }
+
+    // Тут `typed` — это физические латинские клавиши.
+    // Если пользователь хотел английское слово, оно уже находится в `typed`.
+    fn evaluate_ru_to_en(&self, typed: &str, context: &DetectionContext, scope: Scope) -> Decision {
+        if !is_english_spelling(typed) {
+            return Decision::skip(Reason::SkippedNotEnglishSpelling);
+        }
+
+        let screen_table = self.tables.find(&context.active).unwrap_or(&self.ru);
+        let would_be_ru = self.us.convert(typed, screen_table);
+        let target = find_target_layout(&context.installed, Script::Latin, "en");
+        let margin = -ru_over_en_margin(typed, &would_be_ru);
+
+        // На экране настоящее русское слово — не трогаем; набрано известное английское — исправляем.
+        let mut reasons = Vec::new();
+        let correct = if let Some(exception) = self.exception(&would_be_ru, context) {
+            exception_reason(exception, &mut reasons)
+        } else if Dictionary::russian().contains(&would_be_ru)
+            && !(scope == Scope::Phrase && Dictionary::english().contains(english_core(typed)))
+        {
+            reasons.push(Reason::DictionaryKeep { language: "ru" });
+            false
+        } else if Dictionary::english().contains(english_core(typed)) {
+            reasons.push(Reason::DictionaryRuToEn);
+            true
+        } else {
+            reasons.push(Reason::ModelRuToEn { margin });
+            self.passes_confidence(margin, &mut reasons)
+        };
+
+        self.decide(correct, target, would_be_ru, typed.to_string(), margin, reasons)
+    }
+
+    fn exception(&self, original: &str, context: &DetectionContext) -> Option<Exception> {
+        self.exceptions.get(original, context.app.as_deref())
+    }
+
+    fn passes_confidence(&self, margin: f64, reasons: &mut Vec<Reason>) -> bool {
+        let passes = margin >= self.confidence;
+        if !passes {
+            reasons.push(Reason::BelowConfidence {
+                confidence: self.confidence,
+            });
+        }
+        passes
+    }
+
+    fn decide(
+        &self,
+        correct: bool,
+        target: Option<LayoutId>,
+        original: String,
+        replacement: String,
+        confidence: f64,
+        mut reasons: Vec<Reason>,
+    ) -> Decision {
+        if !correct {
+            return Decision {
+                action: Action::Keep,
+                target: None,
+                original,
+                replacement: String::new(),
+                confidence,
+                reasons,
+            };
+        }
+        if target.is_none() {
+            reasons.push(Reason::NoTargetLayout);
+        }
+        Decision {
+            action: Action::Correct,
+            target,
+            original,
+            replacement,
+            confidence,
+            reasons,
+        }
+    }
+}
+
+// Исправлять ли слово по исключению; причина — в `reasons`.
+fn exception_reason(exception: Exception, reasons: &mut Vec<Reason>) -> bool {
+    match exception {
+        Exception::Never => {
+            reasons.push(Reason::ExceptionNever);
+            false
+        }
+        Exception::Always => {
+            reasons.push(Reason::ExceptionAlways);
+            true
+        }
+    }
+}
+
+// Раскладка для переключения после исправления: сначала нужный язык,
+// иначе — любая установленная раскладка того же алфавита.
+pub fn find_target_layout(layouts: &[LayoutId], script: Script, language: &str) -> Option<LayoutId> {
+    layouts
+        .iter()
+        .find(|l| l.script == script && l.primary_language() == language)
+        .or_else(|| layouts.iter().find(|l| l.script == script))
+        .cloned()
+}
+
+fn is_all_upper_ascii(s: &str) -> bool {
+    let mut has_letters = false;
+    for ch in s.chars() {
+        if ch.is_ascii_alphabetic() {
+            has_letters = true;
+            if !ch.is_ascii_uppercase() {
+                return false;
+            }
+        }
+    }
+    has_letters
+}
+
+// Заглавная не первой буквой при наличии строчных: getValue, JavaScript, iPhone.
+// Title-case ("Ghbdtn") сюда не попадает.
+fn is_camel_case_ascii(s: &str) -> bool {
+    let letters: Vec<char> = s.chars().filter(char::is_ascii_alphabetic).collect();
+    let has_lower = letters.iter().any(char::is_ascii_lowercase);
+    let has_inner_upper = letters.iter().skip(1).any(char::is_ascii_uppercase);
+    has_lower && has_inner_upper
+}
+
+// Символы US QWERTY, которые в ЙЦУКЕН печатают буквы (base и shift уровни).
+fn is_ascii_layout_keys(s: &str) -> bool {
+    !s.is_empty()
+        && s.chars().all(|c| {
+            c.is_ascii_alphabetic()
+                || matches!(
+                    c,
+                    ';' | ':' | '\'' | '"' | '[' | '{' | ']' | '}' | '`' | '~' | ',' | '<' | '.' | '>'
+                )
+        })
+}
+
+// Английское написание: буквы и апострофы внутри слова (don't, it's),
+// пунктуация в конце (hello, / end.) не мешает.
+fn is_english_spelling(typed: &str) -> bool {
+    let core = typed.trim_end_matches([',', '.', ';', ':', '\'', '"']);
+    core.starts_with(|c: char| c.is_ascii_alphabetic())
+        && core.chars().all(|c| c.is_ascii_alphabetic() || c == '\'')
+}
+
+// Английское слово без пунктуации в конце (hello, → hello) для поиска в словаре.
+fn english_core(typed: &str) -> &str {
+    typed.trim_end_matches([',', '.', ';', ':', '"'])
+}
+
+// Перевес русской модели над английской для одних и тех же клавиш: ln P(ru) − ln P(en).
+// Пунктуация в конце английского набора (hello,) не учитывается ни с одной стороны.
+fn ru_over_en_margin(typed: &str, ru_text: &str) -> f64 {
+    let core = english_core(typed);
+    let ru_core: String = ru_text.chars().take(core.chars().count()).collect();
+    NgramModel::russian().log_prob(&ru_core) - NgramModel::english().log_prob(core)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detector() -> Detector {
+        Detector::new(Arc::new(LayoutTables::builtin()), 7.0).unwrap()
+    }
+
+    fn context(active: &str) -> DetectionContext {
+        DetectionContext {
+            active: LayoutId::from_xkb(active),
+            installed: vec![LayoutId::from_xkb("us"), LayoutId::from_xkb("ru")],
+            app: None,
+        }
+    }
+
+    // Набрано в EN раскладке — исправить на RU?
+    fn en_to_ru(typed: &str) -> bool {
+        detector().evaluate(typed, &context("us")).action == Action::Correct
+    }
+
+    // Набрано в RU раскладке — исправить на EN?
+    fn ru_to_en(typed: &str) -> bool {
+        detector().evaluate(typed, &context("ru")).action == Action::Correct
+    }
+
+    #[test]
+    fn test_find_target_layout() {
+        let layouts = vec![
+            LayoutId::from_xkb("ua"),
+            LayoutId::from_xkb("us"),
+            LayoutId::from_xkb("ru(phonetic)"),
+        ];
+        assert_eq!(
+            find_target_layout(&layouts, Script::Cyrillic, "ru"),
+            Some(LayoutId::from_xkb("ru(phonetic)"))
+        );
+        assert_eq!(
+            find_target_layout(&layouts, Script::Latin, "en"),
+            Some(LayoutId::from_xkb("us"))
+        );
+
+        // Русской нет — берём любую кириллическую.
+        let layouts = vec![LayoutId::from_xkb("us"), LayoutId::from_xkb("ua")];
+        assert_eq!(
+            find_target_layout(&layouts, Script::Cyrillic, "ru"),
+            Some(LayoutId::from_xkb("ua"))
+        );
+        assert_eq!(find_target_layout(&[], Script::Latin, "en"), None);
+    }
+
+    #[test]
+    fn test_en_to_ru() {
+        let decision = detector().evaluate("ghbdtn", &context("us"));
+        assert_eq!(decision.action, Action::Correct);
+        assert_eq!(decision.original, "ghbdtn");
+        assert_eq!(decision.replacement, "привет");
+        assert_eq!(decision.target, Some(LayoutId::from_xkb("ru")));
+        assert_eq!(decision.reasons, vec![Reason::DictionaryEnToRu]);
+
+        for (typed, expected) in [("dctv", "всем"), ("tcnm", "есть"), ("yt", "не")] {
+            let decision = detector().evaluate(typed, &context("us"));
+            assert_eq!(decision.replacement, expected);
+            assert_eq!(decision.reasons, vec![Reason::ExceptionAlways]);
+        }
+
+        assert!(!en_to_ru("hello"));
+
+        // Смешанный кейс: 'б' набирается через VK_OEM_COMMA, а остальное — через A-Z.
+        // На экране это выглядит как "chf,отать", а по физическим клавишам — "chf,jnfnm".
+        let decision = detector().evaluate("chf,jnfnm", &context("us"));
+        assert_eq!(decision.replacement, "сработать");
+        assert_eq!(decision.action, Action::Correct);
+    }
+
+    #[test]
+    fn test_exceptions_decide_before_dictionaries() {
+        let mut detector = detector();
+        detector.exceptions_mut().learn("ghbdtn", None, Exception::Never);
+        detector.exceptions_mut().learn("руддщ", Some("code"), Exception::Never);
+        detector.exceptions_mut().learn("ntcn", None, Exception::Always);
+
+        let decision = detector.evaluate("Ghbdtn", &context("us"));
+        assert_eq!(decision.action, Action::Keep);
+        assert_eq!(decision.reasons, vec![Reason::ExceptionNever]);
+
+        // Правило приложения действует только в нём.
+        let mut in_code = context("ru");
+        in_code.app = Some("code".to_string());
+        assert_eq!(detector.evaluate("hello", &in_code).action, Action::Keep);
+        assert_eq!(detector.evaluate("hello", &context("ru")).action, Action::Correct);
+
+        // Правило по тексту на экране не срабатывает в обратную сторону.
+        let decision = detector.evaluate("ntcn", &context("us"));
+        assert_eq!(decision.replacement, "тест");
+        assert_eq!(decision.reasons, vec![Reason::ExceptionAlways]);
+        assert_ne!(detector.evaluate("ntcn", &context("ru")).reasons, vec![Reason::ExceptionAlways]);
+    }
+
+    #[test]
+    fn test_ru_to_en() {
+        // Пользователь в RU раскладке хотел EN: 'hello' на экране выглядит как 'руддщ'.
+        let decision = detector().evaluate("hello", &context("ru"));
+        assert_eq!(decision.action, Action::Correct);
+        assert_eq!(decision.original, "руддщ");
+        assert_eq!(decision.replacement, "hello");
+        assert_eq!(decision.target, Some(LayoutId::from_xkb("us")));
+        assert_eq!(decision.reasons, vec![Reason::DictionaryRuToEn]);
+
+        assert!(ru_to_en("thanks"));
+
+        // Пользователь реально набирал русское: на экране это похоже на слово.
+        let decision = detector().evaluate("ghbdtn", &context("ru"));
+        assert_eq!(decision.action, Action::Keep);
+        assert_eq!(decision.reasons, vec![Reason::DictionaryKeep { language: "ru" }]);
+    }
+
+    #[test]
+    fn test_oem_keys_are_part_of_the_word() {
+        // ж э х ъ ё и их заглавные через Shift.
+        for (typed, expected) in [
+            ("ht;bv", "режим"),
+            ("[jhjij", "хорошо"),
+            (":ehyfk", "Журнал"),
+            ("j,]`v", "объём"),
+        ] {
+            let decision = detector().evaluate(typed, &context("us"));
+            assert_eq!(decision.replacement, expected);
+            assert_eq!(decision.action, Action::Correct, "{typed}");
+        }
+
+        // Английское слово с пунктуацией в конце — не трогаем.
+        assert!(!en_to_ru("hello,"));
+    }
+
+    #[test]
+    fn test_title_case_is_not_camel_case() {
+        assert!(!is_camel_case_ascii("Ghbdtn"));
+        assert!(!is_camel_case_ascii("ghbdtn"));
+        assert!(!is_camel_case_ascii(":ehyfk"));
+        assert!(!is_camel_case_ascii("GHBDTN"));
+        assert!(is_camel_case_ascii("getValue"));
+        assert!(is_camel_case_ascii("JavaScript"));
+        assert!(is_camel_case_ascii("iPhone"));
+
+        // Регистр каждой буквы переносится в RU.
+        assert_eq!(detector().evaluate("Ghbdtn", &context("us")).replacement, "Привет");
+        assert_eq!(detector().evaluate("Yt", &context("us")).replacement, "Не");
+
+        for typed in ["GHBDTN", "getValue"] {
+            let decision = detector().evaluate(typed, &context("us"));
+            assert_eq!(decision.reasons, vec![Reason::SkippedFilter]);
+        }
+    }
+
+    #[test]
+    fn test_dictionary_decides_before_model() {
+        // "first" и "start" — известные английские слова, их не трогаем.
+        for typed in ["first", "start", "First"] {
+            let decision = detector().evaluate(typed, &context("us"));
+            assert_eq!(decision.reasons, vec![Reason::DictionaryKeep { language: "en" }]);
+        }
+        assert!(ru_to_en("world"));
+        assert!(ru_to_en("thanks,"));
+
+        // Конвертация — известное русское слово.
+        assert!(en_to_ru("plhfdcndeqnt"));
+        assert!(!ru_to_en("plhfdcndeqnt"));
+    }
+
+    #[test]
+    fn test_model_decides_out_of_vocabulary_words() {
+        // Слов нет в словарях, но модель уверена.
+        let decision = detector().evaluate("dtkjcbgtl", &context("us"));
+        assert_eq!(decision.replacement, "велосипед");
+        assert!(matches!(decision.reasons[..], [Reason::ModelEnToRu { margin }] if margin >= 7.0));
+        assert_eq!(decision.confidence, ru_over_en_margin("dtkjcbgtl", "велосипед"));
+
+        assert!(ru_to_en("reviewing"));
+        assert!(ru_to_en("gregory"));
+        assert!(!en_to_ru("reviewing"));
+
+        // Перевес меньше порога — ничего не делаем ни в одну сторону.
+        for typed in ["http", "db"] {
+            let decision = detector().evaluate(typed, &context("us"));
+            assert_eq!(decision.action, Action::Keep);
+            assert_eq!(
+                decision.reasons.last(),
+                Some(&Reason::BelowConfidence { confidence: 7.0 })
+            );
+            assert!(!ru_to_en(typed), "{typed}");
+        }
+        // Порог настраивается.
+        let lenient = Detector::new(Arc::new(LayoutTables::builtin()), 1.0).unwrap();
+        assert_eq!(lenient.evaluate("http", &context("us")).action, Action::Correct);
+    }
+
+    #[test]
+    fn test_skips_and_missing_target() {
+        let decision = detector().evaluate("b", &context("us"));
+        assert_eq!(decision.reasons, vec![Reason::SkippedTooShort]);
+
+        let decision = detector().evaluate("ghbdtn", &context("fi"));
+        assert_eq!(decision.reasons, vec![Reason::SkippedUnknownScript]);
+
+        // Русской раскладки не установлено — текст всё равно исправляем.
+        let context = DetectionContext {
+            active: LayoutId::from_xkb("us"),
+            installed: vec![LayoutId::from_xkb("us")],
+            app: None,
+        };
+        let decision = detector().evaluate("ghbdtn", &context);
+        assert_eq!(decision.action, Action::Correct);
+        assert_eq!(decision.target, None);
+        assert_eq!(decision.replacement, "привет");
+        assert_eq!(
+            decision.reasons,
+            vec![Reason::DictionaryEnToRu, Reason::NoTargetLayout]
+        );
+    }
+
+    #[test]
+    fn test_phrase_context_relaxes_short_and_ambiguous_words() {
+        let detector = detector();
+        for (typed, expected) in [("b", "и"), ("r", "к"), ("rfr", "как")] {
+            let decision = detector.evaluate_in_phrase(typed, &context("us"));
+            assert_eq!(decision.action, Action::Correct, "{typed}");
+            assert_eq!(decision.replacement, expected);
+            assert_eq!(decision.reasons[0], Reason::InPhrase);
+        }
+        assert_eq!(detector.evaluate("b", &context("us")).action, Action::Keep);
+
+        // Английское слово на своём месте и модель ниже порога фразу не продолжают.
+        for typed in ["the", "a", "http"] {
+            let decision = detector.evaluate_in_phrase(typed, &context("us"));
+            assert_eq!(decision.action, Action::Keep, "{typed}");
+        }
+
+        let mut detector = detector;
+        detector.exceptions_mut().learn("b", None, Exception::Never);
+        let decision = detector.evaluate_in_phrase("b", &context("us"));
+        assert_eq!(decision.reasons, vec![Reason::InPhrase, Reason::ExceptionNever]);
+    }
+
+    #[test]
+    fn test_apostrophe_survives_ru_to_en() {
+        for typed in ["don't", "couldn't", "wouldn't"] {
+            assert!(ru_to_en(typed), "{typed}");
+        }
+    }
+}
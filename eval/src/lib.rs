@@ -0,0 +1,388 @@
+// Прогон `Detector` по текстам: каждое слово «набирается» в своей раскладке (исправлять нельзя)
+// и в чужой (исправить нужно ровно в это слово). Итог — точность, полнота и худшие ошибки.
+// Слова, на которых обучена модель (корпус триграмм и словарь), по умолчанию не оцениваются:
+// на них детектор заведомо прав, и цифры вышли бы лучше, чем на новом тексте.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use anyhow::Context;
+use serde::Serialize;
+use smart_switcher_detector::{Action, Decision, DetectionContext, Detector};
+use smart_switcher_language::Dictionary;
+use smart_switcher_layouts::LayoutTables;
+use smart_switcher_shared_types::LayoutId;
+
+// Раскладка, в которой правильно набирать текст на языке, и раскладка, в которой — ошибочно.
+fn layouts_for(language: &str) -> Option<(LayoutId, LayoutId)> {
+    let (own, other) = match language {
+        "en" => ("us", "ru"),
+        "ru" => ("ru", "us"),
+        _ => return None,
+    };
+    Some((LayoutId::from_xkb(own), LayoutId::from_xkb(other)))
+}
+
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct Counts {
+    // Слово набрано не в той раскладке и исправлено именно в него.
+    pub true_positives: usize,
+    // Исправлено слово, набранное правильно, или исправлено не в то.
+    pub false_positives: usize,
+    // Слово набрано не в той раскладке, а детектор его не тронул.
+    pub false_negatives: usize,
+    pub true_negatives: usize,
+}
+
+impl Counts {
+    pub fn precision(&self) -> f64 {
+        ratio(self.true_positives, self.true_positives + self.false_positives)
+    }
+
+    pub fn recall(&self) -> f64 {
+        ratio(self.true_positives, self.true_positives + self.false_negatives)
+    }
+
+    fn add(&mut self, other: &Counts) {
+        self.true_positives += other.true_positives;
+        self.false_positives += other.false_positives;
+        self.false_negatives += other.false_negatives;
+        self.true_negatives += other.true_negatives;
+    }
+}
+
+// Пустой знаменатель — ошибок нет, считаем 1.
+fn ratio(numerator: usize, denominator: usize) -> f64 {
+    if denominator == 0 {
+        1.0
+    } else {
+        numerator as f64 / denominator as f64
+    }
+}
+
+// Одна и та же ошибка на одном слове, сколько раз встретилась в тексте.
+#[derive(Debug, Clone, Serialize)]
+pub struct Offender {
+    pub word: String,
+    // Клавиши как символы US QWERTY — то, что получает детектор.
+    pub keys: String,
+    // Раскладка, в которой «набрано» слово (`LayoutId::variant`).
+    pub layout: String,
+    pub replacement: String,
+    pub confidence: f64,
+    pub reasons: Vec<String>,
+    pub count: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LanguageReport {
+    pub language: String,
+    pub words: usize,
+    // Слова с символами, которых нет в раскладке языка (цифры, дефис и т.п.).
+    pub skipped_words: usize,
+    // Слова из обучающих данных модели: в оценку не вошли.
+    pub trained_words: usize,
+    #[serde(flatten)]
+    pub counts: Counts,
+    pub precision: f64,
+    pub recall: f64,
+    pub worst_false_positives: Vec<Offender>,
+    pub worst_false_negatives: Vec<Offender>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Report {
+    pub confidence: f64,
+    pub languages: Vec<LanguageReport>,
+    #[serde(flatten)]
+    pub total: Counts,
+    pub precision: f64,
+    pub recall: f64,
+}
+
+pub struct Evaluator {
+    detector: Detector,
+    tables: Arc<LayoutTables>,
+    confidence: f64,
+    // Сколько худших слов попадает в отчёт.
+    top: usize,
+    // false — оценивать и слова, на которых обучена модель.
+    held_out_only: bool,
+}
+
+impl Evaluator {
+    pub fn new(
+        tables: Arc<LayoutTables>,
+        confidence: f64,
+        top: usize,
+        held_out_only: bool,
+    ) -> anyhow::Result<Evaluator> {
+        let detector = Detector::new(tables.clone(), confidence)?;
+        Ok(Evaluator {
+            detector,
+            tables,
+            confidence,
+            top,
+            held_out_only,
+        })
+    }
+
+    // `corpora` — пары (язык, текст); поддерживаются "en" и "ru".
+    pub fn run(&self, corpora: &[(String, String)]) -> anyhow::Result<Report> {
+        let mut languages = Vec::new();
+        let mut total = Counts::default();
+        for (language, text) in corpora {
+            let report = self.evaluate_language(language, text)?;
+            total.add(&report.counts);
+            languages.push(report);
+        }
+        Ok(Report {
+            confidence: self.confidence,
+            languages,
+            precision: total.precision(),
+            recall: total.recall(),
+            total,
+        })
+    }
+
+    fn evaluate_language(&self, language: &str, text: &str) -> anyhow::Result<LanguageReport> {
+        let (own, other) = layouts_for(language)
+            .with_context(|| format!("no layouts to simulate language: {language}"))?;
+        let own_table = self
+            .tables
+            .find(&own)
+            .with_context(|| format!("keyboard layout table '{own}' is missing"))?;
+        let us = self
+            .tables
+            .get("us")
+            .context("keyboard layout table 'us' is missing")?;
+        let installed = vec![own.clone(), other.clone()];
+        let trained = TrainedWords::for_language(language);
+
+        let mut counts = Counts::default();
+        let mut words = 0;
+        let mut skipped_words = 0;
+        let mut trained_words = 0;
+        let mut false_positives = HashMap::new();
+        let mut false_negatives = HashMap::new();
+
+        for word in text_words(text) {
+            words += 1;
+            if !word.chars().all(|ch| own_table.key_for(ch).is_some()) {
+                skipped_words += 1;
+                continue;
+            }
+            if self.held_out_only && trained.contains(word) {
+                trained_words += 1;
+                continue;
+            }
+            let keys = own_table.convert(word, us);
+
+            // В своей раскладке: любое исправление — ложное срабатывание.
+            let context = DetectionContext {
+                active: own.clone(),
+                installed: installed.clone(),
+                app: None,
+            };
+            let decision = self.detector.evaluate(&keys, &context);
+            if decision.action == Action::Correct {
+                counts.false_positives += 1;
+                record(&mut false_positives, word, &keys, &own, &decision);
+            } else {
+                counts.true_negatives += 1;
+            }
+
+            // В чужой раскладке: нужно исправить ровно в исходное слово.
+            let context = DetectionContext {
+                active: other.clone(),
+                installed: installed.clone(),
+                app: None,
+            };
+            let decision = self.detector.evaluate(&keys, &context);
+            match decision.action {
+                Action::Correct if decision.replacement == word => counts.true_positives += 1,
+                Action::Correct => {
+                    counts.false_positives += 1;
+                    record(&mut false_positives, word, &keys, &other, &decision);
+                }
+                Action::Keep => {
+                    counts.false_negatives += 1;
+                    record(&mut false_negatives, word, &keys, &other, &decision);
+                }
+            }
+        }
+
+        Ok(LanguageReport {
+            language: language.to_string(),
+            words,
+            skipped_words,
+            trained_words,
+            precision: counts.precision(),
+            recall: counts.recall(),
+            counts,
+            worst_false_positives: worst(false_positives, self.top),
+            worst_false_negatives: worst(false_negatives, self.top),
+        })
+    }
+}
+
+// Корпуса, по которым build.rs языкового крейта собирает триграммы. В сам крейт они не входят,
+// чтобы не попадать в каждый бинарник.
+fn training_corpus(language: &str) -> Option<&'static str> {
+    match language {
+        "en" => Some(include_str!("../../language/data/corpus/en.txt")),
+        "ru" => Some(include_str!("../../language/data/corpus/ru.txt")),
+        _ => None,
+    }
+}
+
+// Слова, на которых обучена модель языка: корпус триграмм и частотный словарь.
+struct TrainedWords {
+    corpus: HashSet<String>,
+    dictionary: Option<&'static Dictionary>,
+}
+
+impl TrainedWords {
+    fn for_language(language: &str) -> TrainedWords {
+        // Дефис делит слово корпуса на части — и «кто-то», и «то» считаются знакомыми.
+        let corpus = training_corpus(language)
+            .map(|text| text_words(text).map(normalize).collect())
+            .unwrap_or_default();
+        TrainedWords {
+            corpus,
+            dictionary: Dictionary::for_language(language),
+        }
+    }
+
+    fn contains(&self, word: &str) -> bool {
+        self.corpus.contains(&normalize(word))
+            || self.dictionary.is_some_and(|dictionary| dictionary.contains(word))
+    }
+}
+
+// Как в `smart_switcher_language`: нижний регистр, `ё` → `е`.
+fn normalize(word: &str) -> String {
+    word.to_lowercase().replace('ё', "е")
+}
+
+// Слова текста: буквы и апостроф внутри слова, регистр сохраняется.
+fn text_words(text: &str) -> impl Iterator<Item = &str> {
+    text.split(|c: char| !(c.is_alphabetic() || c == '\''))
+        .map(|word| word.trim_matches('\''))
+        .filter(|word| !word.is_empty())
+}
+
+fn record(
+    offenders: &mut HashMap<(String, String), Offender>,
+    word: &str,
+    keys: &str,
+    layout: &LayoutId,
+    decision: &Decision,
+) {
+    offenders
+        .entry((word.to_string(), layout.variant.clone()))
+        .or_insert_with(|| Offender {
+            word: word.to_string(),
+            keys: keys.to_string(),
+            layout: layout.variant.clone(),
+            replacement: decision.replacement.clone(),
+            confidence: decision.confidence,
+            reasons: decision.reasons.iter().map(|r| format!("{r:?}")).collect(),
+            count: 0,
+        })
+        .count += 1;
+}
+
+// Сначала самые частые, при равенстве — самые уверенные ошибки.
+fn worst(offenders: HashMap<(String, String), Offender>, top: usize) -> Vec<Offender> {
+    let mut offenders: Vec<_> = offenders.into_values().collect();
+    offenders.sort_by(|a, b| {
+        b.count
+            .cmp(&a.count)
+            .then(b.confidence.abs().total_cmp(&a.confidence.abs()))
+            .then_with(|| a.word.cmp(&b.word))
+    });
+    offenders.truncate(top);
+    offenders
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Словарные слова короче и проще придумывать — здесь оцениваются все слова.
+    fn evaluator() -> Evaluator {
+        Evaluator::new(Arc::new(LayoutTables::builtin()), 7.0, 5, false).unwrap()
+    }
+
+    #[test]
+    fn counts_both_layouts_for_every_word() {
+        let corpora = vec![
+            ("en".to_string(), "hello world, hello".to_string()),
+            ("ru".to_string(), "Привет, мир 2024".to_string()),
+        ];
+        let report = evaluator().run(&corpora).unwrap();
+
+        let en = &report.languages[0];
+        assert_eq!(en.words, 3);
+        assert_eq!(en.counts.true_positives, 3);
+        assert_eq!(en.counts.true_negatives, 3);
+        assert_eq!(en.precision, 1.0);
+        assert_eq!(en.recall, 1.0);
+
+        let ru = &report.languages[1];
+        assert_eq!(ru.words, 2);
+        assert_eq!(ru.counts.true_positives + ru.counts.false_negatives, 2);
+        assert_eq!(report.total.true_negatives, 5);
+    }
+
+    #[test]
+    fn reports_worst_offenders_by_frequency() {
+        // Одна буква не исправляется никогда — два пропуска "b" против одного "x".
+        let corpora = vec![("en".to_string(), "b b x".to_string())];
+        let report = evaluator().run(&corpora).unwrap();
+
+        let en = &report.languages[0];
+        assert_eq!(en.counts.false_negatives, 3);
+        assert_eq!(en.recall, 0.0);
+        let worst = &en.worst_false_negatives;
+        assert_eq!((worst[0].word.as_str(), worst[0].count), ("b", 2));
+        assert_eq!(worst[0].layout, "ru");
+        assert_eq!(worst[0].reasons, vec!["SkippedTooShort"]);
+        assert_eq!(worst[1].word, "x");
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["languages"][0]["false_negatives"], 3);
+    }
+
+    #[test]
+    fn held_out_report_skips_words_the_model_was_trained_on() {
+        let evaluator = Evaluator::new(Arc::new(LayoutTables::builtin()), 7.0, 5, true).unwrap();
+        // "hello" — в словаре, "Ёлка" — в словаре с `ё`, "zorblax" не видела ни одна модель.
+        let corpora = vec![
+            ("en".to_string(), "hello zorblax".to_string()),
+            ("ru".to_string(), "Ёлка".to_string()),
+        ];
+        let report = evaluator.run(&corpora).unwrap();
+
+        let en = &report.languages[0];
+        assert_eq!((en.words, en.trained_words), (2, 1));
+        // Оценено одно слово: по разу в своей и в чужой раскладке.
+        let counts = &en.counts;
+        let checks = counts.true_positives
+            + counts.false_positives
+            + counts.false_negatives
+            + counts.true_negatives;
+        assert_eq!(checks, 2);
+
+        let ru = &report.languages[1];
+        assert_eq!((ru.words, ru.trained_words), (1, 1));
+        assert_eq!(ru.counts.true_negatives, 0);
+    }
+
+    #[test]
+    fn rejects_unknown_language() {
+        let corpora = vec![("fi".to_string(), "hei".to_string())];
+        assert!(evaluator().run(&corpora).is_err());
+    }
+}
@@ -0,0 +1,55 @@
+// Оценка детектора на текстах:
+// `cargo run -p smart_switcher_eval -- [--confidence 7.0] [--top 20] [--all-words]
+//  [en=path ru=path]`.
+// Без путей берутся тексты из `eval/data/`. Слова, на которых обучена модель, не оцениваются,
+// если не задан `--all-words`. Отчёт — JSON в stdout.
+
+use std::sync::Arc;
+
+use anyhow::{bail, Context};
+use smart_switcher_eval::Evaluator;
+use smart_switcher_layouts::LayoutTables;
+use smart_switcher_shared_types::config::LayoutSwitcherConfig;
+
+fn main() -> anyhow::Result<()> {
+    let mut confidence = LayoutSwitcherConfig::default().confidence;
+    let mut top = 20;
+    let mut held_out_only = true;
+    let mut corpora = Vec::new();
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--confidence" => {
+                let value = args.next().context("--confidence needs a value")?;
+                confidence = value.parse().with_context(|| format!("bad --confidence: {value}"))?;
+            }
+            "--top" => {
+                let value = args.next().context("--top needs a value")?;
+                top = value.parse().with_context(|| format!("bad --top: {value}"))?;
+            }
+            "--all-words" => held_out_only = false,
+            corpus => match corpus.split_once('=') {
+                Some((language, path)) => {
+                    let text = std::fs::read_to_string(path)
+                        .with_context(|| format!("failed to read corpus: {path}"))?;
+                    corpora.push((language.to_string(), text));
+                }
+                None => bail!("expected LANG=PATH, got: {corpus}"),
+            },
+        }
+    }
+
+    if corpora.is_empty() {
+        corpora = vec![
+            ("en".to_string(), include_str!("../data/en.txt").to_string()),
+            ("ru".to_string(), include_str!("../data/ru.txt").to_string()),
+        ];
+    }
+
+    let evaluator =
+        Evaluator::new(Arc::new(LayoutTables::builtin()), confidence, top, held_out_only)?;
+    let report = evaluator.run(&corpora)?;
+    println!("{}", serde_json::to_string_pretty(&report)?);
+    Ok(())
+}
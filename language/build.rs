@@ -0,0 +1,101 @@
+// Частотные словари `data/*.txt` собираются в FST (слово → ранг) при сборке,
+// модели символьных триграмм — из `data/corpus/*.txt` и тех же словарей.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+const DICTIONARIES: [&str; 2] = ["en", "ru"];
+
+// Вероятности в модели хранятся в миллионных долях.
+const PROBABILITY_SCALE: f64 = 1_000_000.0;
+
+fn main() {
+    let out_dir = std::env::var("OUT_DIR").expect("OUT_DIR");
+
+    for lang in DICTIONARIES {
+        let source_path = format!("data/{lang}.txt");
+        println!("cargo:rerun-if-changed={source_path}");
+        let source = std::fs::read_to_string(&source_path).expect("read dictionary source");
+
+        // Первое вхождение слова — его ранг (0 — самое частое).
+        let mut ranks = BTreeMap::new();
+        let words = source
+            .lines()
+            .filter(|line| !line.trim_start().starts_with('#'))
+            .flat_map(str::split_whitespace);
+        for (rank, word) in words.enumerate() {
+            ranks.entry(normalize(word)).or_insert(rank as u64);
+        }
+
+        let bytes = fst::Map::from_iter(ranks.iter().map(|(word, &rank)| (word, rank)))
+            .expect("build dictionary fst")
+            .into_fst()
+            .into_inner();
+        std::fs::write(Path::new(&out_dir).join(format!("{lang}.fst")), bytes)
+            .expect("write dictionary fst");
+
+        let corpus_path = format!("data/corpus/{lang}.txt");
+        println!("cargo:rerun-if-changed={corpus_path}");
+        let corpus = std::fs::read_to_string(&corpus_path).expect("read corpus");
+
+        let mut counts = NgramCounts::default();
+        for word in corpus_words(&corpus).chain(ranks.keys().cloned()) {
+            counts.add_word(&word);
+        }
+        let bytes = fst::Map::from_iter(counts.probabilities())
+            .expect("build ngram fst")
+            .into_fst()
+            .into_inner();
+        std::fs::write(Path::new(&out_dir).join(format!("{lang}.ngrams")), bytes)
+            .expect("write ngram fst");
+    }
+}
+
+// Та же нормализация, что и при поиске: нижний регистр, `ё` → `е`.
+fn normalize(word: &str) -> String {
+    word.to_lowercase().replace('ё', "е")
+}
+
+// Слова корпуса: буквы, апостроф и дефис внутри слова.
+fn corpus_words(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !(c.is_alphabetic() || c == '\'' || c == '-'))
+        .map(|word| word.trim_matches(['\'', '-']))
+        .filter(|word| !word.is_empty())
+        .map(normalize)
+}
+
+#[derive(Default)]
+struct NgramCounts {
+    // n-грамма (1–3 символа) → сколько раз встретилась.
+    ngrams: HashMap<String, u64>,
+    // Контекст (0–2 символа) → сколько раз за ним шёл символ.
+    contexts: HashMap<String, u64>,
+}
+
+impl NgramCounts {
+    // Слово дополняется пробелами как в `NgramModel::log_prob`: два в начале, один в конце.
+    fn add_word(&mut self, word: &str) {
+        let padded: Vec<char> = format!("  {word} ").chars().collect();
+        for i in 2..padded.len() {
+            for order in 1..=3 {
+                let ngram: String = padded[i + 1 - order..=i].iter().collect();
+                let context: String = padded[i + 1 - order..i].iter().collect();
+                *self.ngrams.entry(ngram).or_default() += 1;
+                *self.contexts.entry(context).or_default() += 1;
+            }
+        }
+    }
+
+    // n-грамма → P(последний символ | предыдущие), отсортировано для FST.
+    fn probabilities(&self) -> BTreeMap<String, u64> {
+        self.ngrams
+            .iter()
+            .map(|(ngram, &count)| {
+                let context: String = ngram.chars().take(ngram.chars().count() - 1).collect();
+                let total = self.contexts[&context] as f64;
+                let scaled = (count as f64 / total * PROBABILITY_SCALE).round() as u64;
+                (ngram.clone(), scaled.max(1))
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,100 @@
+use std::sync::OnceLock;
+
+use fst::Map;
+
+mod ngram;
+
+pub use ngram::NgramModel;
+
+// FST из `data/*.txt`, собранные build.rs.
+const EN_FST: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/en.fst"));
+const RU_FST: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/ru.fst"));
+
+// Встроенный частотный словарь одного языка: слово → ранг (0 — самое частое).
+pub struct Dictionary {
+    language: &'static str,
+    words: Map<&'static [u8]>,
+}
+
+impl Dictionary {
+    pub fn english() -> &'static Dictionary {
+        static EN: OnceLock<Dictionary> = OnceLock::new();
+        EN.get_or_init(|| Dictionary::from_fst("en", EN_FST))
+    }
+
+    pub fn russian() -> &'static Dictionary {
+        static RU: OnceLock<Dictionary> = OnceLock::new();
+        RU.get_or_init(|| Dictionary::from_fst("ru", RU_FST))
+    }
+
+    // Словарь по основному языку BCP 47 ("en", "ru"); для остальных языков словаря нет.
+    pub fn for_language(language: &str) -> Option<&'static Dictionary> {
+        match language.split('-').next()? {
+            "en" => Some(Dictionary::english()),
+            "ru" => Some(Dictionary::russian()),
+            _ => None,
+        }
+    }
+
+    fn from_fst(language: &'static str, bytes: &'static [u8]) -> Dictionary {
+        let words = Map::new(bytes)
+            .unwrap_or_else(|e| panic!("built-in dictionary {language} is invalid: {e}"));
+        Dictionary { language, words }
+    }
+
+    pub fn language(&self) -> &'static str {
+        self.language
+    }
+
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    // Ранг слова без учёта регистра и разницы `ё`/`е`.
+    pub fn rank(&self, word: &str) -> Option<u64> {
+        self.words.get(normalize(word))
+    }
+
+    pub fn contains(&self, word: &str) -> bool {
+        self.rank(word).is_some()
+    }
+}
+
+// Та же нормализация, что и в build.rs.
+fn normalize(word: &str) -> String {
+    word.to_lowercase().replace('ё', "е")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_dictionaries_are_loaded() {
+        assert!(Dictionary::english().len() > 500);
+        assert!(Dictionary::russian().len() > 500);
+        assert_eq!(Dictionary::for_language("ru-RU").unwrap().language(), "ru");
+        assert_eq!(Dictionary::for_language("en").unwrap().language(), "en");
+        assert!(Dictionary::for_language("uk").is_none());
+    }
+
+    #[test]
+    fn lookup_ignores_case_and_yo() {
+        let en = Dictionary::english();
+        assert!(en.contains("hello"));
+        assert!(en.contains("Thanks"));
+        assert!(en.contains("don't"));
+        assert!(!en.contains("ghbdtn"));
+
+        let ru = Dictionary::russian();
+        assert!(ru.contains("Привет"));
+        assert!(ru.contains("объём"));
+        assert!(ru.contains("ЕЛКА"));
+        assert!(!ru.contains("руддщ"));
+        assert!(ru.rank("и").unwrap() < ru.rank("браузер").unwrap());
+    }
+}
@@ -0,0 +1,112 @@
+use std::sync::OnceLock;
+
+use fst::Map;
+
+use crate::normalize;
+
+// Модели из `data/corpus/*.txt` + словарей, собранные build.rs.
+const EN_NGRAMS: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/en.ngrams"));
+const RU_NGRAMS: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/ru.ngrams"));
+
+// Масштаб вероятностей в таблице, как в build.rs.
+const PROBABILITY_SCALE: f64 = 1_000_000.0;
+
+// Веса интерполяции триграмм, биграмм, униграмм и равномерного распределения:
+// незнакомая триграмма не обнуляет вероятность слова.
+const WEIGHTS: [f64; 4] = [0.6, 0.3, 0.09, 0.01];
+
+// Размер равномерного распределения для символов, которых модель не видела.
+const ALPHABET_SIZE: f64 = 64.0;
+
+// Символьная триграммная модель одного языка.
+pub struct NgramModel {
+    language: &'static str,
+    probabilities: Map<&'static [u8]>,
+}
+
+impl NgramModel {
+    pub fn english() -> &'static NgramModel {
+        static EN: OnceLock<NgramModel> = OnceLock::new();
+        EN.get_or_init(|| NgramModel::from_fst("en", EN_NGRAMS))
+    }
+
+    pub fn russian() -> &'static NgramModel {
+        static RU: OnceLock<NgramModel> = OnceLock::new();
+        RU.get_or_init(|| NgramModel::from_fst("ru", RU_NGRAMS))
+    }
+
+    // Модель по основному языку BCP 47 ("en", "ru").
+    pub fn for_language(language: &str) -> Option<&'static NgramModel> {
+        match language.split('-').next()? {
+            "en" => Some(NgramModel::english()),
+            "ru" => Some(NgramModel::russian()),
+            _ => None,
+        }
+    }
+
+    fn from_fst(language: &'static str, bytes: &'static [u8]) -> NgramModel {
+        let probabilities = Map::new(bytes)
+            .unwrap_or_else(|e| panic!("built-in ngram model {language} is invalid: {e}"));
+        NgramModel {
+            language,
+            probabilities,
+        }
+    }
+
+    pub fn language(&self) -> &'static str {
+        self.language
+    }
+
+    // Натуральный логарифм вероятности слова целиком (вместе с границами слова).
+    // Чем длиннее слово, тем больше по модулю — сравнивать имеет смысл одинаковые по длине строки.
+    pub fn log_prob(&self, word: &str) -> f64 {
+        let padded: Vec<char> = format!("  {} ", normalize(word)).chars().collect();
+        (2..padded.len())
+            .map(|i| {
+                let p = (1..=3)
+                    .map(|order| {
+                        let ngram: String = padded[i + 1 - order..=i].iter().collect();
+                        WEIGHTS[3 - order] * self.probability(&ngram)
+                    })
+                    .sum::<f64>()
+                    + WEIGHTS[3] / ALPHABET_SIZE;
+                p.ln()
+            })
+            .sum()
+    }
+
+    fn probability(&self, ngram: &str) -> f64 {
+        self.probabilities
+            .get(ngram)
+            .map_or(0.0, |scaled| scaled as f64 / PROBABILITY_SCALE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prefers_own_language() {
+        let en = NgramModel::english();
+        let ru = NgramModel::russian();
+
+        // Слова не из словарей: имена, склонения, сленг.
+        for word in ["gregory", "reviewing", "deployments"] {
+            assert!(en.log_prob(word) > ru.log_prob(word), "{word}");
+        }
+        for word in ["григорий", "проверяющими", "деплоить"] {
+            assert!(ru.log_prob(word) > en.log_prob(word), "{word}");
+        }
+    }
+
+    #[test]
+    fn real_words_score_above_wrong_layout_noise() {
+        let en = NgramModel::english();
+        let ru = NgramModel::russian();
+        assert!(ru.log_prob("проверка") > ru.log_prob("ыекутпер"));
+        assert!(en.log_prob("reviewing") > en.log_prob("кумшуцштп"));
+        assert!(en.log_prob("reviewing") > en.log_prob("xtqhqzxvk"));
+        assert_eq!(en.log_prob("Reviewing"), en.log_prob("reviewing"));
+    }
+}
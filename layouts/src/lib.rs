@@ -0,0 +1,281 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use anyhow::Context;
+use serde::Deserialize;
+use smart_switcher_shared_types::{Key, LayoutId};
+
+mod xkb;
+
+// Встроенные раскладки. Файл пользователя с тем же `name` заменяет встроенный.
+const BUILTIN: [(&str, &str); 2] = [
+    ("us.toml", include_str!("../data/us.toml")),
+    ("ru.toml", include_str!("../data/ru.toml")),
+];
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LayoutFile {
+    name: String,
+    language: String,
+    // Имя клавиши (`KeyboardEvent.code`) → [base, shift].
+    keys: HashMap<String, [char; 2]>,
+}
+
+// Что печатает каждая физическая клавиша в одной раскладке.
+#[derive(Debug, Clone)]
+pub struct KeyboardLayout {
+    // Совпадает с `LayoutId::variant` у XKB: "us", "ru", "ru(phonetic)".
+    pub name: String,
+    // BCP 47, по нему раскладка находится на Windows и для вариантов без своего файла.
+    pub language: String,
+    keys: HashMap<Key, [char; 2]>,
+    chars: HashMap<char, (Key, bool)>,
+}
+
+impl KeyboardLayout {
+    pub fn parse(source: &str) -> anyhow::Result<KeyboardLayout> {
+        let file: LayoutFile = toml::from_str(source)?;
+
+        let mut keys = HashMap::new();
+        for (code, levels) in file.keys {
+            let key = Key::from_code(&code).with_context(|| format!("unknown key code: {code}"))?;
+            keys.insert(key, levels);
+        }
+
+        Ok(KeyboardLayout::from_keys(file.name, file.language, keys))
+    }
+
+    // Раскладка из системного XKB: `variant` как в `LayoutId::variant` ("us", "ru(phonetic)"),
+    // `symbols_dir` — обычно `/usr/share/X11/xkb/symbols`.
+    pub fn from_xkb(symbols_dir: &Path, variant: &str) -> anyhow::Result<KeyboardLayout> {
+        let keys = xkb::load_symbols(symbols_dir, variant)?;
+        let language = LayoutId::from_xkb(variant).language;
+        Ok(KeyboardLayout::from_keys(
+            variant.to_string(),
+            language,
+            keys,
+        ))
+    }
+
+    fn from_keys(name: String, language: String, keys: HashMap<Key, [char; 2]>) -> KeyboardLayout {
+        // Обратная таблица: символ на base уровне важнее того же символа на shift,
+        // при равенстве — клавиша раньше по порядку `Key`, чтобы результат не зависел от HashMap.
+        let mut sorted: Vec<_> = keys.iter().map(|(&key, &levels)| (key, levels)).collect();
+        sorted.sort_by_key(|&(key, _)| key);
+
+        let mut chars = HashMap::new();
+        for shift in [false, true] {
+            for &(key, [base, shifted]) in &sorted {
+                chars
+                    .entry(if shift { shifted } else { base })
+                    .or_insert((key, shift));
+            }
+        }
+
+        KeyboardLayout {
+            name,
+            language,
+            keys,
+            chars,
+        }
+    }
+
+    pub fn char_for(&self, key: Key, shift: bool) -> Option<char> {
+        self.keys
+            .get(&key)
+            .map(|&[base, shifted]| if shift { shifted } else { base })
+    }
+
+    pub fn key_for(&self, ch: char) -> Option<(Key, bool)> {
+        self.chars.get(&ch).copied()
+    }
+
+    // Текст, набранный в этой раскладке, каким он вышел бы теми же клавишами в `to`.
+    // Символы, которых нет на клавиатуре (пробел, перевод строки), остаются как есть.
+    pub fn convert(&self, text: &str, to: &KeyboardLayout) -> String {
+        text.chars()
+            .map(|ch| {
+                self.key_for(ch)
+                    .and_then(|(key, shift)| to.char_for(key, shift))
+                    .unwrap_or(ch)
+            })
+            .collect()
+    }
+}
+
+// Все известные раскладки: встроенные + файлы пользователя.
+#[derive(Debug, Clone)]
+pub struct LayoutTables {
+    layouts: Vec<KeyboardLayout>,
+}
+
+impl LayoutTables {
+    pub fn builtin() -> LayoutTables {
+        let layouts = BUILTIN
+            .iter()
+            .map(|(file, source)| {
+                KeyboardLayout::parse(source)
+                    .unwrap_or_else(|e| panic!("built-in layout {file} is invalid: {e:#}"))
+            })
+            .collect();
+        LayoutTables { layouts }
+    }
+
+    // Встроенные раскладки + `*.toml` из `dir`.
+    pub fn load(dir: impl AsRef<Path>) -> anyhow::Result<LayoutTables> {
+        let mut tables = LayoutTables::builtin();
+        tables.add_dir(dir)?;
+        Ok(tables)
+    }
+
+    // Таблица установленной XKB раскладки из системных symbols-файлов;
+    // заменяет встроенную с тем же именем.
+    pub fn add_xkb(
+        &mut self,
+        symbols_dir: impl AsRef<Path>,
+        layout: &LayoutId,
+    ) -> anyhow::Result<()> {
+        let table = KeyboardLayout::from_xkb(symbols_dir.as_ref(), &layout.variant)?;
+        self.insert(table);
+        Ok(())
+    }
+
+    // `*.toml` из `dir` поверх уже загруженных. Каталога нет — ничего не делаем;
+    // битый файл — ошибка, чтобы опечатка не превращалась в тихую порчу текста.
+    pub fn add_dir(&mut self, dir: impl AsRef<Path>) -> anyhow::Result<()> {
+        let dir = dir.as_ref();
+        if !dir.is_dir() {
+            return Ok(());
+        }
+
+        let mut paths: Vec<_> = std::fs::read_dir(dir)
+            .with_context(|| format!("failed to read layouts dir: {}", dir.display()))?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+            .collect();
+        paths.sort();
+
+        for path in paths {
+            let source = std::fs::read_to_string(&path)
+                .with_context(|| format!("failed to read layout: {}", path.display()))?;
+            let layout = KeyboardLayout::parse(&source)
+                .with_context(|| format!("failed to parse layout: {}", path.display()))?;
+            self.insert(layout);
+        }
+
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> Option<&KeyboardLayout> {
+        self.layouts.iter().find(|layout| layout.name == name)
+    }
+
+    // Таблица для установленной раскладки: точное имя, затем язык, затем основной язык.
+    // Так `ru(phonetic)` без своего файла получает ЙЦУКЕН, а Windows HKL — таблицу по языку.
+    pub fn find(&self, layout: &LayoutId) -> Option<&KeyboardLayout> {
+        self.get(&layout.variant)
+            .or_else(|| self.layouts.iter().find(|l| l.language == layout.language))
+            .or_else(|| {
+                self.layouts
+                    .iter()
+                    .find(|l| l.language.split('-').next() == Some(layout.primary_language()))
+            })
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.layouts.iter().map(|layout| layout.name.as_str())
+    }
+
+    fn insert(&mut self, layout: KeyboardLayout) {
+        match self.layouts.iter_mut().find(|l| l.name == layout.name) {
+            Some(existing) => *existing = layout,
+            None => self.layouts.push(layout),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_layouts_cover_every_printable_key() {
+        let tables = LayoutTables::builtin();
+        for name in ["us", "ru"] {
+            let layout = tables.get(name).unwrap();
+            assert_eq!(layout.keys.len(), 47, "{name}");
+        }
+    }
+
+    #[test]
+    fn converts_between_any_pair_of_layouts() {
+        let tables = LayoutTables::builtin();
+        let us = tables.get("us").unwrap();
+        let ru = tables.get("ru").unwrap();
+
+        assert_eq!(us.convert("ghbdtn", ru), "привет");
+        assert_eq!(us.convert("chf,jnfnm", ru), "сработать");
+        assert_eq!(us.convert("Ghbdtn vbh", ru), "Привет мир");
+        assert_eq!(us.convert("k`;f", ru), "лёжа");
+        assert_eq!(ru.convert("руддщ", us), "hello");
+        assert_eq!(ru.convert("Рщц фку нщгю", us), "How are you.");
+        assert_eq!(ru.convert("руддщ", ru), "руддщ");
+    }
+
+    #[test]
+    fn finds_table_for_installed_layout() {
+        let tables = LayoutTables::builtin();
+        assert_eq!(tables.find(&LayoutId::from_xkb("ru")).unwrap().name, "ru");
+        assert_eq!(
+            tables
+                .find(&LayoutId::from_xkb("ru(phonetic)"))
+                .unwrap()
+                .name,
+            "ru"
+        );
+        assert_eq!(
+            tables
+                .find(&LayoutId::from_windows_hkl(0x0419_0419))
+                .unwrap()
+                .name,
+            "ru"
+        );
+        assert_eq!(
+            tables
+                .find(&LayoutId::from_windows_hkl(0x0809_0809))
+                .unwrap()
+                .name,
+            "us"
+        );
+        assert!(tables.find(&LayoutId::from_xkb("fi")).is_none());
+    }
+
+    #[test]
+    fn user_files_add_and_replace_layouts() {
+        let dir =
+            std::env::temp_dir().join(format!("smart_switcher_layouts_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("ru_phonetic.toml"),
+            "name = \"ru(phonetic)\"\nlanguage = \"ru-RU\"\n[keys]\nKeyQ = [\"я\", \"Я\"]\n",
+        )
+        .unwrap();
+
+        let tables = LayoutTables::load(&dir).unwrap();
+        let phonetic = tables.find(&LayoutId::from_xkb("ru(phonetic)")).unwrap();
+        assert_eq!(phonetic.char_for(Key::Q, false), Some('я'));
+        assert!(tables.get("us").is_some());
+
+        std::fs::write(
+            dir.join("broken.toml"),
+            "name = \"x\"\nlanguage = \"und\"\n[keys]\nKeyQQ = [\"a\", \"A\"]\n",
+        )
+        .unwrap();
+        let err = LayoutTables::load(&dir).unwrap_err();
+        assert!(format!("{err:#}").contains("KeyQQ"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(LayoutTables::load(&dir).unwrap().names().count(), 2);
+    }
+}
@@ -0,0 +1,370 @@
+// Разбор XKB symbols-файлов (`/usr/share/X11/xkb/symbols/*`): только то, что нужно
+// для таблиц раскладок — `include`, `key <XXXX> { [ base, shift, ... ] }`, первая группа.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use anyhow::Context;
+use smart_switcher_shared_types::Key;
+
+// Защита от циклов `include`.
+const MAX_INCLUDE_DEPTH: usize = 16;
+
+pub(crate) fn load_symbols(
+    symbols_dir: &Path,
+    variant: &str,
+) -> anyhow::Result<HashMap<Key, [char; 2]>> {
+    let mut keys = HashMap::new();
+    load_section(symbols_dir, variant, 0, &mut keys)?;
+    if keys.is_empty() {
+        anyhow::bail!("xkb layout {variant} has no printable keys");
+    }
+    Ok(keys)
+}
+
+// "ru(phonetic)" → ("ru", Some("phonetic")).
+fn split_variant(variant: &str) -> (&str, Option<&str>) {
+    match variant.split_once('(') {
+        Some((file, section)) => (file.trim(), Some(section.trim_end_matches(')').trim())),
+        None => (variant.trim(), None),
+    }
+}
+
+fn load_section(
+    symbols_dir: &Path,
+    variant: &str,
+    depth: usize,
+    keys: &mut HashMap<Key, [char; 2]>,
+) -> anyhow::Result<()> {
+    if depth > MAX_INCLUDE_DEPTH {
+        anyhow::bail!("xkb include depth exceeded at {variant}");
+    }
+
+    let (file, section) = split_variant(variant);
+    let path = symbols_dir.join(file);
+    let source = std::fs::read_to_string(&path)
+        .with_context(|| format!("failed to read xkb symbols: {}", path.display()))?;
+    let source = strip_comments(&source);
+    let body = find_section(&source, section)
+        .with_context(|| format!("xkb section not found: {variant}"))?;
+
+    for statement in body.split(';') {
+        let mut statement = statement.trim();
+        // `include` пишется без `;` и склеивается со следующим оператором.
+        while let Some(rest) = statement.strip_prefix("include") {
+            let Some(included) = quoted(rest) else {
+                break;
+            };
+            // "us(basic)+level3(ralt_switch)": буквы берём из всех частей.
+            for part in included.split(['+', '|']).filter(|part| !part.is_empty()) {
+                // "ru:2" — вторая группа, к этой раскладке не относится.
+                if part.contains(':') {
+                    continue;
+                }
+                // Вспомогательных файлов (kpdl, level3) может не быть — раскладка от этого не ломается,
+                // а пустой результат в итоге всё равно станет ошибкой в `load_symbols`.
+                let _ = load_section(symbols_dir, part, depth + 1, keys);
+            }
+            let end = rest.find(included).unwrap_or_default() + included.len() + 1;
+            statement = rest[end..].trim_start();
+        }
+
+        if let Some((key, levels)) = parse_key(statement) {
+            keys.insert(key, levels);
+        }
+    }
+
+    Ok(())
+}
+
+// Тело секции `xkb_symbols "name" { ... }`; без имени — секция `default`, иначе первая.
+fn find_section<'a>(source: &'a str, section: Option<&str>) -> Option<&'a str> {
+    let mut first = None;
+    let mut pos = 0;
+
+    while let Some(found) = source[pos..].find("xkb_symbols") {
+        let header = pos + found;
+        let flags = &source[pos..header];
+        let after = header + "xkb_symbols".len();
+        let name = quoted(&source[after..])?;
+        let open = after + source[after..].find('{')?;
+        let close = open + matching_brace(&source[open..])?;
+        let body = &source[open + 1..close];
+
+        match section {
+            Some(section) if name == section => return Some(body),
+            None if flags.split_whitespace().any(|flag| flag == "default") => return Some(body),
+            _ => {}
+        }
+        first.get_or_insert(body);
+        pos = close + 1;
+    }
+
+    if section.is_none() { first } else { None }
+}
+
+// `//` комментарии до конца строки.
+fn strip_comments(source: &str) -> String {
+    source
+        .lines()
+        .map(|line| line.split_once("//").map_or(line, |(code, _)| code))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// Позиция закрывающей `}` для строки, начинающейся с `{`.
+fn matching_brace(text: &str) -> Option<usize> {
+    let mut depth = 0usize;
+    for (i, ch) in text.char_indices() {
+        match ch {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+fn quoted(text: &str) -> Option<&str> {
+    let start = text.find('"')? + 1;
+    let len = text[start..].find('"')?;
+    Some(&text[start..start + len])
+}
+
+// `key <AD01> { [ q, Q ] }`, `key <AD01> { type= "...", symbols[Group1]= [ q, Q ] }`.
+fn parse_key(statement: &str) -> Option<(Key, [char; 2])> {
+    let statement = statement
+        .strip_prefix("replace")
+        .unwrap_or(statement)
+        .trim_start();
+    let rest = statement.strip_prefix("key")?.trim_start();
+    let name = rest.strip_prefix('<')?;
+    let (name, rest) = name.split_once('>')?;
+    let key = key_for_xkb_name(name)?;
+
+    // Список символов — первая `[`, не прилипшая к имени (`type[Group1]`, `symbols[Group1]`).
+    let open = rest.char_indices().find_map(|(i, ch)| {
+        let attached = rest[..i]
+            .chars()
+            .next_back()
+            .is_some_and(|prev| prev.is_alphanumeric());
+        (ch == '[' && !attached).then_some(i)
+    })?;
+    let close = rest[open..].find(']')? + open;
+    let mut levels = rest[open + 1..close]
+        .split(',')
+        .map(|sym| keysym_char(sym.trim()));
+
+    let base = levels.next()??;
+    let shift = levels.next().flatten().unwrap_or(base);
+    Some((key, [base, shift]))
+}
+
+#[rustfmt::skip]
+const XKB_KEYS: [(&str, Key); 48] = [
+    ("TLDE", Key::Backquote),
+    ("AE01", Key::Digit1), ("AE02", Key::Digit2), ("AE03", Key::Digit3), ("AE04", Key::Digit4),
+    ("AE05", Key::Digit5), ("AE06", Key::Digit6), ("AE07", Key::Digit7), ("AE08", Key::Digit8),
+    ("AE09", Key::Digit9), ("AE10", Key::Digit0), ("AE11", Key::Minus), ("AE12", Key::Equal),
+    ("AD01", Key::Q), ("AD02", Key::W), ("AD03", Key::E), ("AD04", Key::R), ("AD05", Key::T),
+    ("AD06", Key::Y), ("AD07", Key::U), ("AD08", Key::I), ("AD09", Key::O), ("AD10", Key::P),
+    ("AD11", Key::BracketLeft), ("AD12", Key::BracketRight), ("BKSL", Key::Backslash),
+    ("AC01", Key::A), ("AC02", Key::S), ("AC03", Key::D), ("AC04", Key::F), ("AC05", Key::G),
+    ("AC06", Key::H), ("AC07", Key::J), ("AC08", Key::K), ("AC09", Key::L),
+    ("AC10", Key::Semicolon), ("AC11", Key::Quote),
+    ("AB01", Key::Z), ("AB02", Key::X), ("AB03", Key::C), ("AB04", Key::V), ("AB05", Key::B),
+    ("AB06", Key::N), ("AB07", Key::M), ("AB08", Key::Comma), ("AB09", Key::Period),
+    ("AB10", Key::Slash), ("LSGT", Key::IntlBackslash),
+];
+
+fn key_for_xkb_name(name: &str) -> Option<Key> {
+    // Алиасы из keycodes/aliases: <LatQ> — клавиша Q на QWERTY (фонетические раскладки).
+    if let Some(letter) = name.strip_prefix("Lat") {
+        return Key::from_code(&format!("Key{letter}"));
+    }
+    XKB_KEYS
+        .iter()
+        .find(|&&(xkb, _)| xkb == name)
+        .map(|&(_, key)| key)
+}
+
+// Имена keysym'ов из keysymdef.h: ASCII, Latin-1 и кириллица (строчные формы;
+// заглавные выводятся из регистра имени).
+#[rustfmt::skip]
+const KEYSYM_NAMES: &[(&str, char)] = &[
+    ("space", ' '), ("exclam", '!'), ("quotedbl", '"'), ("numbersign", '#'), ("dollar", '$'),
+    ("percent", '%'), ("ampersand", '&'), ("apostrophe", '\''), ("quoteright", '\''),
+    ("parenleft", '('), ("parenright", ')'), ("asterisk", '*'), ("plus", '+'), ("comma", ','),
+    ("minus", '-'), ("period", '.'), ("slash", '/'), ("colon", ':'), ("semicolon", ';'),
+    ("less", '<'), ("equal", '='), ("greater", '>'), ("question", '?'), ("at", '@'),
+    ("bracketleft", '['), ("backslash", '\\'), ("bracketright", ']'), ("asciicircum", '^'),
+    ("underscore", '_'), ("grave", '`'), ("quoteleft", '`'), ("braceleft", '{'), ("bar", '|'),
+    ("braceright", '}'), ("asciitilde", '~'),
+    ("nobreakspace", '\u{a0}'), ("exclamdown", '¡'), ("cent", '¢'), ("sterling", '£'),
+    ("currency", '¤'), ("yen", '¥'), ("brokenbar", '¦'), ("section", '§'), ("diaeresis", '¨'),
+    ("copyright", '©'), ("ordfeminine", 'ª'), ("guillemotleft", '«'), ("guillemetleft", '«'),
+    ("notsign", '¬'), ("registered", '®'), ("macron", '¯'), ("degree", '°'), ("plusminus", '±'),
+    ("twosuperior", '²'), ("threesuperior", '³'), ("acute", '´'), ("mu", 'µ'), ("paragraph", '¶'),
+    ("periodcentered", '·'), ("cedilla", '¸'), ("onesuperior", '¹'), ("masculine", 'º'),
+    ("guillemotright", '»'), ("guillemetright", '»'), ("questiondown", '¿'), ("multiply", '×'),
+    ("division", '÷'), ("ssharp", 'ß'),
+    ("agrave", 'à'), ("aacute", 'á'), ("acircumflex", 'â'), ("atilde", 'ã'), ("adiaeresis", 'ä'),
+    ("aring", 'å'), ("ae", 'æ'), ("ccedilla", 'ç'), ("egrave", 'è'), ("eacute", 'é'),
+    ("ecircumflex", 'ê'), ("ediaeresis", 'ë'), ("igrave", 'ì'), ("iacute", 'í'),
+    ("icircumflex", 'î'), ("idiaeresis", 'ï'), ("eth", 'ð'), ("ntilde", 'ñ'), ("ograve", 'ò'),
+    ("oacute", 'ó'), ("ocircumflex", 'ô'), ("otilde", 'õ'), ("odiaeresis", 'ö'), ("oslash", 'ø'),
+    ("ugrave", 'ù'), ("uacute", 'ú'), ("ucircumflex", 'û'), ("udiaeresis", 'ü'), ("yacute", 'ý'),
+    ("thorn", 'þ'), ("ydiaeresis", 'ÿ'),
+    ("numerosign", '№'), ("EuroSign", '€'),
+    ("Cyrillic_a", 'а'), ("Cyrillic_be", 'б'), ("Cyrillic_ve", 'в'), ("Cyrillic_ghe", 'г'),
+    ("Cyrillic_de", 'д'), ("Cyrillic_ie", 'е'), ("Cyrillic_io", 'ё'), ("Cyrillic_zhe", 'ж'),
+    ("Cyrillic_ze", 'з'), ("Cyrillic_i", 'и'), ("Cyrillic_shorti", 'й'), ("Cyrillic_ka", 'к'),
+    ("Cyrillic_el", 'л'), ("Cyrillic_em", 'м'), ("Cyrillic_en", 'н'), ("Cyrillic_o", 'о'),
+    ("Cyrillic_pe", 'п'), ("Cyrillic_er", 'р'), ("Cyrillic_es", 'с'), ("Cyrillic_te", 'т'),
+    ("Cyrillic_u", 'у'), ("Cyrillic_ef", 'ф'), ("Cyrillic_ha", 'х'), ("Cyrillic_tse", 'ц'),
+    ("Cyrillic_che", 'ч'), ("Cyrillic_sha", 'ш'), ("Cyrillic_shcha", 'щ'),
+    ("Cyrillic_hardsign", 'ъ'), ("Cyrillic_yeru", 'ы'), ("Cyrillic_softsign", 'ь'),
+    ("Cyrillic_e", 'э'), ("Cyrillic_yu", 'ю'), ("Cyrillic_ya", 'я'),
+    ("Ukrainian_ie", 'є'), ("Ukrainian_i", 'і'), ("Ukrainian_yi", 'ї'),
+    ("Ukrainian_ghe_with_upturn", 'ґ'), ("Byelorussian_shortu", 'ў'),
+    ("Byelorussian_i", 'і'), ("Cyrillic_je", 'ј'), ("Cyrillic_lje", 'љ'), ("Cyrillic_nje", 'њ'),
+    ("Cyrillic_dzhe", 'џ'), ("Serbian_dje", 'ђ'), ("Serbian_tshe", 'ћ'), ("Macedonia_gje", 'ѓ'),
+    ("Macedonia_kje", 'ќ'), ("Macedonia_dse", 'ѕ'), ("Cyrillic_ghe_bar", 'ғ'),
+    ("Cyrillic_ka_descender", 'қ'), ("Cyrillic_en_descender", 'ң'), ("Cyrillic_u_straight", 'ү'),
+    ("Cyrillic_u_straight_bar", 'ұ'), ("Cyrillic_shha", 'һ'), ("Cyrillic_schwa", 'ә'),
+    ("Cyrillic_o_bar", 'ө'), ("Cyrillic_zhe_descender", 'җ'), ("Cyrillic_che_descender", 'ҷ'),
+];
+
+// Символ keysym'а по имени; dead keys, `NoSymbol` и прочее непечатаемое — `None`.
+fn keysym_char(name: &str) -> Option<char> {
+    let mut chars = name.chars();
+    if let (Some(ch), None) = (chars.next(), chars.next()) {
+        return ch.is_ascii_graphic().then_some(ch);
+    }
+
+    // "U0451" — Unicode, "0x1000451" — Unicode keysym числом.
+    if let Some(hex) = name.strip_prefix('U')
+        && let Ok(code) = u32::from_str_radix(hex, 16)
+    {
+        return char::from_u32(code);
+    }
+    if let Some(hex) = name.strip_prefix("0x")
+        && let Ok(code) = u32::from_str_radix(hex, 16)
+    {
+        return match code {
+            0x0100_0000.. => char::from_u32(code - 0x0100_0000),
+            0x20..=0xFF => char::from_u32(code),
+            _ => None,
+        };
+    }
+
+    if let Some(ch) = lookup(name) {
+        return Some(ch);
+    }
+
+    // Заглавные: "Cyrillic_SHORTI", "Ukrainian_GHE_WITH_UPTURN", "Adiaeresis".
+    let lowered = match name.split_once('_') {
+        Some((prefix, suffix)) if suffix.chars().any(|c| c.is_uppercase()) => {
+            format!("{prefix}_{}", suffix.to_lowercase())
+        }
+        _ => {
+            let mut chars = name.chars();
+            let first = chars.next()?;
+            if !first.is_uppercase() {
+                return None;
+            }
+            first.to_lowercase().chain(chars).collect()
+        }
+    };
+    let lower = lookup(&lowered)?;
+    let mut upper = lower.to_uppercase();
+    match (upper.next(), upper.next()) {
+        (Some(ch), None) => Some(ch),
+        _ => None,
+    }
+}
+
+fn lookup(name: &str) -> Option<char> {
+    KEYSYM_NAMES
+        .iter()
+        .find(|&&(keysym, _)| keysym == name)
+        .map(|&(_, ch)| ch)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RU: &str = r#"
+// Windows layout
+default partial alphanumeric_keys
+xkb_symbols "winkeys" {
+    include "ru(common)"
+    name[Group1]= "Russian";
+    key <AE03> { [ 3, numerosign ] };
+    key <AB10> { [ period, comma ] };
+};
+
+hidden partial alphanumeric_keys
+xkb_symbols "common" {
+    key <AE03> { [ 3, numbersign ] };
+    key <TLDE> { [ Cyrillic_io, Cyrillic_IO ] };
+    key <AD01> { [ Cyrillic_shorti, Cyrillic_SHORTI ] };
+    key <AC10> { type[Group1]= "ALPHABETIC", symbols[Group1]= [ Cyrillic_zhe, Cyrillic_ZHE ] };
+    key <AB10> { [ slash, question ] };
+    include "kpdl(comma)"
+};
+
+partial alphanumeric_keys
+xkb_symbols "phonetic" {
+    include "ru(common)"
+    key <LatQ> { [ Cyrillic_ya, Cyrillic_YA ] };
+    key <AD02> { [ U0436, U0416 ] };   // ж
+};
+"#;
+
+    fn symbols_dir() -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("smart_switcher_xkb_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("ru"), RU).unwrap();
+        dir
+    }
+
+    #[test]
+    fn default_section_follows_includes_and_overrides() {
+        let dir = symbols_dir();
+        let keys = load_symbols(&dir, "ru").unwrap();
+
+        assert_eq!(keys[&Key::Backquote], ['ё', 'Ё']);
+        assert_eq!(keys[&Key::Q], ['й', 'Й']);
+        assert_eq!(keys[&Key::Semicolon], ['ж', 'Ж']);
+        assert_eq!(keys[&Key::Digit3], ['3', '№']);
+        assert_eq!(keys[&Key::Slash], ['.', ',']);
+
+        let phonetic = load_symbols(&dir, "ru(phonetic)").unwrap();
+        assert_eq!(phonetic[&Key::Q], ['я', 'Я']);
+        assert_eq!(phonetic[&Key::W], ['ж', 'Ж']);
+        assert_eq!(phonetic[&Key::Digit3], ['3', '#']);
+
+        assert!(load_symbols(&dir, "ru(missing)").is_err());
+        assert!(load_symbols(&dir, "xx").is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn keysym_names_map_to_chars() {
+        assert_eq!(keysym_char("q"), Some('q'));
+        assert_eq!(keysym_char("apostrophe"), Some('\''));
+        assert_eq!(keysym_char("Cyrillic_HARDSIGN"), Some('Ъ'));
+        assert_eq!(keysym_char("Ukrainian_GHE_WITH_UPTURN"), Some('Ґ'));
+        assert_eq!(keysym_char("Odiaeresis"), Some('Ö'));
+        assert_eq!(keysym_char("0x10004d9"), Some('ә'));
+        assert_eq!(keysym_char("dead_acute"), None);
+        assert_eq!(keysym_char("NoSymbol"), None);
+    }
+}
@@ -0,0 +1,80 @@
+use smart_switcher_shared_types::{Key, KeyboardEvent, Modifiers};
+
+// Клавиша действия из конфига с модификаторами: "pause", "shift+scrolllock".
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct KeyHotkey {
+    pub key: Key,
+    pub modifiers: Modifiers,
+}
+
+impl KeyHotkey {
+    // "" или неизвестная клавиша — None (действие выключено).
+    pub fn parse(hotkey: &str) -> Option<Self> {
+        let hotkey = hotkey.to_lowercase();
+        let mut parts: Vec<&str> = hotkey.split('+').map(str::trim).collect();
+        let key = match parts.pop()? {
+            "pause" | "break" => Key::Pause,
+            "scrolllock" => Key::ScrollLock,
+            _ => return None,
+        };
+
+        let mut modifiers = Modifiers::default();
+        for part in parts {
+            match part {
+                "shift" => modifiers.shift = true,
+                "ctrl" | "control" => modifiers.ctrl = true,
+                "alt" => modifiers.alt = true,
+                "meta" | "super" | "win" => modifiers.meta = true,
+                _ => return None,
+            }
+        }
+        Some(Self { key, modifiers })
+    }
+
+    // Модификаторы — точно как в конфиге: Shift+Pause не срабатывает как Pause.
+    pub fn matches(&self, event: &KeyboardEvent) -> bool {
+        event.is_key_down && event.key == self.key && event.modifiers == self.modifiers
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use smart_switcher_shared_types::KeyOrigin;
+
+    use super::*;
+
+    fn press(key: Key, modifiers: Modifiers) -> KeyboardEvent {
+        KeyboardEvent {
+            key,
+            modifiers,
+            text: None,
+            is_key_down: true,
+            origin: KeyOrigin::Physical,
+        }
+    }
+
+    #[test]
+    fn parses_key_with_modifiers() {
+        let shift = Modifiers {
+            shift: true,
+            ..Modifiers::default()
+        };
+        assert_eq!(
+            KeyHotkey::parse("Shift+ScrollLock"),
+            Some(KeyHotkey {
+                key: Key::ScrollLock,
+                modifiers: shift,
+            })
+        );
+        assert_eq!(KeyHotkey::parse("break").map(|h| h.key), Some(Key::Pause));
+        assert_eq!(KeyHotkey::parse(""), None);
+        assert_eq!(KeyHotkey::parse("hyper+pause"), None);
+        assert_eq!(KeyHotkey::parse("shift+a"), None);
+
+        let hotkey = KeyHotkey::parse("shift+scrolllock").unwrap();
+        assert!(hotkey.matches(&press(Key::ScrollLock, shift)));
+        assert!(!hotkey.matches(&press(Key::ScrollLock, Modifiers::default())));
+        let plain = KeyHotkey::parse("scrolllock").unwrap();
+        assert!(!plain.matches(&press(Key::ScrollLock, shift)));
+    }
+}
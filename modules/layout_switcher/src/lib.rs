@@ -1,19 +1,33 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
 use anyhow::Context;
 use async_trait::async_trait;
 use smart_switcher_core::{Module, ModuleContext, ModuleHandle};
-use smart_switcher_shared_types::{config::LayoutSwitcherConfig, AppEvent};
+use smart_switcher_detector::{
+    find_target_layout, Action, Decision, DetectionContext, Detector, Exception, Exceptions,
+};
+use smart_switcher_layouts::{KeyboardLayout, LayoutTables};
+use smart_switcher_platform::{ClipboardShortcut, ClipboardState, Platform, Selection};
+use smart_switcher_shared_types::{
+    config::LayoutSwitcherConfig, AppEvent, KeyOrigin, LayoutId, Modifiers, Script,
+};
 use tracing::{debug, info, warn};
 
-fn is_short_en_to_ru_allowlisted(typed: &str) -> bool {
-    // Намеренно минимальный allowlist для самых частых коротких слов,
-    // чтобы не увеличивать ложные срабатывания.
-    // 2 буквы: yt->не, yf->на, bp->из, jn->от, pf->за, dc->во, lj->до
-    // 4 буквы: dctv->всем, tcnm->есть
-    matches!(
-        typed.to_ascii_lowercase().as_str(),
-        "yt" | "yf" | "bp" | "jn" | "pf" | "dc" | "lj" | "dctv" | "tcnm"
-    )
-}
+mod hotkey;
+mod selection;
+mod undo;
+mod words;
+
+use hotkey::KeyHotkey;
+use undo::LastCorrection;
+use words::{RecentWords, TypedWord};
+
+// Сколько ждём, пока приложение скопирует выделенное в буфер после Ctrl+C.
+const COPY_POLL_ATTEMPTS: usize = 10;
+const COPY_POLL_INTERVAL: Duration = Duration::from_millis(20);
+// Приложение читает буфер после Ctrl+V не сразу — раньше буфер не возвращаем.
+const PASTE_DELAY: Duration = Duration::from_millis(100);
 
 pub struct LayoutSwitcherModule {
     config: LayoutSwitcherConfig,
@@ -33,12 +47,22 @@ impl Module for LayoutSwitcherModule {
 
     async fn start(&self, ctx: ModuleContext) -> anyhow::Result<ModuleHandle> {
         let mut rx = ctx.bus.subscribe();
+        let bus = ctx.bus.clone();
         let config = self.config.clone();
         let platform = ctx.platform.clone();
+        let tables = ctx.layouts.clone();
+        let us = tables.get("us").cloned().context("keyboard layout table 'us' is missing")?;
+        // Буфер слова хранит клавиши по US QWERTY (`Key::us_char`), их и оценивает детектор.
+        let mut detector = Detector::new(ctx.layouts.clone(), config.confidence)?;
+        let exceptions_path = exceptions_path(&config.exceptions_file);
+        if let Some(path) = &exceptions_path {
+            match Exceptions::load(path) {
+                Ok(exceptions) => *detector.exceptions_mut() = exceptions,
+                Err(e) => warn!(error = %e, "exceptions are not loaded, using defaults"),
+            }
+        }
 
         let join = tokio::spawn(async move {
-            let min_autocorrect_len = 5usize;
-
             info!("✅ layout_switcher запущен");
             info!("   Hotkey: {} (переключение делает Windows)", config.hotkey);
             info!(
@@ -46,8 +70,7 @@ impl Module for LayoutSwitcherModule {
                 if config.auto_detect { "включено" } else { "выключено" }
             );
             if config.auto_detect {
-                info!("   Порог детекта (минимум клавиш): {}", config.detect_threshold);
-                info!("   Мин. длина слова для автоисправления: {}", min_autocorrect_len);
+                info!("   Уверенность модели для слов вне словаря: {}", config.confidence);
             }
             info!("   Для теста: набери 'ghbdtn' + пробел в любом поле ввода (EN раскладка)");
 
@@ -56,71 +79,79 @@ impl Module for LayoutSwitcherModule {
                 warn!(hotkey = %config.hotkey, "unsupported hotkey, only alt+shift is supported in MVP");
             }
 
-            let mut is_alt_down = false;
-            let mut is_shift_down = false;
+            let undo_key = KeyHotkey::parse(&config.undo_hotkey);
+            if undo_key.is_some() {
+                info!("   Отмена исправления: {}", config.undo_hotkey);
+            } else if !config.undo_hotkey.is_empty() {
+                warn!(hotkey = %config.undo_hotkey, "unsupported undo hotkey, undo is disabled");
+            }
+            let convert_key = KeyHotkey::parse(&config.convert_hotkey);
+            if convert_key.is_some() {
+                info!("   Конвертация последних слов: {}", config.convert_hotkey);
+            } else if !config.convert_hotkey.is_empty() {
+                warn!(hotkey = %config.convert_hotkey, "unsupported convert hotkey, manual conversion is disabled");
+            }
+            let selection_key = KeyHotkey::parse(&config.convert_selection_hotkey);
+            if selection_key.is_some() {
+                info!("   Конвертация выделенного: {}", config.convert_selection_hotkey);
+            } else if !config.convert_selection_hotkey.is_empty() {
+                warn!(
+                    hotkey = %config.convert_selection_hotkey,
+                    "unsupported convert selection hotkey, selection conversion is disabled"
+                );
+            }
+
             let mut hotkey_fired = false;
 
-            let mut word_keys: Vec<char> = Vec::new();
+            // Набираемое слово из событий декодера — клавиши по US QWERTY.
+            let mut word_keys = String::new();
+            // Последнее исправление, пока его ещё можно отменить.
+            let mut last_correction: Option<LastCorrection> = None;
+            // Исправление, которое пользователь стёр сам: если он наберёт то же слово
+            // снова, исправление было ошибкой.
+            let mut erased_correction: Option<LastCorrection> = None;
+            // Слова перед курсором — для конвертации вручную.
+            let mut words = RecentWords::default();
+            let mut manual: Option<ManualConversion> = None;
+            // Нажата клавиша конвертации выделенного, ждём отпускания модификаторов.
+            let mut selection_pending = false;
 
-            let is_letter_vk = |vk: u32| (0x41..=0x5A).contains(&vk);
-            let vk_to_letter = |vk: u32, shift: bool| {
-                let base = (vk as u8 as char).to_ascii_lowercase();
-                if shift {
-                    base.to_ascii_uppercase()
-                } else {
-                    base
+            loop {
+                let event = rx.recv().await.context("event bus recv")?;
+                if let AppEvent::ShutdownRequested = event {
+                    info!("⏹️  layout_switcher остановлен");
+                    break;
                 }
-            };
-
-            let is_all_upper_ascii = |s: &str| {
-                let mut has_letters = false;
-                for ch in s.chars() {
-                    if ch.is_ascii_alphabetic() {
-                        has_letters = true;
-                        if !ch.is_ascii_uppercase() {
-                            return false;
-                        }
-                    }
+                if hotkey != "alt+shift" {
+                    continue;
                 }
-                has_letters
-            };
 
-            let is_mixed_case_ascii = |s: &str| {
-                let mut has_lower = false;
-                let mut has_upper = false;
-                for ch in s.chars() {
-                    if ch.is_ascii_lowercase() {
-                        has_lower = true;
-                    } else if ch.is_ascii_uppercase() {
-                        has_upper = true;
-                    }
-                }
-                has_lower && has_upper
-            };
-
-            let map_en_to_ru = |ch: char| -> char { map_en_to_ru(ch) };
-
-            let is_alt_vk = |vk: u32| matches!(vk, 0x12 | 0xA4 | 0xA5);
-            let is_shift_vk = |vk: u32| matches!(vk, 0x10 | 0xA0 | 0xA1);
-
-            loop {
-                match rx.recv().await.context("event bus recv")? {
-                    AppEvent::ShutdownRequested => {
-                        info!("⏹️  layout_switcher остановлен");
-                        break;
-                    }
+                match event {
                     AppEvent::Keyboard(ev) => {
-                        if hotkey != "alt+shift" {
+                        // Свои вставленные нажатия — не действия пользователя: иначе, например,
+                        // Backspace от конвертации обрывал бы серию нажатий.
+                        if ev.origin == KeyOrigin::OwnInjection {
                             continue;
                         }
 
-                        if is_alt_vk(ev.vk_code) {
-                            is_alt_down = ev.is_key_down;
-                        }
-                        if is_shift_vk(ev.vk_code) {
-                            is_shift_down = ev.is_key_down;
+                        // Конвертируем, когда отпущены все модификаторы сочетания:
+                        // иначе наш Ctrl+C придёт в приложение как, например, Ctrl+Shift+C.
+                        if selection_pending && !ev.is_key_down && ev.modifiers == Modifiers::default() {
+                            selection_pending = false;
+                            convert_selection(&platform, &config, &tables).await;
+                            // Текст перед курсором теперь другой.
+                            bus.send(AppEvent::TextReplaced);
+                            word_keys.clear();
+                            words.clear();
+                            manual = None;
+                            last_correction = None;
+                            erased_correction = None;
+                            continue;
                         }
 
+                        let is_alt_down = ev.modifiers.alt;
+                        let is_shift_down = ev.modifiers.shift;
+
                         if !ev.is_key_down {
                             if !(is_alt_down && is_shift_down) {
                                 hotkey_fired = false;
@@ -136,7 +167,8 @@ impl Module for LayoutSwitcherModule {
                             info!("⌨️ Alt+Shift: переключение делает Windows");
                         }
 
-                        if !config.auto_detect {
+                        if selection_key.is_some_and(|h| h.matches(&ev)) {
+                            selection_pending = true;
                             continue;
                         }
 
@@ -144,216 +176,273 @@ impl Module for LayoutSwitcherModule {
                             continue;
                         }
 
-                        match ev.vk_code {
-                            0x08 => {
-                                // Backspace
-                                word_keys.pop();
-                            }
-                            0x20 => {
-                                // Space
-                                let typed: String = word_keys.iter().collect();
-                                
-                                // Проверяем длину только если слово НЕ в allowlist
-                                let meets_threshold = word_keys.len() >= config.detect_threshold as usize
-                                    || is_short_en_to_ru_allowlisted(&typed);
-                                
-                                if meets_threshold {
-                                    // Fail-closed: никаких действий в запрещённых контекстах.
-                                    // Сразу выходим, чтобы не "подвешивать" эвристики в терминалах/менеджерах паролей.
-                                    match platform.is_forbidden_context(&config.forbidden_contexts) {
-                                        Ok(true) => {
-                                            debug!("auto-correct skipped (forbidden context)");
-                                            word_keys.clear();
-                                            continue;
-                                        }
-                                        Ok(false) => {}
-                                        Err(e) => {
-                                            debug!(error = %e, "auto-correct skipped (forbidden context check failed)");
-                                            word_keys.clear();
-                                            continue;
-                                        }
-                                    }
-
-                                    let lang = platform.get_active_lang_id().unwrap_or(0);
-                                    let commit_is_cyrillic = is_cyrillic_lang_id(lang);
-                                    let commit_is_latin = !commit_is_cyrillic;
-
-                                    debug!(
-                                        word = %typed,
-                                        lang = format_args!("0x{lang:04X}"),
-                                        commit_is_latin,
-                                        commit_is_cyrillic,
-                                        "space commit"
-                                    );
-
-                                    // Консервативный фильтр: не трогаем короткие слова и акронимы.
-                                    if (typed.len() < min_autocorrect_len
-                                        && !is_short_en_to_ru_allowlisted(&typed))
-                                        || is_all_upper_ascii(&typed)
-                                        || is_mixed_case_ascii(&typed)
-                                    {
-                                        debug!(
-                                            word = %typed,
-                                            lang = format_args!("0x{lang:04X}"),
-                                            "auto-correct skipped (filter)"
+                        if undo_key.is_some_and(|h| h.matches(&ev)) {
+                            // После отмены на экране другой текст — недонабранное слово не исправляем.
+                            word_keys.clear();
+                            words.clear();
+                            manual = None;
+                            match last_correction.take() {
+                                Some(correction) => {
+                                    undo_correction(&platform, &config, &tables, &us, &correction);
+                                    bus.send(AppEvent::TextReplaced);
+                                    // Исправленная фраза — каждое её слово.
+                                    for word in correction.original.split_whitespace() {
+                                        learn(
+                                            &mut detector,
+                                            exceptions_path.as_deref(),
+                                            word,
+                                            correction.app.as_deref(),
+                                            Exception::Never,
                                         );
-                                        word_keys.clear();
-                                        continue;
                                     }
+                                }
+                                None => debug!("undo: nothing to undo"),
+                            }
+                            continue;
+                        }
 
-                                    if commit_is_latin {
-                                        // EN (0x0409) -> RU (0x0419)
-                                        let converted: String = typed.chars().map(map_en_to_ru).collect();
-
-                                        if should_autocorrect_en_to_ru(&typed, &converted) {
-                                            match platform.set_layout_by_lang_id(
-                                                &config.forbidden_contexts,
-                                                0x0419,
-                                            ) {
-                                                Ok(true) => debug!("set layout RU: ok"),
-                                                Ok(false) => debug!("set layout RU: skipped/failed"),
-                                                Err(e) => debug!(error = %e, "set layout RU: error"),
-                                            }
-                                            // +1 для стирания пробела, который уже попал в поле
-                                            let erased = match platform.send_backspaces(
-                                                &config.forbidden_contexts,
-                                                word_keys.len() + 1,
-                                            ) {
-                                                Ok(v) => v,
-                                                Err(e) => {
-                                                    debug!(error = %e, "send_backspaces failed");
-                                                    false
-                                                }
-                                            };
-                                            if erased {
-                                                // Вставляем исправленный текст + пробел
-                                                let text_with_space = format!("{} ", converted);
-                                                let injected = match platform.send_unicode_text(
-                                                    &config.forbidden_contexts,
-                                                    &text_with_space,
-                                                ) {
-                                                    Ok(v) => v,
-                                                    Err(e) => {
-                                                        debug!(error = %e, "send_unicode_text failed");
-                                                        false
-                                                    }
-                                                };
-                                                if injected {
-                                                    info!("🔤 Исправлено EN→RU: '{}' → '{}'", typed, converted);
-                                                } else {
-                                                    debug!("send_unicode_text returned false");
-                                                }
-                                            } else {
-                                                debug!("send_backspaces returned false");
-                                            }
-                                        } else {
-                                            debug!(
-                                                word = %typed,
-                                                converted = %converted,
-                                                lang = format_args!("0x{lang:04X}"),
-                                                "auto-correct skipped (heuristic EN→RU)"
-                                            );
-                                        }
-                                    } else if commit_is_cyrillic {
-                                        // RU (0x0419) -> EN (0x0409)
-                                        // Тут `typed` — это физические латинские клавиши.
-                                        // Если пользователь хотел английское слово, оно уже находится в `typed`.
-                                        let would_be_ru: String = typed.chars().map(map_en_to_ru).collect();
-
-                                        // Если то, что видно на экране, выглядит как нормальное русское слово — не трогаем.
-                                        // Исправляем только когда "экранное RU" выглядит как мусор, а `typed` похоже на EN.
-                                        if should_autocorrect_ru_to_en(&typed, &would_be_ru) {
-                                            match platform.set_layout_by_lang_id(
-                                                &config.forbidden_contexts,
-                                                0x0409,
-                                            ) {
-                                                Ok(true) => debug!("set layout EN: ok"),
-                                                Ok(false) => debug!("set layout EN: skipped/failed"),
-                                                Err(e) => debug!(error = %e, "set layout EN: error"),
-                                            }
-                                            // +1 для стирания пробела
-                                            let erased = match platform.send_backspaces(
-                                                &config.forbidden_contexts,
-                                                word_keys.len() + 1,
-                                            ) {
-                                                Ok(v) => v,
-                                                Err(e) => {
-                                                    debug!(error = %e, "send_backspaces failed");
-                                                    false
-                                                }
-                                            };
-                                            if erased {
-                                                let text_with_space = format!("{} ", typed);
-                                                let injected = match platform.send_unicode_text(
-                                                    &config.forbidden_contexts,
-                                                    &text_with_space,
-                                                ) {
-                                                    Ok(v) => v,
-                                                    Err(e) => {
-                                                        debug!(error = %e, "send_unicode_text failed");
-                                                        false
-                                                    }
-                                                };
-                                                if injected {
-                                                    info!("🔤 Исправлено RU→EN: набрано в RU раскладке, исправлено на '{}'", typed);
-                                                } else {
-                                                    debug!("send_unicode_text returned false");
-                                                }
-                                            } else {
-                                                debug!("send_backspaces returned false");
-                                            }
-                                        } else {
-                                            debug!(
-                                                word = %typed,
-                                                would_be_ru = %would_be_ru,
-                                                lang = format_args!("0x{lang:04X}"),
-                                                "auto-correct skipped (heuristic RU→EN)"
-                                            );
-                                        }
-                                    } else {
-                                        debug!(
-                                            word = %typed,
-                                            lang = format_args!("0x{lang:04X}"),
-                                            "auto-correct skipped (unknown layout class)"
-                                        );
+                        if convert_key.is_some_and(|h| h.matches(&ev)) {
+                            if !word_keys.is_empty() {
+                                // Курсор сразу за словом — конвертируем и его.
+                                let keys = std::mem::take(&mut word_keys);
+                                match platform.active_layout() {
+                                    Ok(active) => words.push(keys, active),
+                                    Err(e) => {
+                                        debug!(error = %e, "convert: active layout unknown");
+                                        words.clear();
                                     }
-                                } else {
-                                    // Слово не прошло порог detect_threshold и не в allowlist
-                                    debug!(
-                                        word = %typed,
-                                        len = word_keys.len(),
-                                        threshold = config.detect_threshold,
-                                        "auto-correct skipped (length threshold)"
-                                    );
                                 }
-
-                                word_keys.clear();
                             }
-                            0x0D => {
-                                // Enter
-                                // Консервативно: НЕ автоисправляем на Enter, чтобы не ломать переносы строк
-                                // (в разных приложениях это может быть \n или \r\n).
-                                word_keys.clear();
+                            last_correction = None;
+                            erased_correction = None;
+                            let converted = convert_last_words(
+                                &platform,
+                                &config,
+                                &tables,
+                                &us,
+                                &mut words,
+                                &mut manual,
+                            );
+                            // Недонабранное слово модуль забрал себе — декодер его больше не ведёт.
+                            bus.send(AppEvent::TextReplaced);
+                            // Сконвертировал сам — значит, так и надо; откатил авто-исправление — не надо.
+                            if let (Some(word), Some(conversion)) = (converted, &manual) {
+                                let (text, exception) = if word.auto_corrected {
+                                    (word.text_in(&tables, &us, &conversion.target), Exception::Never)
+                                } else {
+                                    (word.text_in(&tables, &us, &word.layout), Exception::Always)
+                                };
+                                learn(
+                                    &mut detector,
+                                    exceptions_path.as_deref(),
+                                    &text,
+                                    focused_app(&platform, &config).as_deref(),
+                                    exception,
+                                );
                             }
-                            0xBC => {
-                                // VK_OEM_COMMA: в RU раскладке это буква 'б'
-                                word_keys.push(',');
+                            continue;
+                        }
+                        if !ev.key.is_modifier() {
+                            manual = None;
+                        }
+                    }
+                    AppEvent::WordEdited { raw_keys } => {
+                        // Обычно одна клавиша, но Ctrl+Backspace стирает слово целиком.
+                        let common = word_keys
+                            .chars()
+                            .zip(raw_keys.chars())
+                            .take_while(|(old, new)| old == new)
+                            .count();
+                        for _ in common..word_keys.chars().count() {
+                            correction_erased(&mut last_correction, &mut erased_correction);
+                        }
+                        for ch in raw_keys.chars().skip(common) {
+                            correction_typed(&mut last_correction, ch);
+                        }
+                        word_keys = raw_keys;
+                    }
+                    AppEvent::WordReopened { raw_keys } => {
+                        // Стёрт пробел за словом — слово снова набирается.
+                        words.backspace();
+                        words.resume();
+                        correction_erased(&mut last_correction, &mut erased_correction);
+                        word_keys = raw_keys;
+                    }
+                    AppEvent::CharErased => {
+                        // Без пробелов за последним словом Backspace попадает в само слово.
+                        words.backspace();
+                        correction_erased(&mut last_correction, &mut erased_correction);
+                    }
+                    AppEvent::DelimiterTyped { delimiter: ' ' } => {
+                        correction_typed(&mut last_correction, ' ');
+                        erased_correction = None;
+                        words.space();
+                    }
+                    AppEvent::WordCommitted {
+                        raw_keys: typed,
+                        layout: active,
+                        delimiter: ' ',
+                        ..
+                    } => {
+                        correction_typed(&mut last_correction, ' ');
+                        let erased = erased_correction.take();
+                        word_keys.clear();
+                        words.push(typed.clone(), active.clone());
+                        words.space();
+
+                        if !config.auto_detect {
+                            continue;
+                        }
+
+                        // Fail-closed: никаких действий в запрещённых контекстах.
+                        // Сразу выходим, чтобы не "подвешивать" детектор в терминалах/менеджерах паролей.
+                        match platform.is_forbidden_context(&config.forbidden_contexts) {
+                            Ok(true) => {
+                                debug!("auto-correct skipped (forbidden context)");
+                                continue;
                             }
-                            0xBE => {
-                                // VK_OEM_PERIOD: в RU раскладке это буква 'ю'
-                                word_keys.push('.');
+                            Ok(false) => {}
+                            Err(e) => {
+                                debug!(error = %e, "auto-correct skipped (forbidden context check failed)");
+                                continue;
                             }
-                            vk if is_letter_vk(vk) => {
-                                // letters: collect physical key as latin char
-                                let ch = vk_to_letter(vk, is_shift_down);
-                                word_keys.push(ch);
+                        }
+
+                        let context = DetectionContext {
+                            active,
+                            installed: platform.list_layouts().unwrap_or_default(),
+                            app: focused_app(&platform, &config),
+                        };
+
+                        let decision = detector.evaluate(&typed, &context);
+                        debug!(
+                            word = %typed,
+                            layout = %context.active,
+                            action = ?decision.action,
+                            confidence = decision.confidence,
+                            reasons = ?decision.reasons,
+                            "space commit"
+                        );
+                        if decision.action != Action::Correct {
+                            continue;
+                        }
+
+                        // Стёрли исправление и набрали то же слово заново — оставляем как есть.
+                        if let Some(erased) = erased
+                            && erased.original.split_whitespace().last()
+                                == Some(decision.original.as_str())
+                        {
+                            info!("✋ '{}' набрано заново после исправления — больше не исправляем", typed);
+                            learn(
+                                &mut detector,
+                                exceptions_path.as_deref(),
+                                &decision.original,
+                                context.app.as_deref(),
+                                Exception::Never,
+                            );
+                            continue;
+                        }
+
+                        let phrase =
+                            phrase_before(&detector, &words, &context, config.phrase_words);
+
+                        match &decision.target {
+                            Some(target) => match platform.set_layout(
+                                &config.forbidden_contexts,
+                                target,
+                            ) {
+                                Ok(true) => debug!(layout = %target, "set layout: ok"),
+                                Ok(false) => debug!(layout = %target, "set layout: skipped/failed"),
+                                Err(e) => debug!(error = %e, "set layout: error"),
+                            },
+                            // Текст всё равно исправляем — раскладку пользователь переключит сам.
+                            None => debug!("set layout: no installed target layout"),
+                        }
+                        // Слова фразы вместе с пробелами после них и +1 для пробела,
+                        // который уже попал в поле.
+                        let phrase_original: String = phrase
+                            .iter()
+                            .map(|(d, spaces)| with_spaces(&d.original, *spaces))
+                            .collect();
+                        let phrase_replacement: String = phrase
+                            .iter()
+                            .map(|(d, spaces)| with_spaces(&d.replacement, *spaces))
+                            .collect();
+                        let erased = match platform.send_backspaces(
+                            &config.forbidden_contexts,
+                            phrase_original.chars().count() + typed.chars().count() + 1,
+                        ) {
+                            Ok(v) => v,
+                            Err(e) => {
+                                debug!(error = %e, "send_backspaces failed");
+                                false
                             }
-                            _ => {
-                                // delimiter / control
-                                word_keys.clear();
+                        };
+                        if !erased {
+                            debug!("send_backspaces returned false");
+                            continue;
+                        }
+                        // Вставляем исправленный текст + пробел
+                        let text_with_space =
+                            format!("{}{} ", phrase_replacement, decision.replacement);
+                        let injected = match platform.send_unicode_text(
+                            &config.forbidden_contexts,
+                            &text_with_space,
+                        ) {
+                            Ok(v) => v,
+                            Err(e) => {
+                                debug!(error = %e, "send_unicode_text failed");
+                                false
                             }
+                        };
+                        // Текст перед курсором уже не тот, что набран.
+                        bus.send(AppEvent::TextReplaced);
+                        if injected {
+                            info!(
+                                "🔤 Исправлено {}: '{}{}' → '{}'",
+                                direction(&context.active),
+                                phrase_original,
+                                typed,
+                                text_with_space.trim_end()
+                            );
+                            match &decision.target {
+                                Some(target) => words.auto_corrected(phrase.len() + 1, target),
+                                // Чем показаны слова на экране, неизвестно.
+                                None => words.clear(),
+                            }
+                            last_correction = Some(LastCorrection::new(
+                                context.active.clone(),
+                                context.app.clone(),
+                                format!("{}{} ", phrase_original, decision.original),
+                                text_with_space,
+                            ));
+                        } else {
+                            debug!("send_unicode_text returned false");
+                            words.clear();
                         }
                     }
+                    // Enter, цифры, знаки, стрелки: консервативно не исправляем — переносы строк
+                    // в разных приложениях разные, а после стрелок стирать вслепую уже нельзя.
+                    AppEvent::WordCommitted { .. }
+                    | AppEvent::DelimiterTyped { .. }
+                    | AppEvent::LineCommitted { .. }
+                    | AppEvent::BufferReset => {
+                        word_keys.clear();
+                        words.clear();
+                        last_correction = None;
+                        erased_correction = None;
+                    }
+                    // Слова и исправление — из прошлого окна или от места до клика: стирать
+                    // их вслепую нельзя. Недонабранное слово декодер помнит для каждого окна
+                    // и пришлёт снова, когда набор продолжится; после клика — забывает.
+                    AppEvent::FocusChanged { .. } | AppEvent::PointerActivity => {
+                        word_keys.clear();
+                        words.clear();
+                        manual = None;
+                        last_correction = None;
+                        erased_correction = None;
+                    }
+                    _ => {}
                 }
             }
 
@@ -364,267 +453,383 @@ impl Module for LayoutSwitcherModule {
     }
 }
 
-fn primary_lang_id(lang_id: u16) -> u16 {
-    lang_id & 0x03FF
-}
-
-fn is_cyrillic_lang_id(lang_id: u16) -> bool {
-    matches!(primary_lang_id(lang_id), 0x0019 | 0x0022 | 0x0023)
-}
-
-fn is_ascii_layout_keys(s: &str) -> bool {
-    !s.is_empty()
-        && s.chars()
-            .all(|c| c.is_ascii_alphabetic() || matches!(c, ',' | '.'))
-}
-
-fn map_en_to_ru(ch: char) -> char {
-    match ch.to_ascii_lowercase() {
-        'q' => 'й',
-        'w' => 'ц',
-        'e' => 'у',
-        'r' => 'к',
-        't' => 'е',
-        'y' => 'н',
-        'u' => 'г',
-        'i' => 'ш',
-        'o' => 'щ',
-        'p' => 'з',
-        'a' => 'ф',
-        's' => 'ы',
-        'd' => 'в',
-        'f' => 'а',
-        'g' => 'п',
-        'h' => 'р',
-        'j' => 'о',
-        'k' => 'л',
-        'l' => 'д',
-        'z' => 'я',
-        'x' => 'ч',
-        'c' => 'с',
-        'v' => 'м',
-        'b' => 'и',
-        'n' => 'т',
-        'm' => 'ь',
-        ',' => 'б',
-        '.' => 'ю',
-        other => other,
+// Файл исключений: из конфига или в данных пользователя
+// (%APPDATA%\smart_switcher, $XDG_DATA_HOME/smart_switcher, ~/.local/share/smart_switcher).
+fn exceptions_path(configured: &str) -> Option<PathBuf> {
+    if !configured.is_empty() {
+        return Some(PathBuf::from(configured));
     }
-}
-
-fn en_vowel_ratio(s: &str) -> f32 {
-    let mut vowels = 0usize;
-    let mut letters = 0usize;
-
-    for ch in s.chars() {
-        if ch.is_ascii_alphabetic() {
-            letters += 1;
-            if matches!(
-                ch,
-                'a' | 'e' | 'i' | 'o' | 'u' | 'y' | 'A' | 'E' | 'I' | 'O' | 'U' | 'Y'
-            ) {
-                vowels += 1;
-            }
-        }
-    }
-
-    if letters == 0 {
-        0.0
+    let env_dir = |name: &str| std::env::var_os(name).filter(|v| !v.is_empty()).map(PathBuf::from);
+    let data_dir = if cfg!(windows) {
+        env_dir("APPDATA")
     } else {
-        vowels as f32 / letters as f32
+        env_dir("XDG_DATA_HOME").or_else(|| env_dir("HOME").map(|home| home.join(".local/share")))
+    };
+    match data_dir {
+        Some(dir) => Some(dir.join("smart_switcher").join("exceptions.toml")),
+        None => {
+            warn!("no user data dir, learned exceptions will not be saved");
+            None
+        }
     }
 }
 
-fn ru_vowel_ratio(s: &str) -> f32 {
-    let mut vowels = 0usize;
-    let mut letters = 0usize;
-
-    for ch in s.chars() {
-        if ch.is_alphabetic() {
-            letters += 1;
-        }
-        if matches!(
-            ch,
-            'а' | 'е' | 'ё' | 'и' | 'о' | 'у' | 'ы' | 'э' | 'ю' | 'я'
-                | 'А' | 'Е' | 'Ё' | 'И' | 'О' | 'У' | 'Ы' | 'Э' | 'Ю' | 'Я'
-        ) {
-            vowels += 1;
+// Процесс окна в фокусе, если исключения ведутся по приложениям.
+fn focused_app(platform: &Platform, config: &LayoutSwitcherConfig) -> Option<String> {
+    if !config.exceptions_per_app {
+        return None;
+    }
+    match platform.active_window() {
+        Ok(window) => window.and_then(|w| w.process_name).map(|name| name.to_lowercase()),
+        Err(e) => {
+            debug!(error = %e, "active window unknown, using global exceptions");
+            None
         }
     }
+}
 
-    if letters == 0 {
-        0.0
-    } else {
-        vowels as f32 / letters as f32
+// Запоминает исключение и сразу сохраняет файл.
+fn learn(
+    detector: &mut Detector,
+    path: Option<&Path>,
+    word: &str,
+    app: Option<&str>,
+    exception: Exception,
+) {
+    if !detector.exceptions_mut().learn(word, app, exception) {
+        return;
     }
+    let scope = app.map(|app| format!(" ({app})")).unwrap_or_default();
+    info!("📝 Исключение {:?}: '{}'{}", exception, word, scope);
+    if let Some(path) = path
+        && let Err(e) = detector.exceptions().save(path)
+    {
+        warn!(error = %e, "failed to save exceptions");
+    }
+}
+
+// Слова прямо перед исправляемым, набранные в той же раскладке и не исправленные:
+// раз следующее слово набрано не в той раскладке, они, скорее всего, тоже.
+// Идём назад, пока детектор согласен; возвращает решения и пробелы после слов, от старых к новым.
+fn phrase_before(
+    detector: &Detector,
+    words: &RecentWords,
+    context: &DetectionContext,
+    limit: usize,
+) -> Vec<(Decision, usize)> {
+    // Последнее слово — то, что сейчас исправляется.
+    let mut phrase: Vec<(Decision, usize)> = words
+        .iter()
+        .rev()
+        .skip(1)
+        .take(limit)
+        .take_while(|word| word.layout == context.active && !word.auto_corrected)
+        .map(|word| (detector.evaluate_in_phrase(&word.keys, context), word.spaces))
+        .take_while(|(decision, _)| decision.action == Action::Correct)
+        .collect();
+    phrase.reverse();
+    phrase
 }
 
-fn looks_like_english_word(typed: &str) -> bool {
-    if !is_ascii_layout_keys(typed) {
-        return false;
+// Напечатан символ после исправления; слишком далеко — отменять уже поздно.
+fn correction_typed(last: &mut Option<LastCorrection>, ch: char) {
+    if let Some(correction) = last.as_mut()
+        && !correction.key_typed(ch)
+    {
+        *last = None;
     }
+}
 
-    let ratio = en_vowel_ratio(typed);
-    if ratio < 0.15 || ratio > 0.70 {
-        return false;
+// Стёрт символ после исправления; стёрли само исправление — запоминаем его.
+fn correction_erased(last: &mut Option<LastCorrection>, erased: &mut Option<LastCorrection>) {
+    if let Some(correction) = last.as_mut()
+        && !correction.backspace()
+    {
+        *erased = last.take();
     }
+}
 
-    // Небольшой бонус к уверенности: частые EN биграммы.
-    let lower = typed.to_ascii_lowercase();
-    ["th", "sh", "ch", "ck", "qu", "ng", "oo", "ee"]
-        .iter()
-        .any(|b| lower.contains(b))
-        || ratio >= 0.25
+fn with_spaces(word: &str, spaces: usize) -> String {
+    format!("{}{}", word, " ".repeat(spaces))
 }
 
-fn has_strong_english_bigrams(typed: &str) -> bool {
-    let lower = typed.to_ascii_lowercase();
-    ["th", "sh", "ch", "ck", "qu", "ng", "oo", "ee"]
-        .iter()
-        .any(|b| lower.contains(b))
+// Серия нажатий клавиши конвертации: сколько последних слов уже сконвертировано и куда.
+struct ManualConversion {
+    count: usize,
+    target: LayoutId,
 }
 
-fn should_autocorrect_en_to_ru(typed: &str, converted: &str) -> bool {
-    if !is_ascii_layout_keys(typed) {
-        return false;
+// Конвертирует на одно слово больше, чем прошлым нажатием серии, и переключает раскладку —
+// без детектора. Возвращает слово, добавленное этим нажатием, в виде до конвертации.
+fn convert_last_words(
+    platform: &Platform,
+    config: &LayoutSwitcherConfig,
+    tables: &LayoutTables,
+    us: &KeyboardLayout,
+    words: &mut RecentWords,
+    manual: &mut Option<ManualConversion>,
+) -> Option<TypedWord> {
+    let count = manual.as_ref().map_or(1, |m| m.count + 1);
+    let Some(word) = words.nth_back(count).cloned() else {
+        debug!(count, "convert: no more words before the cursor");
+        return None;
+    };
+
+    let target = match manual.as_ref() {
+        Some(m) => m.target.clone(),
+        None => {
+            let installed = platform.list_layouts().unwrap_or_default();
+            let target = match word.layout.script {
+                Script::Latin => find_target_layout(&installed, Script::Cyrillic, "ru"),
+                Script::Cyrillic => find_target_layout(&installed, Script::Latin, "en"),
+                Script::Other => None,
+            };
+            let Some(target) = target else {
+                debug!(layout = %word.layout, "convert: no layout to convert to");
+                return None;
+            };
+            target
+        }
+    };
+    let Some(target_table) = tables.find(&target) else {
+        debug!(layout = %target, "convert: no table for target layout");
+        return None;
+    };
+    let plan = words.plan(count, us, target_table);
+
+    match platform.set_layout(&config.forbidden_contexts, &target) {
+        Ok(true) => debug!(layout = %target, "convert: set layout ok"),
+        Ok(false) => debug!(layout = %target, "convert: set layout skipped/failed"),
+        Err(e) => debug!(error = %e, "convert: set layout error"),
     }
-    
-    // Ранний проход для коротких слов из allowlist (минуя эвристики)
-    if is_short_en_to_ru_allowlisted(typed) {
-        return true;
+    match platform.send_backspaces(&config.forbidden_contexts, plan.backspaces) {
+        Ok(true) => {}
+        Ok(false) => {
+            debug!("convert: send_backspaces returned false");
+            return None;
+        }
+        Err(e) => {
+            debug!(error = %e, "convert: send_backspaces failed");
+            return None;
+        }
     }
-    
-    if looks_like_english_word(typed) {
-        return false;
+    match platform.send_unicode_text(&config.forbidden_contexts, &plan.text) {
+        Ok(true) => {}
+        Ok(false) => {
+            debug!("convert: send_unicode_text returned false");
+            words.clear();
+            return None;
+        }
+        Err(e) => {
+            debug!(error = %e, "convert: send_unicode_text failed");
+            words.clear();
+            return None;
+        }
     }
 
-    // Если в русском варианте есть "нормальная" гласность — это хороший сигнал,
-    // что пользователь хотел русское слово.
-    ru_vowel_ratio(converted) >= 0.20
+    info!("🔁 Сконвертировано слов: {} → '{}'", count, plan.text.trim_end());
+    words.converted(count, &target);
+    *manual = Some(ManualConversion { count, target });
+    Some(word)
 }
 
-fn should_autocorrect_ru_to_en(typed: &str, would_be_ru: &str) -> bool {
-    if !is_ascii_layout_keys(typed) {
-        return false;
-    }
-    if !looks_like_english_word(typed) {
-        return false;
+// Выделенный текст: копируем его сами (Ctrl+C), а если приложение ничего не скопировало —
+// берём PRIMARY. Что приложение скопировало, видно по смене номера содержимого буфера.
+async fn copy_selection(
+    platform: &Platform,
+    config: &LayoutSwitcherConfig,
+    clipboard: Option<ClipboardState>,
+) -> Option<String> {
+    if let Some(before) = clipboard
+        && matches!(
+            platform.send_clipboard_shortcut(&config.forbidden_contexts, ClipboardShortcut::Copy),
+            Ok(true)
+        )
+    {
+        for _ in 0..COPY_POLL_ATTEMPTS {
+            tokio::time::sleep(COPY_POLL_INTERVAL).await;
+            match platform.clipboard_state(Selection::Clipboard) {
+                Ok(Some(state)) if state.sequence != before.sequence => {
+                    match platform.clipboard_text(Selection::Clipboard) {
+                        Ok(Some(text)) if !text.is_empty() => return Some(text),
+                        Ok(_) => break,
+                        Err(e) => {
+                            debug!(error = %e, "convert selection: clipboard read failed");
+                            break;
+                        }
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    debug!(error = %e, "convert selection: clipboard state failed");
+                    break;
+                }
+            }
+        }
     }
 
-    // Если "экранное" RU похоже на реальное русское слово — не трогаем.
-    // Исправляем только когда оно выглядит как мусор. Для высокой уверенности ("th", "sh"...)
-    // допускаем более мягкий порог, чтобы ловить кейсы вроде "thanks" → "ерфтлы".
-    let ru_ratio = ru_vowel_ratio(would_be_ru);
-    if ru_ratio < 0.25 {
-        return true;
+    match platform.clipboard_text(Selection::Primary) {
+        Ok(text) => text.filter(|text| !text.is_empty()),
+        Err(e) => {
+            debug!(error = %e, "convert selection: primary selection read failed");
+            None
+        }
     }
-
-    has_strong_english_bigrams(typed) && ru_ratio < 0.45
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_primary_lang_id() {
-        assert_eq!(primary_lang_id(0x0419), 0x0019);
-        assert_eq!(primary_lang_id(0x0422), 0x0022);
-        assert_eq!(primary_lang_id(0x0423), 0x0023);
+// Буфер обмена, которым можно пользоваться: в нём только текст, и бэкенд видит, когда
+// содержимое меняется. Картинку или файлы текстом не вернуть — такой буфер не трогаем.
+fn usable_clipboard(platform: &Platform) -> Option<ClipboardState> {
+    match platform.clipboard_state(Selection::Clipboard) {
+        Ok(Some(state)) if !state.has_non_text => Some(state),
+        Ok(Some(_)) => {
+            debug!("convert selection: clipboard holds non-text data, leaving it alone");
+            None
+        }
+        Ok(None) => None,
+        Err(e) => {
+            debug!(error = %e, "convert selection: clipboard state unavailable");
+            None
+        }
     }
+}
 
-    #[test]
-    fn test_lang_classification() {
-        assert!(is_cyrillic_lang_id(0x0419));
-        assert!(is_cyrillic_lang_id(0x0422));
-        assert!(is_cyrillic_lang_id(0x0423));
-        assert!(!is_cyrillic_lang_id(0x0409));
+// Конвертирует выделенный текст, вставляет его вместо выделенного и переключает раскладку.
+// Буфер обмена пользователя после этого возвращается как был.
+async fn convert_selection(platform: &Platform, config: &LayoutSwitcherConfig, tables: &LayoutTables) {
+    match platform.is_forbidden_context(&config.forbidden_contexts) {
+        Ok(false) => {}
+        Ok(true) => {
+            debug!("convert selection skipped (forbidden context)");
+            return;
+        }
+        Err(e) => {
+            debug!(error = %e, "convert selection skipped (forbidden context check failed)");
+            return;
+        }
     }
+    let clipboard = usable_clipboard(platform);
+    let saved = match clipboard {
+        Some(_) => match platform.clipboard_text(Selection::Clipboard) {
+            Ok(text) => text,
+            Err(e) => {
+                debug!(error = %e, "convert selection: clipboard unavailable");
+                return;
+            }
+        },
+        None => None,
+    };
 
-    #[test]
-    fn test_map_en_to_ru_basic() {
-        let typed = "ghbdtn";
-        let converted: String = typed.chars().map(map_en_to_ru).collect();
-        assert_eq!(converted, "привет");
+    if let Some(selected) = copy_selection(platform, config, clipboard).await {
+        paste_converted(platform, config, tables, &selected, clipboard.is_some()).await;
+    } else {
+        debug!("convert selection: nothing is selected");
     }
 
-    #[test]
-    fn test_map_en_to_ru_punctuation_keys() {
-        let typed = ",.";
-        let converted: String = typed.chars().map(map_en_to_ru).collect();
-        assert_eq!(converted, "бю");
+    let Some(before) = clipboard else {
+        return;
+    };
+    // Ни приложение, ни мы в буфер ничего не записали — возвращать нечего.
+    let current = platform.clipboard_state(Selection::Clipboard);
+    if matches!(current, Ok(Some(state)) if state.sequence == before.sequence) {
+        return;
     }
+    // Пустого буфера не бывает у всех платформ — пустой текст ближе всего.
+    let saved = saved.unwrap_or_default();
+    if let Err(e) = platform.set_clipboard_text(Selection::Clipboard, &saved) {
+        warn!(error = %e, "failed to restore the clipboard");
+    }
+}
 
-    #[test]
-    fn test_should_autocorrect_en_to_ru() {
-        let typed = "ghbdtn";
-        let converted: String = typed.chars().map(map_en_to_ru).collect();
-        assert!(should_autocorrect_en_to_ru(typed, &converted));
-
-        let typed = "dctv";
-        let converted: String = typed.chars().map(map_en_to_ru).collect();
-        assert_eq!(converted, "всем");
-        assert!(should_autocorrect_en_to_ru(typed, &converted));
-
-        let typed = "tcnm";
-        let converted: String = typed.chars().map(map_en_to_ru).collect();
-        assert_eq!(converted, "есть");
-        assert!(should_autocorrect_en_to_ru(typed, &converted));
-
-        let typed = "yt";
-        let converted: String = typed.chars().map(map_en_to_ru).collect();
-        assert_eq!(converted, "не");
-        assert!(should_autocorrect_en_to_ru(typed, &converted));
-
-        let typed = "hello";
-        let converted: String = typed.chars().map(map_en_to_ru).collect();
-        assert!(!should_autocorrect_en_to_ru(typed, &converted));
-
-        // Смешанный кейс: 'б' набирается через VK_OEM_COMMA, а остальное — через A-Z.
-        // На экране это выглядит как "chf,отать", а по физическим клавишам — "chf,jnfnm".
-        let typed = "chf,jnfnm";
-        let converted: String = typed.chars().map(map_en_to_ru).collect();
-        assert_eq!(converted, "сработать");
-        assert!(should_autocorrect_en_to_ru(typed, &converted));
+async fn paste_converted(
+    platform: &Platform,
+    config: &LayoutSwitcherConfig,
+    tables: &LayoutTables,
+    selected: &str,
+    use_clipboard: bool,
+) {
+    let installed = platform.list_layouts().unwrap_or_default();
+    let Some(converted) = selection::convert_text(tables, &installed, selected) else {
+        debug!("convert selection: no letters to convert");
+        return;
+    };
+
+    let written = if use_clipboard {
+        platform.set_clipboard_text(Selection::Clipboard, &converted.text)
+    } else {
+        Ok(false)
+    };
+    let pasted = match written {
+        Ok(true) => platform.send_clipboard_shortcut(&config.forbidden_contexts, ClipboardShortcut::Paste),
+        // Буфер недоступен или занят не текстом — печатаем поверх выделенного.
+        Ok(false) => platform.send_unicode_text(&config.forbidden_contexts, &converted.text),
+        Err(e) => Err(e),
+    };
+    match pasted {
+        Ok(true) => tokio::time::sleep(PASTE_DELAY).await,
+        Ok(false) => {
+            debug!("convert selection: paste returned false");
+            return;
+        }
+        Err(e) => {
+            debug!(error = %e, "convert selection: paste failed");
+            return;
+        }
+    }
+    info!("📋 Выделенное сконвертировано: '{}' → '{}'", selected, converted.text);
+
+    match &converted.target {
+        Some(target) => match platform.set_layout(&config.forbidden_contexts, target) {
+            Ok(true) => debug!(layout = %target, "convert selection: set layout ok"),
+            Ok(false) => debug!(layout = %target, "convert selection: set layout skipped/failed"),
+            Err(e) => debug!(error = %e, "convert selection: set layout error"),
+        },
+        None => debug!("convert selection: no installed target layout"),
     }
+}
 
-    #[test]
-    fn test_short_en_to_ru_allowlist() {
-        // 2-letter words
-        assert!(is_short_en_to_ru_allowlisted("yt"));
-        assert!(is_short_en_to_ru_allowlisted("YT"));
-        assert!(is_short_en_to_ru_allowlisted("yf"));
-        assert!(is_short_en_to_ru_allowlisted("bp"));
-        assert!(is_short_en_to_ru_allowlisted("jn"));
-        assert!(is_short_en_to_ru_allowlisted("pf"));
-        assert!(is_short_en_to_ru_allowlisted("dc"));
-        assert!(is_short_en_to_ru_allowlisted("lj"));
-        // 4-letter words
-        assert!(is_short_en_to_ru_allowlisted("dctv"));
-        assert!(is_short_en_to_ru_allowlisted("DCTV"));
-        assert!(is_short_en_to_ru_allowlisted("tcnm"));
-        assert!(is_short_en_to_ru_allowlisted("TCNM"));
-        // Not in allowlist
-        assert!(!is_short_en_to_ru_allowlisted("http"));
-        assert!(!is_short_en_to_ru_allowlisted("ab"));
+// Возвращает раскладку, стирает вставленный текст (и набранное после него)
+// и печатает исходные символы.
+fn undo_correction(
+    platform: &Platform,
+    config: &LayoutSwitcherConfig,
+    tables: &LayoutTables,
+    us: &KeyboardLayout,
+    correction: &LastCorrection,
+) {
+    let plan = correction.plan(us, tables.find(&correction.previous_layout));
+
+    match platform.set_layout(&config.forbidden_contexts, &correction.previous_layout) {
+        Ok(true) => debug!(layout = %correction.previous_layout, "undo: set layout ok"),
+        Ok(false) => debug!(layout = %correction.previous_layout, "undo: set layout skipped/failed"),
+        Err(e) => debug!(error = %e, "undo: set layout error"),
+    }
+    match platform.send_backspaces(&config.forbidden_contexts, plan.backspaces) {
+        Ok(true) => {}
+        Ok(false) => {
+            debug!("undo: send_backspaces returned false");
+            return;
+        }
+        Err(e) => {
+            debug!(error = %e, "undo: send_backspaces failed");
+            return;
+        }
     }
+    match platform.send_unicode_text(&config.forbidden_contexts, &plan.text) {
+        Ok(true) => info!(
+            "↩️ Исправление отменено: '{}' → '{}'",
+            correction.injected.trim_end(),
+            correction.original.trim_end()
+        ),
+        Ok(false) => debug!("undo: send_unicode_text returned false"),
+        Err(e) => debug!(error = %e, "undo: send_unicode_text failed"),
+    }
+}
 
-    #[test]
-    fn test_should_autocorrect_ru_to_en() {
-        // Пользователь в RU раскладке хотел EN: 'hello' на экране выглядит как 'руддщ'.
-        let typed = "hello";
-        let would_be_ru: String = typed.chars().map(map_en_to_ru).collect();
-        assert!(should_autocorrect_ru_to_en(typed, &would_be_ru));
-
-        // Типовой кейс: в RU раскладке хотел EN, а на экране получилось "похоже на слово",
-        // но это всё равно мусор для пользователя.
-        let typed = "thanks";
-        let would_be_ru: String = typed.chars().map(map_en_to_ru).collect();
-        assert!(should_autocorrect_ru_to_en(typed, &would_be_ru));
-
-        // Пользователь реально набирал русское: на экране это похоже на слово.
-        let typed = "ghbdtn";
-        let would_be_ru: String = typed.chars().map(map_en_to_ru).collect();
-        assert!(!should_autocorrect_ru_to_en(typed, &would_be_ru));
+// Направление исправления для лога: из раскладки, в которой слово набрано.
+fn direction(active: &LayoutId) -> &'static str {
+    match active.script {
+        Script::Cyrillic => "RU→EN",
+        _ => "EN→RU",
     }
 }
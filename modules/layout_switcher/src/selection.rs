@@ -0,0 +1,88 @@
+use smart_switcher_detector::find_target_layout;
+use smart_switcher_layouts::LayoutTables;
+use smart_switcher_shared_types::{LayoutId, Script};
+
+// Выделенный текст, пересчитанный на другую раскладку.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ConvertedSelection {
+    pub text: String,
+    // Установленная раскладка, в которую переключиться; None — её нет, конвертировали
+    // по встроенной таблице.
+    pub target: Option<LayoutId>,
+}
+
+// Направление — по алфавиту большинства букв: латиница → кириллица и обратно.
+// Набран текст давно, поэтому активная раскладка тут ничего не говорит.
+pub(crate) fn convert_text(
+    tables: &LayoutTables,
+    installed: &[LayoutId],
+    text: &str,
+) -> Option<ConvertedSelection> {
+    let latin = ("en", Script::Latin, "us");
+    let cyrillic = ("ru", Script::Cyrillic, "ru");
+    let (from, to) = match majority_script(text)? {
+        Script::Latin => (latin, cyrillic),
+        _ => (cyrillic, latin),
+    };
+
+    let layout = |(language, script, builtin): (&str, Script, &str)| {
+        let layout = find_target_layout(installed, script, language);
+        let table = layout
+            .as_ref()
+            .and_then(|layout| tables.find(layout))
+            .or_else(|| tables.get(builtin))?;
+        Some((layout, table))
+    };
+    let (_, source) = layout(from)?;
+    let (target, target_table) = layout(to)?;
+
+    Some(ConvertedSelection {
+        text: source.convert(text, target_table),
+        target,
+    })
+}
+
+fn majority_script(text: &str) -> Option<Script> {
+    let latin = text.chars().filter(char::is_ascii_alphabetic).count();
+    let cyrillic = text
+        .chars()
+        .filter(|ch| ('\u{400}'..='\u{4FF}').contains(ch))
+        .count();
+    match latin.cmp(&cyrillic) {
+        std::cmp::Ordering::Greater => Some(Script::Latin),
+        std::cmp::Ordering::Less => Some(Script::Cyrillic),
+        std::cmp::Ordering::Equal => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_by_majority_script() {
+        let tables = LayoutTables::builtin();
+        let installed = vec![LayoutId::from_xkb("us"), LayoutId::from_xkb("ru")];
+
+        let converted = convert_text(&tables, &installed, "Ghbdtn? vbh\n").unwrap();
+        assert_eq!(converted.text, "Привет, мир\n");
+        assert_eq!(converted.target, Some(LayoutId::from_xkb("ru")));
+
+        // Латиница в меньшинстве — текст набран в русской раскладке.
+        let converted = convert_text(&tables, &installed, "руддщ цщкдв http").unwrap();
+        assert_eq!(converted.text, "hello world http");
+        assert_eq!(converted.target, Some(LayoutId::from_xkb("us")));
+
+        assert_eq!(convert_text(&tables, &installed, "2024 ..."), None);
+    }
+
+    #[test]
+    fn falls_back_to_builtin_tables_without_installed_layout() {
+        let tables = LayoutTables::builtin();
+        let installed = vec![LayoutId::from_xkb("us")];
+
+        let converted = convert_text(&tables, &installed, "ghbdtn").unwrap();
+        assert_eq!(converted.text, "привет");
+        assert_eq!(converted.target, None);
+    }
+}
@@ -0,0 +1,134 @@
+use smart_switcher_layouts::KeyboardLayout;
+use smart_switcher_shared_types::LayoutId;
+
+// Дальше стольких нажатий после исправления отмена уже не имеет смысла.
+const MAX_KEYS_SINCE: usize = 64;
+
+// Последнее авто-исправление и всё, что набрано после него, — чтобы его можно было отменить.
+#[derive(Debug, Clone)]
+pub(crate) struct LastCorrection {
+    // Раскладка до исправления, в неё и возвращаемся.
+    pub previous_layout: LayoutId,
+    // Приложение, в котором исправляли (если исключения ведутся по приложениям).
+    pub app: Option<String>,
+    // Что было на экране до исправления (вместе с пробелом).
+    pub original: String,
+    // Что вставили вместо него.
+    pub injected: String,
+    // Напечатанное после исправления: клавиши по US QWERTY (`Key::us_char`).
+    keys_since: String,
+}
+
+// Что сделать, чтобы отменить исправление.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct UndoPlan {
+    pub backspaces: usize,
+    pub text: String,
+}
+
+impl LastCorrection {
+    pub fn new(
+        previous_layout: LayoutId,
+        app: Option<String>,
+        original: String,
+        injected: String,
+    ) -> Self {
+        Self {
+            previous_layout,
+            app,
+            original,
+            injected,
+            keys_since: String::new(),
+        }
+    }
+
+    // Напечатана клавиша после исправления. false — отменять уже поздно.
+    pub fn key_typed(&mut self, key: char) -> bool {
+        self.keys_since.push(key);
+        self.keys_since.chars().count() <= MAX_KEYS_SINCE
+    }
+
+    // Backspace после исправления. false — пользователь начал стирать сам исправленный текст.
+    pub fn backspace(&mut self) -> bool {
+        self.keys_since.pop().is_some()
+    }
+
+    // Стираем вставленное и набранное после него, печатаем исходный текст и те же клавиши
+    // в раскладке до исправления — как будто исправления не было.
+    pub fn plan(&self, us: &KeyboardLayout, previous_table: Option<&KeyboardLayout>) -> UndoPlan {
+        let typed_since = us.convert(&self.keys_since, previous_table.unwrap_or(us));
+
+        UndoPlan {
+            backspaces: self.injected.chars().count() + self.keys_since.chars().count(),
+            text: format!("{}{}", self.original, typed_since),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use smart_switcher_layouts::LayoutTables;
+
+    use super::*;
+
+    fn correction() -> LastCorrection {
+        LastCorrection::new(
+            LayoutId::from_xkb("us"),
+            None,
+            "ghbdtn ".to_string(),
+            "привет ".to_string(),
+        )
+    }
+
+    #[test]
+    fn undo_right_after_correction() {
+        let tables = LayoutTables::builtin();
+        let us = tables.get("us").unwrap();
+        let plan = correction().plan(us, Some(us));
+        assert_eq!(
+            plan,
+            UndoPlan {
+                backspaces: 7,
+                text: "ghbdtn ".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn keys_typed_since_are_replayed_in_previous_layout() {
+        let tables = LayoutTables::builtin();
+        let mut correction = correction();
+        // После исправления на экране "привет ми", пользователь стёр одну букву.
+        let us = tables.get("us").unwrap();
+        assert!(correction.key_typed('v'));
+        assert!(correction.key_typed('b'));
+        assert!(correction.key_typed('h'));
+        assert!(correction.backspace());
+
+        let plan = correction.plan(us, Some(us));
+        assert_eq!(plan.backspaces, 9);
+        assert_eq!(plan.text, "ghbdtn vb");
+
+        // Отмена исправления RU→EN: набранное после него возвращается кириллицей.
+        let mut correction = LastCorrection::new(
+            LayoutId::from_xkb("ru"),
+            None,
+            "руддщ ".to_string(),
+            "hello ".to_string(),
+        );
+        assert!(correction.key_typed('Q'));
+        assert_eq!(correction.plan(us, tables.get("ru")).text, "руддщ Й");
+    }
+
+    #[test]
+    fn erasing_the_correction_or_typing_on_expires_it() {
+        let mut correction = correction();
+        assert!(!correction.backspace());
+
+        let mut correction = self::correction();
+        for _ in 0..MAX_KEYS_SINCE {
+            assert!(correction.key_typed('a'));
+        }
+        assert!(!correction.key_typed('a'));
+    }
+}
@@ -0,0 +1,189 @@
+use std::collections::VecDeque;
+
+use smart_switcher_layouts::{KeyboardLayout, LayoutTables};
+use smart_switcher_shared_types::LayoutId;
+
+// Сколько последних слов можно сконвертировать повторными нажатиями.
+const MAX_WORDS: usize = 16;
+
+// Слово перед курсором, как оно сейчас на экране.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct TypedWord {
+    // Клавиши по US QWERTY (`Key::us_char`): одна клавиша — один символ на экране.
+    pub keys: String,
+    // Раскладка, в которой клавиши сейчас показаны на экране.
+    pub layout: LayoutId,
+    // Слово исправлено автоматически — конвертация вручную его отменяет.
+    pub auto_corrected: bool,
+    // Пробелы после слова.
+    pub spaces: usize,
+}
+
+// Что сделать, чтобы сконвертировать последние слова.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ConvertPlan {
+    pub backspaces: usize,
+    pub text: String,
+}
+
+// Последние слова до курсора, от старых к новым. Всё, что уводит курсор
+// или правит текст посреди слова, сбрасывает историю.
+#[derive(Debug, Default)]
+pub(crate) struct RecentWords {
+    words: VecDeque<TypedWord>,
+}
+
+impl RecentWords {
+    pub fn push(&mut self, keys: String, layout: LayoutId) {
+        if self.words.len() == MAX_WORDS {
+            self.words.pop_front();
+        }
+        self.words.push_back(TypedWord {
+            keys,
+            layout,
+            auto_corrected: false,
+            spaces: 0,
+        });
+    }
+
+    pub fn space(&mut self) {
+        if let Some(word) = self.words.back_mut() {
+            word.spaces += 1;
+        }
+    }
+
+    // Backspace при пустом буфере слова стирает пробел, а без пробелов — попадает в слово:
+    // оно снова набирается, его клавиши возвращаются в буфер.
+    pub fn backspace(&mut self) -> Option<String> {
+        match self.words.back_mut() {
+            Some(word) if word.spaces > 0 => {
+                word.spaces -= 1;
+                None
+            }
+            _ => self.resume(),
+        }
+    }
+
+    // Набор продолжается сразу за словом (без пробела) — слово снова в буфере.
+    pub fn resume(&mut self) -> Option<String> {
+        if self.words.back()?.spaces > 0 {
+            return None;
+        }
+        self.words.pop_back().map(|word| word.keys)
+    }
+
+    pub fn clear(&mut self) {
+        self.words.clear();
+    }
+
+    // От старых к новым.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &TypedWord> {
+        self.words.iter()
+    }
+
+    // `count`-е слово с конца (1 — последнее).
+    pub fn nth_back(&self, count: usize) -> Option<&TypedWord> {
+        self.words.len().checked_sub(count).and_then(|i| self.words.get(i))
+    }
+
+    // Стираем последние `count` слов вместе с пробелами и печатаем их клавиши в `target`.
+    pub fn plan(&self, count: usize, us: &KeyboardLayout, target: &KeyboardLayout) -> ConvertPlan {
+        let tail = self.words.iter().skip(self.words.len().saturating_sub(count));
+        let mut plan = ConvertPlan {
+            backspaces: 0,
+            text: String::new(),
+        };
+        for word in tail {
+            plan.backspaces += word.keys.chars().count() + word.spaces;
+            plan.text.push_str(&us.convert(&word.keys, target));
+            plan.text.extend(std::iter::repeat_n(' ', word.spaces));
+        }
+        plan
+    }
+
+    // Последние `count` слов теперь на экране в `target`.
+    pub fn converted(&mut self, count: usize, target: &LayoutId) {
+        let skip = self.words.len().saturating_sub(count);
+        for word in self.words.iter_mut().skip(skip) {
+            word.layout = target.clone();
+        }
+    }
+
+    // То же после авто-исправления: конвертация вручную эти слова вернёт.
+    pub fn auto_corrected(&mut self, count: usize, target: &LayoutId) {
+        self.converted(count, target);
+        let skip = self.words.len().saturating_sub(count);
+        for word in self.words.iter_mut().skip(skip) {
+            word.auto_corrected = true;
+        }
+    }
+}
+
+impl TypedWord {
+    // Слово так, как оно показано в раскладке `layout`.
+    pub fn text_in(&self, tables: &LayoutTables, us: &KeyboardLayout, layout: &LayoutId) -> String {
+        us.convert(&self.keys, tables.find(layout).unwrap_or(us))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn us() -> LayoutId {
+        LayoutId::from_xkb("us")
+    }
+
+    fn ru() -> LayoutId {
+        LayoutId::from_xkb("ru")
+    }
+
+    #[test]
+    fn plan_covers_the_last_words_and_their_spaces() {
+        let tables = LayoutTables::builtin();
+        let (us_table, ru_table) = (tables.get("us").unwrap(), tables.get("ru").unwrap());
+        let mut words = RecentWords::default();
+        words.push("ghbdtn".to_string(), us());
+        words.space();
+        words.push("vbh".to_string(), us());
+
+        assert_eq!(
+            words.plan(1, us_table, ru_table),
+            ConvertPlan {
+                backspaces: 3,
+                text: "мир".to_string(),
+            }
+        );
+
+        words.converted(1, &ru());
+        let plan = words.plan(2, us_table, ru_table);
+        assert_eq!(plan.backspaces, 10);
+        assert_eq!(plan.text, "привет мир");
+
+        // Слов меньше, чем нажатий, — конвертируем все.
+        assert_eq!(words.plan(5, us_table, ru_table), plan);
+        assert_eq!(words.nth_back(2).unwrap().text_in(&tables, us_table, &us()), "ghbdtn");
+        assert_eq!(words.nth_back(3), None);
+    }
+
+    #[test]
+    fn backspace_into_a_word_resumes_it() {
+        let mut words = RecentWords::default();
+        words.push("ghbdtn".to_string(), us());
+        words.space();
+        words.space();
+
+        assert_eq!(words.backspace(), None);
+        assert_eq!(words.resume(), None);
+        assert_eq!(words.backspace(), None);
+        assert_eq!(words.words.len(), 1);
+        assert_eq!(words.backspace().as_deref(), Some("ghbdtn"));
+        assert_eq!(words.words.len(), 0);
+        assert_eq!(words.backspace(), None);
+
+        for _ in 0..MAX_WORDS + 3 {
+            words.push("a".to_string(), us());
+        }
+        assert_eq!(words.words.len(), MAX_WORDS);
+    }
+}
@@ -0,0 +1,609 @@
+// Сквозные тесты модуля: нажатия идут через шину, правки — в текстовое поле FakePlatform.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use layout_switcher::LayoutSwitcherModule;
+use smart_switcher_core::{EventBus, KeystrokeDecoder, Module, ModuleContext, ModuleHandle};
+use smart_switcher_layouts::LayoutTables;
+use smart_switcher_platform::{ClipboardShortcut, FakeCall, FakePlatform};
+use smart_switcher_shared_types::config::{ForbiddenContextsConfig, InputConfig, LayoutSwitcherConfig};
+use smart_switcher_shared_types::{AppEvent, Key, KeyOrigin, KeyboardEvent, LayoutId, Modifiers};
+
+fn us() -> LayoutId {
+    LayoutId::from_xkb("us")
+}
+
+fn ru() -> LayoutId {
+    LayoutId::from_xkb("ru")
+}
+
+// Свой файл исключений на каждый конфиг: тесты не трогают данные пользователя и друг друга.
+fn exceptions_file() -> String {
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+    let n = NEXT.fetch_add(1, Ordering::Relaxed);
+    let name = format!("smart_switcher_exceptions_{}_{n}.toml", std::process::id());
+    let path = std::env::temp_dir().join(name);
+    let _ = std::fs::remove_file(&path);
+    path.display().to_string()
+}
+
+fn config() -> LayoutSwitcherConfig {
+    LayoutSwitcherConfig {
+        enabled: true,
+        exceptions_file: exceptions_file(),
+        ..LayoutSwitcherConfig::default()
+    }
+}
+
+async fn start_module(fake: &FakePlatform, config: LayoutSwitcherConfig) -> (EventBus, ModuleHandle) {
+    let layouts = Arc::new(LayoutTables::builtin());
+    let decoder = KeystrokeDecoder::new(fake.platform(), layouts.clone(), &InputConfig::default());
+    let bus = EventBus::with_decoder(1024, decoder);
+    let ctx = ModuleContext {
+        bus: bus.clone(),
+        platform: fake.platform(),
+        layouts,
+    };
+    let handle = LayoutSwitcherModule::new(config)
+        .start(ctx)
+        .await
+        .expect("start layout_switcher");
+    (bus, handle)
+}
+
+fn send(bus: &EventBus, events: Vec<KeyboardEvent>) {
+    for event in events {
+        bus.send(AppEvent::Keyboard(event));
+    }
+}
+
+// Прогоняет нажатия через модуль и дожидается его остановки.
+async fn run_module(fake: &FakePlatform, config: LayoutSwitcherConfig, events: Vec<KeyboardEvent>) {
+    let (bus, handle) = start_module(fake, config).await;
+    send(&bus, events);
+    bus.send(AppEvent::ShutdownRequested);
+
+    handle.join().await.expect("layout_switcher task");
+}
+
+// Отдаёт управление модулю, пока он не сделает `count` вызовов платформы.
+async fn wait_for_calls(fake: &FakePlatform, count: usize) {
+    for _ in 0..10_000 {
+        if fake.calls().len() >= count {
+            return;
+        }
+        tokio::task::yield_now().await;
+    }
+    panic!("expected {count} platform calls, got {:?}", fake.calls());
+}
+
+#[tokio::test]
+async fn en_typed_russian_word_is_corrected() {
+    let fake = FakePlatform::new();
+    let events = fake.type_text("ghbdtn ");
+
+    run_module(&fake, config(), events).await;
+
+    assert_eq!(fake.text(), "привет ");
+    assert_eq!(fake.active_layout(), ru());
+    assert_eq!(
+        fake.calls(),
+        vec![
+            FakeCall::SetLayout(ru()),
+            FakeCall::SendBackspaces(7),
+            FakeCall::SendUnicodeText("привет ".to_string()),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn words_before_a_corrected_word_are_corrected_as_a_phrase() {
+    let fake = FakePlatform::new();
+    let events = fake.type_text("hello z ghbdtn ");
+
+    run_module(&fake, config(), events).await;
+
+    // "z" одно не исправляется, но перед "ghbdtn" это "я"; "hello" на своём месте.
+    assert_eq!(fake.text(), "hello я привет ");
+    assert_eq!(fake.active_layout(), ru());
+    assert_eq!(
+        fake.calls(),
+        vec![
+            FakeCall::SetLayout(ru()),
+            FakeCall::SendBackspaces(9),
+            FakeCall::SendUnicodeText("я привет ".to_string()),
+        ]
+    );
+
+    // Отмена возвращает всю фразу.
+    let config = config();
+    let fake = FakePlatform::new();
+    let mut events = fake.type_text("z ghbdtn ");
+    events.extend(tap(Key::Pause));
+
+    run_module(&fake, config.clone(), events).await;
+
+    assert_eq!(fake.text(), "z ghbdtn ");
+    let saved = std::fs::read_to_string(&config.exceptions_file).unwrap();
+    assert!(saved.contains("\"z\"") && saved.contains("\"ghbdtn\""), "{saved}");
+
+    let fake = FakePlatform::new();
+    let events = fake.type_text("z ghbdtn ");
+    let config = LayoutSwitcherConfig {
+        phrase_words: 0,
+        ..self::config()
+    };
+
+    run_module(&fake, config, events).await;
+
+    assert_eq!(fake.text(), "z привет ");
+}
+
+// Нажатие и отпускание клавиши, которая ничего не печатает.
+fn tap(key: Key) -> Vec<KeyboardEvent> {
+    [true, false]
+        .into_iter()
+        .map(|is_key_down| KeyboardEvent {
+            key,
+            modifiers: Modifiers::default(),
+            text: None,
+            is_key_down,
+            origin: KeyOrigin::Physical,
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn undo_hotkey_reverts_the_last_correction() {
+    let fake = FakePlatform::new();
+    let mut events = fake.type_text("ghbdtn ");
+    events.extend(tap(Key::Pause));
+
+    run_module(&fake, config(), events).await;
+
+    assert_eq!(fake.text(), "ghbdtn ");
+    assert_eq!(fake.active_layout(), us());
+    assert_eq!(
+        fake.calls()[3..],
+        [
+            FakeCall::SetLayout(us()),
+            FakeCall::SendBackspaces(7),
+            FakeCall::SendUnicodeText("ghbdtn ".to_string()),
+        ]
+    );
+
+    // Второе нажатие отменять уже нечего.
+    let fake = FakePlatform::new();
+    fake.set_active_layout(&ru());
+    let mut events = fake.type_text("руддщ ");
+    events.extend(tap(Key::Pause));
+    events.extend(tap(Key::Pause));
+
+    run_module(&fake, config(), events).await;
+
+    assert_eq!(fake.text(), "руддщ ");
+    assert_eq!(fake.active_layout(), ru());
+    assert_eq!(fake.calls().len(), 6);
+}
+
+#[tokio::test]
+async fn undone_word_is_never_corrected_again() {
+    let config = config();
+    let fake = FakePlatform::new();
+    let mut events = fake.type_text("ghbdtn ");
+    events.extend(tap(Key::Pause));
+
+    run_module(&fake, config.clone(), events).await;
+
+    let saved = std::fs::read_to_string(&config.exceptions_file).unwrap();
+    assert!(saved.contains("\"ghbdtn\""), "{saved}");
+
+    // Исключение читается из файла при следующем запуске.
+    let fake = FakePlatform::new();
+    let events = fake.type_text("ghbdtn ");
+
+    run_module(&fake, config, events).await;
+
+    assert_eq!(fake.text(), "ghbdtn ");
+    assert!(fake.calls().is_empty());
+}
+
+#[tokio::test]
+async fn exceptions_can_be_kept_per_app() {
+    let config = LayoutSwitcherConfig {
+        exceptions_per_app: true,
+        ..config()
+    };
+    let fake = FakePlatform::new();
+    fake.set_focus(Some("Code.exe"), "main.rs");
+    let mut events = fake.type_text("ghbdtn ");
+    events.extend(tap(Key::Pause));
+
+    run_module(&fake, config.clone(), events).await;
+
+    let saved = std::fs::read_to_string(&config.exceptions_file).unwrap();
+    assert!(saved.contains("[apps.\"code.exe\"]"), "{saved}");
+
+    for (app, expected) in [("code.exe", "ghbdtn "), ("firefox", "привет ")] {
+        let fake = FakePlatform::new();
+        fake.set_focus(Some(app), "");
+        let events = fake.type_text("ghbdtn ");
+
+        run_module(&fake, config.clone(), events).await;
+
+        assert_eq!(fake.text(), expected, "{app}");
+    }
+}
+
+#[tokio::test]
+async fn erasing_a_correction_and_retyping_the_word_keeps_it() {
+    let config = config();
+    let fake = FakePlatform::new();
+    let (bus, handle) = start_module(&fake, config.clone()).await;
+
+    send(&bus, fake.type_text("ghbdtn "));
+    wait_for_calls(&fake, 3).await;
+    assert_eq!(fake.text(), "привет ");
+
+    // Пользователь стирает исправление, возвращает раскладку и набирает слово заново.
+    fake.set_active_layout(&us());
+    send(&bus, fake.type_text(&"\u{8}".repeat(7)));
+    send(&bus, fake.type_text("ghbdtn "));
+    bus.send(AppEvent::ShutdownRequested);
+    handle.join().await.expect("layout_switcher task");
+
+    assert_eq!(fake.text(), "ghbdtn ");
+    assert_eq!(fake.calls().len(), 3);
+    let saved = std::fs::read_to_string(&config.exceptions_file).unwrap();
+    assert!(saved.contains("\"ghbdtn\""), "{saved}");
+}
+
+#[tokio::test]
+async fn word_finished_after_switching_windows_is_corrected_in_its_window() {
+    let fake = FakePlatform::new();
+    let (bus, handle) = start_module(&fake, config()).await;
+
+    bus.send(fake.set_focus(Some("telegram"), "Chat"));
+    send(&bus, fake.type_text("ghb"));
+    bus.send(fake.set_focus(Some("firefox"), "Search"));
+    send(&bus, fake.type_text("hello "));
+    bus.send(fake.set_focus(Some("telegram"), "Chat"));
+    send(&bus, fake.type_text("dtn "));
+    wait_for_calls(&fake, 3).await;
+    bus.send(AppEvent::ShutdownRequested);
+    handle.join().await.expect("layout_switcher task");
+
+    assert_eq!(fake.text(), "привет ");
+    assert_eq!(
+        fake.calls(),
+        vec![
+            FakeCall::SetLayout(ru()),
+            FakeCall::SendBackspaces(7),
+            FakeCall::SendUnicodeText("привет ".to_string()),
+        ]
+    );
+    fake.set_focus(Some("firefox"), "Search");
+    assert_eq!(fake.text(), "hello ");
+}
+
+#[tokio::test]
+async fn click_drops_the_word_and_the_last_correction() {
+    let fake = FakePlatform::new();
+    let (bus, handle) = start_module(&fake, config()).await;
+
+    send(&bus, fake.type_text("ghbdtn "));
+    wait_for_calls(&fake, 3).await;
+    fake.clear_calls();
+
+    // После клика курсор может быть где угодно: ни исправления, ни отмены вслепую.
+    fake.set_active_layout(&us());
+    send(&bus, fake.type_text("vbh"));
+    bus.send(fake.click());
+    send(&bus, fake.type_text(" "));
+    send(&bus, tap(Key::Pause));
+    bus.send(AppEvent::ShutdownRequested);
+    handle.join().await.expect("layout_switcher task");
+
+    assert_eq!(fake.text(), "привет vbh ");
+    assert!(fake.calls().is_empty(), "{:?}", fake.calls());
+}
+
+#[tokio::test]
+async fn convert_hotkey_converts_one_more_word_per_press() {
+    let config = LayoutSwitcherConfig {
+        auto_detect: false,
+        ..config()
+    };
+    let fake = FakePlatform::new();
+    let mut events = fake.type_text("ghbdtn vbh");
+    events.extend(tap(Key::ScrollLock));
+    events.extend(tap(Key::ScrollLock));
+
+    run_module(&fake, config.clone(), events).await;
+
+    assert_eq!(fake.text(), "привет мир");
+    assert_eq!(fake.active_layout(), ru());
+    assert_eq!(
+        fake.calls(),
+        vec![
+            FakeCall::SetLayout(ru()),
+            FakeCall::SendBackspaces(3),
+            FakeCall::SendUnicodeText("мир".to_string()),
+            FakeCall::SetLayout(ru()),
+            FakeCall::SendBackspaces(10),
+            FakeCall::SendUnicodeText("привет мир".to_string()),
+        ]
+    );
+    let saved = std::fs::read_to_string(&config.exceptions_file).unwrap();
+    assert!(saved.contains("never = []"), "{saved}");
+    assert!(saved.contains("\"ghbdtn\"") && saved.contains("\"vbh\""), "{saved}");
+
+    // RU→EN, курсор сразу за словом.
+    let fake = FakePlatform::new();
+    fake.set_active_layout(&ru());
+    let mut events = fake.type_text("руддщ");
+    events.extend(tap(Key::ScrollLock));
+
+    run_module(&fake, self::config(), events).await;
+
+    assert_eq!(fake.text(), "hello");
+    assert_eq!(fake.active_layout(), us());
+}
+
+#[tokio::test]
+async fn own_injected_keys_do_not_interrupt_a_convert_series() {
+    let config = LayoutSwitcherConfig {
+        auto_detect: false,
+        ..config()
+    };
+    let fake = FakePlatform::new();
+    let mut events = fake.type_text("ghbdtn vbh");
+    events.extend(tap(Key::ScrollLock));
+    // Бэкенд возвращает через перехват и то, что вставили мы сами.
+    events.extend(tap(Key::Backspace).into_iter().map(|ev| KeyboardEvent {
+        origin: KeyOrigin::OwnInjection,
+        ..ev
+    }));
+    events.extend(tap(Key::ScrollLock));
+
+    run_module(&fake, config, events).await;
+
+    assert_eq!(fake.text(), "привет мир");
+}
+
+#[tokio::test]
+async fn converting_an_auto_correction_back_learns_never() {
+    let config = config();
+    let fake = FakePlatform::new();
+    let mut events = fake.type_text("ghbdtn ");
+    events.extend(tap(Key::ScrollLock));
+
+    run_module(&fake, config.clone(), events).await;
+
+    assert_eq!(fake.text(), "ghbdtn ");
+    assert_eq!(fake.active_layout(), us());
+    let saved = std::fs::read_to_string(&config.exceptions_file).unwrap();
+    assert!(saved.contains("never = [\"ghbdtn\"]"), "{saved}");
+}
+
+// Сочетание Shift+клавиша: модификаторы в событиях — как их ставит бэкенд.
+// `key` с зажатым модификатором `modifier`.
+fn chord(modifier: Key, key: Key) -> Vec<KeyboardEvent> {
+    let mut held = Modifiers::default();
+    held.update(modifier, true);
+    [
+        (modifier, true, held),
+        (key, true, held),
+        (key, false, held),
+        (modifier, false, Modifiers::default()),
+    ]
+    .into_iter()
+    .map(|(key, is_key_down, modifiers)| KeyboardEvent {
+        key,
+        modifiers,
+        text: None,
+        is_key_down,
+        origin: KeyOrigin::Physical,
+    })
+    .collect()
+}
+
+#[tokio::test]
+async fn selection_hotkey_converts_selected_text_and_restores_clipboard() {
+    let fake = FakePlatform::new();
+    fake.set_text("say ghbdtn");
+    fake.select_last(6);
+    fake.set_clipboard(Some("saved"));
+
+    run_module(&fake, config(), chord(Key::ShiftLeft, Key::ScrollLock)).await;
+
+    assert_eq!(fake.text(), "say привет");
+    assert_eq!(fake.clipboard().as_deref(), Some("saved"));
+    assert_eq!(fake.active_layout(), ru());
+    assert_eq!(
+        fake.calls(),
+        vec![
+            FakeCall::SendClipboardShortcut(ClipboardShortcut::Copy),
+            FakeCall::SendClipboardShortcut(ClipboardShortcut::Paste),
+            FakeCall::SetLayout(ru()),
+        ]
+    );
+
+    // Ничего не выделено: текст не трогаем, пустой буфер остаётся пустым.
+    let fake = FakePlatform::new();
+    fake.set_text("say ghbdtn");
+
+    run_module(&fake, config(), chord(Key::ShiftLeft, Key::ScrollLock)).await;
+
+    assert_eq!(fake.text(), "say ghbdtn");
+    assert_eq!(fake.clipboard(), None);
+    assert_eq!(fake.active_layout(), us());
+    assert_eq!(
+        fake.calls(),
+        vec![FakeCall::SendClipboardShortcut(ClipboardShortcut::Copy)]
+    );
+}
+
+#[tokio::test]
+async fn selection_hotkey_leaves_non_text_clipboard_alone() {
+    let fake = FakePlatform::new();
+    fake.set_text("say ghbdtn");
+    fake.select_last(6);
+    fake.set_clipboard_image();
+
+    run_module(&fake, config(), chord(Key::ShiftLeft, Key::ScrollLock)).await;
+
+    // Выделенное берём из PRIMARY и печатаем: картинка в буфере остаётся.
+    assert_eq!(fake.text(), "say привет");
+    assert!(fake.clipboard_has_image());
+    assert_eq!(fake.active_layout(), ru());
+    assert_eq!(
+        fake.calls(),
+        vec![
+            FakeCall::SendUnicodeText("привет".to_string()),
+            FakeCall::SetLayout(ru()),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn copy_shortcut_inside_a_word_is_not_typed() {
+    let fake = FakePlatform::new();
+    let mut events = fake.type_text("ghbd");
+    events.extend(chord(Key::ControlLeft, Key::C));
+    events.extend(fake.type_text("tn "));
+
+    run_module(&fake, config(), events).await;
+
+    assert_eq!(fake.text(), "привет ");
+}
+
+#[tokio::test]
+async fn comma_and_period_keys_are_part_of_the_word() {
+    let fake = FakePlatform::new();
+    let events = fake.type_text("chf,jnfnm ");
+
+    run_module(&fake, config(), events).await;
+
+    assert_eq!(fake.text(), "сработать ");
+}
+
+#[tokio::test]
+async fn oem_letter_keys_and_shift_are_part_of_the_word() {
+    for (typed, expected) in [("ht;bv ", "режим "), (":ehyfk ", "Журнал ")] {
+        let fake = FakePlatform::new();
+        let events = fake.type_text(typed);
+
+        run_module(&fake, config(), events).await;
+
+        assert_eq!(fake.text(), expected);
+    }
+}
+
+#[tokio::test]
+async fn short_and_out_of_vocabulary_words_are_corrected() {
+    // Короткое слово из словаря и слово вне словаря, которое узнаёт модель.
+    for (typed, expected) in [("vbh ", "мир "), ("dtkjcbgtl ", "велосипед ")] {
+        let fake = FakePlatform::new();
+        let events = fake.type_text(typed);
+
+        run_module(&fake, config(), events).await;
+
+        assert_eq!(fake.text(), expected);
+    }
+
+    // Одна клавиша — не слово.
+    let fake = FakePlatform::new();
+    let events = fake.type_text("plan b ");
+
+    run_module(&fake, config(), events).await;
+
+    assert_eq!(fake.text(), "plan b ");
+}
+
+#[tokio::test]
+async fn capitalised_words_keep_their_case() {
+    let fake = FakePlatform::new();
+    let events = fake.type_text("Ghbdtn ");
+
+    run_module(&fake, config(), events).await;
+
+    assert_eq!(fake.text(), "Привет ");
+
+    let fake = FakePlatform::new();
+    fake.set_active_layout(&ru());
+    let events = fake.type_text("Руддщ ");
+
+    run_module(&fake, config(), events).await;
+
+    assert_eq!(fake.text(), "Hello ");
+}
+
+#[tokio::test]
+async fn apostrophe_survives_ru_to_en() {
+    let fake = FakePlatform::new();
+    fake.set_active_layout(&ru());
+    let events = fake.type_text("вщтэе ");
+
+    run_module(&fake, config(), events).await;
+
+    assert_eq!(fake.text(), "don't ");
+}
+
+#[tokio::test]
+async fn ru_typed_english_word_is_corrected() {
+    let fake = FakePlatform::new();
+    fake.set_active_layout(&ru());
+    let events = fake.type_text("руддщ ");
+
+    run_module(&fake, config(), events).await;
+
+    assert_eq!(fake.text(), "hello ");
+    assert_eq!(fake.active_layout(), us());
+}
+
+#[tokio::test]
+async fn english_word_in_en_layout_is_left_alone() {
+    let fake = FakePlatform::new();
+    let events = fake.type_text("hello world ");
+
+    run_module(&fake, config(), events).await;
+
+    assert_eq!(fake.text(), "hello world ");
+    assert!(fake.calls().is_empty());
+}
+
+#[tokio::test]
+async fn forbidden_context_is_never_edited() {
+    let fake = FakePlatform::new();
+    fake.set_focus(Some("KeePassXC.exe"), "Passwords.kdbx");
+    let config = LayoutSwitcherConfig {
+        forbidden_contexts: ForbiddenContextsConfig {
+            blocked_processes: vec!["keepass".to_string()],
+            ..ForbiddenContextsConfig::default()
+        },
+        ..config()
+    };
+    let events = fake.type_text("ghbdtn ");
+
+    run_module(&fake, config, events).await;
+
+    assert_eq!(fake.text(), "ghbdtn ");
+    assert!(fake.calls().is_empty());
+}
+
+#[tokio::test]
+async fn auto_detect_off_leaves_text_untouched() {
+    let fake = FakePlatform::new();
+    let config = LayoutSwitcherConfig {
+        auto_detect: false,
+        ..config()
+    };
+    let events = fake.type_text("ghbdtn ");
+
+    run_module(&fake, config, events).await;
+
+    assert_eq!(fake.text(), "ghbdtn ");
+    assert!(fake.calls().is_empty());
+}
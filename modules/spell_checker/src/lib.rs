@@ -7,6 +7,7 @@ use smart_switcher_core::{Module, ModuleContext, ModuleHandle};
 use smart_switcher_shared_types::{config::SpellCheckerConfig, AppEvent};
 use tracing::{info, warn};
 use std::num::NonZeroUsize;
+#[cfg(windows)]
 use winrt_toast::{Toast, ToastManager};
 
 const MAX_WORDS_PER_COMMIT: usize = 12;
@@ -17,6 +18,7 @@ struct CachedSpellResult {
     first_message: Option<String>,
 }
 
+#[cfg(windows)]
 fn show_notification(title: &str, message: &str) {
     std::thread::spawn({
         let title = title.to_string();
@@ -35,6 +37,12 @@ fn show_notification(title: &str, message: &str) {
     });
 }
 
+// Toast-уведомления есть только на Windows, на остальных платформах пишем в лог.
+#[cfg(not(windows))]
+fn show_notification(title: &str, message: &str) {
+    info!(title, message, "spell_checker notification");
+}
+
 pub struct SpellCheckerModule {
     config: SpellCheckerConfig,
 }
@@ -71,32 +79,6 @@ impl Module for SpellCheckerModule {
                 "spell_checker started",
             );
 
-            let is_letter_vk = |vk: u32| (0x41..=0x5A).contains(&vk);
-            let vk_to_letter = |vk: u32, shift: bool| {
-                let base = (vk as u8 as char).to_ascii_lowercase();
-                if shift {
-                    base.to_ascii_uppercase()
-                } else {
-                    base
-                }
-            };
-
-            let map_en_to_ru = |ch: char| -> char {
-                match ch.to_ascii_lowercase() {
-                    'q' => 'й', 'w' => 'ц', 'e' => 'у', 'r' => 'к', 't' => 'е', 'y' => 'н', 'u' => 'г', 'i' => 'ш', 'o' => 'щ', 'p' => 'з',
-                    'a' => 'ф', 's' => 'ы', 'd' => 'в', 'f' => 'а', 'g' => 'п', 'h' => 'р', 'j' => 'о', 'k' => 'л', 'l' => 'д',
-                    'z' => 'я', 'x' => 'ч', 'c' => 'с', 'v' => 'м', 'b' => 'и', 'n' => 'т', 'm' => 'ь',
-                    other => other,
-                }
-            };
-
-            let is_alt_vk = |vk: u32| matches!(vk, 0x12 | 0xA4 | 0xA5);
-            let is_shift_vk = |vk: u32| matches!(vk, 0x10 | 0xA0 | 0xA1);
-
-            let mut is_alt_down = false;
-            let mut is_shift_down = false;
-            let mut buffer = String::new();
-
             let mut cache = LruCache::<(String, String), CachedSpellResult>::new(
                 NonZeroUsize::new(config.cache_size.max(1)).expect("cache size > 0"),
             );
@@ -107,173 +89,123 @@ impl Module for SpellCheckerModule {
                         info!("spell_checker shutting down");
                         break;
                     }
-                    AppEvent::Keyboard(ev) => {
-                        if is_alt_vk(ev.vk_code) {
-                            is_alt_down = ev.is_key_down;
+                    AppEvent::LineCommitted { text } => {
+                        let commit = text.trim().to_string();
+
+                        if commit.is_empty() {
+                            continue;
                         }
-                        if is_shift_vk(ev.vk_code) {
-                            is_shift_down = ev.is_key_down;
+
+                        let commit_for_check = last_n_words(&commit, MAX_WORDS_PER_COMMIT);
+                        if commit_for_check.is_empty() {
+                            continue;
                         }
 
-                        if !ev.is_key_down {
+                        let forbidden = platform
+                            .is_forbidden_context(&config.forbidden_contexts)
+                            .unwrap_or(true);
+                        if forbidden {
                             continue;
                         }
 
-                        if is_alt_down {
+                        if config.api.to_lowercase() != "languagetool" {
+                            warn!(api = %config.api, "unsupported spell_checker api (only languagetool is supported in MVP)");
                             continue;
                         }
 
-                        match ev.vk_code {
-                            0x08 => {
-                                // Backspace
-                                buffer.pop();
+                        let cache_key = (config.language.clone(), commit_for_check.clone());
+                        if let Some(hit) = cache.get(&cache_key).cloned() {
+                            if hit.issues == 0 {
+                                info!("spell_checker: no issues (cache)");
+                            } else {
+                                warn!(
+                                    issues = hit.issues,
+                                    message = hit.first_message.as_deref().unwrap_or(""),
+                                    "spell_checker: issues found (cache)"
+                                );
                             }
-                            0x20 => {
-                                // Space
-                                if !buffer.ends_with(' ') {
-                                    buffer.push(' ');
-                                }
-                            }
-                            0x0D => {
-                                // Enter => commit
-                                let commit = buffer.trim().to_string();
-                                buffer.clear();
-
-                                if commit.is_empty() {
-                                    continue;
-                                }
-
-                                let commit_for_check = last_n_words(&commit, MAX_WORDS_PER_COMMIT);
-                                if commit_for_check.is_empty() {
-                                    continue;
-                                }
-
-                                let forbidden = platform
-                                    .is_forbidden_context(&config.forbidden_contexts)
-                                    .unwrap_or(true);
-                                if forbidden {
-                                    continue;
-                                }
-
-                                if config.api.to_lowercase() != "languagetool" {
-                                    warn!(api = %config.api, "unsupported spell_checker api (only languagetool is supported in MVP)");
-                                    continue;
-                                }
-
-                                let cache_key = (config.language.clone(), commit_for_check.clone());
-                                if let Some(hit) = cache.get(&cache_key).cloned() {
-                                    if hit.issues == 0 {
-                                        info!("spell_checker: no issues (cache)");
-                                    } else {
-                                        warn!(
-                                            issues = hit.issues,
-                                            message = hit.first_message.as_deref().unwrap_or(""),
-                                            "spell_checker: issues found (cache)"
-                                        );
-                                    }
-                                    continue;
-                                }
-
-                                match languagetool_check(&client, &config, &commit_for_check).await {
-                                    Ok(result) => {
-                                        let issues = result.matches.len();
-                                        let first_message = result.matches.first().map(|m| m.message.clone());
-                                        cache.put(
-                                            cache_key,
-                                            CachedSpellResult {
-                                                issues,
-                                                first_message: first_message.clone(),
-                                            },
-                                        );
+                            continue;
+                        }
 
-                                        if issues == 0 {
-                                            info!("spell_checker: no issues");
-                                        } else {
-                                            warn!(
-                                                issues,
-                                                message = first_message.as_deref().unwrap_or(""),
-                                                "spell_checker: issues found"
+                        match languagetool_check(&client, &config, &commit_for_check).await {
+                            Ok(result) => {
+                                let issues = result.matches.len();
+                                let first_message = result.matches.first().map(|m| m.message.clone());
+                                cache.put(
+                                    cache_key,
+                                    CachedSpellResult {
+                                        issues,
+                                        first_message: first_message.clone(),
+                                    },
+                                );
+
+                                if issues == 0 {
+                                    info!("spell_checker: no issues");
+                                } else {
+                                    warn!(
+                                        issues,
+                                        message = first_message.as_deref().unwrap_or(""),
+                                        "spell_checker: issues found"
+                                    );
+
+                                    // Find issues in the last word (conservative approach)
+                                    // commit_for_check contains last_n_words, we calculate text length
+                                    let text_len = commit_for_check.chars().count();
+                                    
+                                    // Apply auto-correction or show notification
+                                    for issue in &result.matches {
+                                        // Check if the issue is in the last part of the text (last word + some margin)
+                                        let issue_end = issue.offset + issue.length;
+                                        let is_last_word = issue_end >= text_len.saturating_sub(20);
+                                        
+                                        if issue.replacements.is_empty() {
+                                            // No suggestions - just show notification with the issue
+                                            show_notification(
+                                                "Орфографическая ошибка",
+                                                &issue.message,
+                                            );
+                                        } else if issue.replacements.len() == 1 && is_last_word {
+                                            // Single suggestion for last word - auto-correct
+                                            let replacement = &issue.replacements[0].value;
+                                            info!(
+                                                length = issue.length,
+                                                replacement = %replacement,
+                                                "auto-correcting spelling error"
                                             );
-
-                                            // Find issues in the last word (conservative approach)
-                                            // commit_for_check contains last_n_words, we calculate text length
-                                            let text_len = commit_for_check.chars().count();
                                             
-                                            // Apply auto-correction or show notification
-                                            for issue in &result.matches {
-                                                // Check if the issue is in the last part of the text (last word + some margin)
-                                                let issue_end = issue.offset + issue.length;
-                                                let is_last_word = issue_end >= text_len.saturating_sub(20);
+                                            // Delete the error (issue.length backspaces)
+                                            if platform.send_backspaces(&config.forbidden_contexts, issue.length).unwrap_or(false) {
+                                                // Insert the correction
+                                                let _ = platform.send_unicode_text(&config.forbidden_contexts, replacement);
                                                 
-                                                if issue.replacements.is_empty() {
-                                                    // No suggestions - just show notification with the issue
-                                                    show_notification(
-                                                        "Орфографическая ошибка",
-                                                        &issue.message,
-                                                    );
-                                                } else if issue.replacements.len() == 1 && is_last_word {
-                                                    // Single suggestion for last word - auto-correct
-                                                    let replacement = &issue.replacements[0].value;
-                                                    info!(
-                                                        length = issue.length,
-                                                        replacement = %replacement,
-                                                        "auto-correcting spelling error"
-                                                    );
-                                                    
-                                                    // Delete the error (issue.length backspaces)
-                                                    if platform.send_backspaces(&config.forbidden_contexts, issue.length).unwrap_or(false) {
-                                                        // Insert the correction
-                                                        let _ = platform.send_unicode_text(&config.forbidden_contexts, replacement);
-                                                        
-                                                        show_notification(
-                                                            "Автоисправление",
-                                                            &format!("Исправлено на: {}", replacement),
-                                                        );
-                                                    }
-                                                } else {
-                                                    // Multiple suggestions - show notification
-                                                    let suggestions = issue.replacements
-                                                        .iter()
-                                                        .take(3)
-                                                        .map(|r| r.value.as_str())
-                                                        .collect::<Vec<_>>()
-                                                        .join(", ");
-                                                    
-                                                    show_notification(
-                                                        "Предложения исправлений",
-                                                        &format!("{}\n\nВарианты: {}", issue.message, suggestions),
-                                                    );
-                                                }
+                                                show_notification(
+                                                    "Автоисправление",
+                                                    &format!("Исправлено на: {}", replacement),
+                                                );
                                             }
+                                        } else {
+                                            // Multiple suggestions - show notification
+                                            let suggestions = issue.replacements
+                                                .iter()
+                                                .take(3)
+                                                .map(|r| r.value.as_str())
+                                                .collect::<Vec<_>>()
+                                                .join(", ");
+                                            
+                                            show_notification(
+                                                "Предложения исправлений",
+                                                &format!("{}\n\nВарианты: {}", issue.message, suggestions),
+                                            );
                                         }
                                     }
-                                    Err(err) => {
-                                        warn!(error = %err, "spell_checker request failed");
-                                    }
                                 }
                             }
-                            vk if is_letter_vk(vk) => {
-                                let base = vk_to_letter(vk, is_shift_down);
-                                let lang = platform.get_active_lang_id().unwrap_or(0);
-
-                                let ch = if lang == 0x0419 {
-                                    // RU
-                                    let ru = map_en_to_ru(base);
-                                    if is_shift_down {
-                                        ru.to_uppercase().next().unwrap_or(ru)
-                                    } else {
-                                        ru
-                                    }
-                                } else {
-                                    // EN or unknown
-                                    base
-                                };
-
-                                buffer.push(ch);
+                            Err(err) => {
+                                warn!(error = %err, "spell_checker request failed");
                             }
-                            _ => {}
                         }
                     }
+                    _ => {}
                 }
             }
 
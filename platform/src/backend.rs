@@ -0,0 +1,139 @@
+use smart_switcher_shared_types::LayoutId;
+use smart_switcher_shared_types::config::ForbiddenContextsConfig;
+
+use crate::clipboard::{ClipboardShortcut, ClipboardState, Selection};
+use crate::context::ActiveWindowInfo;
+use crate::hook::KeyboardHook;
+
+// Всё, что модули ожидают от ОС. Ok(false) — действие не выполнено
+// (запрещённый контекст или не поддерживается), Err — сбой бэкенда.
+pub trait PlatformBackend: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    fn start_keyboard_hook(&self) -> anyhow::Result<KeyboardHook>;
+
+    fn switch_to_next_layout(&self, forbidden: &ForbiddenContextsConfig) -> anyhow::Result<bool>;
+
+    // Установленные раскладки в порядке переключения.
+    fn list_layouts(&self) -> anyhow::Result<Vec<LayoutId>>;
+
+    // Err — активная раскладка неизвестна; угадывать её нельзя.
+    fn active_layout(&self) -> anyhow::Result<LayoutId>;
+
+    fn set_layout(
+        &self,
+        forbidden: &ForbiddenContextsConfig,
+        layout: &LayoutId,
+    ) -> anyhow::Result<bool>;
+
+    fn send_backspaces(
+        &self,
+        forbidden: &ForbiddenContextsConfig,
+        count: usize,
+    ) -> anyhow::Result<bool>;
+
+    fn send_unicode_text(
+        &self,
+        forbidden: &ForbiddenContextsConfig,
+        text: &str,
+    ) -> anyhow::Result<bool>;
+
+    fn is_forbidden_context(&self, forbidden: &ForbiddenContextsConfig) -> anyhow::Result<bool>;
+
+    // Окно в фокусе; None — бэкенд окон не видит (evdev, Wayland).
+    fn active_window(&self) -> anyhow::Result<Option<ActiveWindowInfo>>;
+
+    // Текст буфера; None — буфер пуст, в нём не текст или такого буфера нет (PRIMARY вне X11).
+    fn clipboard_text(&self, selection: Selection) -> anyhow::Result<Option<String>>;
+
+    // None — бэкенд не знает, менялся ли буфер и что в нём.
+    fn clipboard_state(&self, selection: Selection) -> anyhow::Result<Option<ClipboardState>>;
+
+    fn set_clipboard_text(&self, selection: Selection, text: &str) -> anyhow::Result<bool>;
+
+    fn send_clipboard_shortcut(
+        &self,
+        forbidden: &ForbiddenContextsConfig,
+        shortcut: ClipboardShortcut,
+    ) -> anyhow::Result<bool>;
+}
+
+// Бэкенд-заглушка: ни один настоящий бэкенд недоступен.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NullBackend;
+
+impl PlatformBackend for NullBackend {
+    fn name(&self) -> &'static str {
+        "null"
+    }
+
+    fn start_keyboard_hook(&self) -> anyhow::Result<KeyboardHook> {
+        Err(anyhow::anyhow!(
+            "keyboard hook is not supported on this platform (on Linux build with the `x11`, `wayland` or `evdev` feature)"
+        ))
+    }
+
+    fn switch_to_next_layout(&self, _forbidden: &ForbiddenContextsConfig) -> anyhow::Result<bool> {
+        Ok(false)
+    }
+
+    fn list_layouts(&self) -> anyhow::Result<Vec<LayoutId>> {
+        Ok(Vec::new())
+    }
+
+    fn active_layout(&self) -> anyhow::Result<LayoutId> {
+        Err(anyhow::anyhow!("keyboard layout is unknown: no platform backend"))
+    }
+
+    fn set_layout(
+        &self,
+        _forbidden: &ForbiddenContextsConfig,
+        _layout: &LayoutId,
+    ) -> anyhow::Result<bool> {
+        Ok(false)
+    }
+
+    fn send_backspaces(
+        &self,
+        _forbidden: &ForbiddenContextsConfig,
+        _count: usize,
+    ) -> anyhow::Result<bool> {
+        Ok(false)
+    }
+
+    fn send_unicode_text(
+        &self,
+        _forbidden: &ForbiddenContextsConfig,
+        _text: &str,
+    ) -> anyhow::Result<bool> {
+        Ok(false)
+    }
+
+    fn is_forbidden_context(&self, _forbidden: &ForbiddenContextsConfig) -> anyhow::Result<bool> {
+        Ok(false)
+    }
+
+    fn active_window(&self) -> anyhow::Result<Option<ActiveWindowInfo>> {
+        Ok(None)
+    }
+
+    fn clipboard_text(&self, _selection: Selection) -> anyhow::Result<Option<String>> {
+        Ok(None)
+    }
+
+    fn clipboard_state(&self, _selection: Selection) -> anyhow::Result<Option<ClipboardState>> {
+        Ok(None)
+    }
+
+    fn set_clipboard_text(&self, _selection: Selection, _text: &str) -> anyhow::Result<bool> {
+        Ok(false)
+    }
+
+    fn send_clipboard_shortcut(
+        &self,
+        _forbidden: &ForbiddenContextsConfig,
+        _shortcut: ClipboardShortcut,
+    ) -> anyhow::Result<bool> {
+        Ok(false)
+    }
+}
@@ -0,0 +1,23 @@
+// Буфер обмена: обычный (Ctrl+C / Ctrl+V) и PRIMARY — выделенное мышью в X11.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Selection {
+    Clipboard,
+    Primary,
+}
+
+// Что известно о содержимом буфера без чтения самого содержимого.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClipboardState {
+    // Меняется с каждым новым содержимым: по нему видно, что приложение скопировало.
+    pub sequence: u64,
+    // Кроме простого текста есть что-то ещё (картинка, файлы, форматирование):
+    // записав текст, это уже не вернуть.
+    pub has_non_text: bool,
+}
+
+// Сочетания, которыми приложение в фокусе само копирует выделенное и вставляет буфер.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClipboardShortcut {
+    Copy,
+    Paste,
+}
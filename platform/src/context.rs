@@ -0,0 +1,27 @@
+use smart_switcher_shared_types::config::ForbiddenContextsConfig;
+
+#[derive(Debug, Clone)]
+pub struct ActiveWindowInfo {
+    pub title: String,
+    pub process_name: Option<String>,
+}
+
+fn contains_any(haystack: &str, needles: &[String]) -> bool {
+    let haystack = haystack.to_lowercase();
+    needles
+        .iter()
+        .map(|s| s.to_lowercase())
+        .any(|needle| !needle.is_empty() && haystack.contains(&needle))
+}
+
+impl ActiveWindowInfo {
+    pub fn is_forbidden(&self, forbidden: &ForbiddenContextsConfig) -> bool {
+        if contains_any(&self.title, &forbidden.blocked_windows) {
+            return true;
+        }
+
+        self.process_name
+            .as_ref()
+            .is_some_and(|proc_name| contains_any(proc_name, &forbidden.blocked_processes))
+    }
+}
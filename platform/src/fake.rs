@@ -0,0 +1,646 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard, mpsc};
+
+use smart_switcher_shared_types::{AppEvent, Key, KeyOrigin, KeyboardEvent, LayoutId, Modifiers};
+use smart_switcher_shared_types::config::ForbiddenContextsConfig;
+
+use crate::Platform;
+use crate::backend::PlatformBackend;
+use crate::clipboard::{ClipboardShortcut, ClipboardState, Selection};
+use crate::context::ActiveWindowInfo;
+use crate::hook::{KeyboardHook, KeyboardHookController};
+
+// Клавиша, base, shift для раскладок, которые умеет «набирать» FakePlatform.
+#[rustfmt::skip]
+const US_KEYS: &[(Key, char, char)] = &[
+    (Key::Digit1, '1', '!'), (Key::Digit2, '2', '@'), (Key::Digit3, '3', '#'),
+    (Key::Digit4, '4', '$'), (Key::Digit5, '5', '%'),
+    (Key::Digit6, '6', '^'), (Key::Digit7, '7', '&'), (Key::Digit8, '8', '*'),
+    (Key::Digit9, '9', '('), (Key::Digit0, '0', ')'),
+    (Key::Q, 'q', 'Q'), (Key::W, 'w', 'W'), (Key::E, 'e', 'E'), (Key::R, 'r', 'R'),
+    (Key::T, 't', 'T'),
+    (Key::Y, 'y', 'Y'), (Key::U, 'u', 'U'), (Key::I, 'i', 'I'), (Key::O, 'o', 'O'),
+    (Key::P, 'p', 'P'),
+    (Key::BracketLeft, '[', '{'), (Key::BracketRight, ']', '}'),
+    (Key::A, 'a', 'A'), (Key::S, 's', 'S'), (Key::D, 'd', 'D'), (Key::F, 'f', 'F'),
+    (Key::G, 'g', 'G'),
+    (Key::H, 'h', 'H'), (Key::J, 'j', 'J'), (Key::K, 'k', 'K'), (Key::L, 'l', 'L'),
+    (Key::Semicolon, ';', ':'), (Key::Quote, '\'', '"'), (Key::Backquote, '`', '~'),
+    (Key::Z, 'z', 'Z'), (Key::X, 'x', 'X'), (Key::C, 'c', 'C'), (Key::V, 'v', 'V'),
+    (Key::B, 'b', 'B'),
+    (Key::N, 'n', 'N'), (Key::M, 'm', 'M'), (Key::Comma, ',', '<'), (Key::Period, '.', '>'),
+    (Key::Slash, '/', '?'),
+    (Key::Space, ' ', ' '),
+];
+
+#[rustfmt::skip]
+const RU_KEYS: &[(Key, char, char)] = &[
+    (Key::Digit1, '1', '!'), (Key::Digit2, '2', '"'), (Key::Digit3, '3', '№'),
+    (Key::Digit4, '4', ';'), (Key::Digit5, '5', '%'),
+    (Key::Digit6, '6', ':'), (Key::Digit7, '7', '?'), (Key::Digit8, '8', '*'),
+    (Key::Digit9, '9', '('), (Key::Digit0, '0', ')'),
+    (Key::Q, 'й', 'Й'), (Key::W, 'ц', 'Ц'), (Key::E, 'у', 'У'), (Key::R, 'к', 'К'),
+    (Key::T, 'е', 'Е'),
+    (Key::Y, 'н', 'Н'), (Key::U, 'г', 'Г'), (Key::I, 'ш', 'Ш'), (Key::O, 'щ', 'Щ'),
+    (Key::P, 'з', 'З'),
+    (Key::BracketLeft, 'х', 'Х'), (Key::BracketRight, 'ъ', 'Ъ'),
+    (Key::A, 'ф', 'Ф'), (Key::S, 'ы', 'Ы'), (Key::D, 'в', 'В'), (Key::F, 'а', 'А'),
+    (Key::G, 'п', 'П'),
+    (Key::H, 'р', 'Р'), (Key::J, 'о', 'О'), (Key::K, 'л', 'Л'), (Key::L, 'д', 'Д'),
+    (Key::Semicolon, 'ж', 'Ж'), (Key::Quote, 'э', 'Э'), (Key::Backquote, 'ё', 'Ё'),
+    (Key::Z, 'я', 'Я'), (Key::X, 'ч', 'Ч'), (Key::C, 'с', 'С'), (Key::V, 'м', 'М'),
+    (Key::B, 'и', 'И'),
+    (Key::N, 'т', 'Т'), (Key::M, 'ь', 'Ь'), (Key::Comma, 'б', 'Б'), (Key::Period, 'ю', 'Ю'),
+    (Key::Slash, '.', ','),
+    (Key::Space, ' ', ' '),
+];
+
+fn layout_keys(layout: &LayoutId) -> &'static [(Key, char, char)] {
+    match layout.primary_language() {
+        "ru" => RU_KEYS,
+        _ => US_KEYS,
+    }
+}
+
+// Физическая клавиша (и нужен ли Shift) для символа в раскладке.
+fn key_for_char(layout: &LayoutId, ch: char) -> Option<(Key, bool)> {
+    match ch {
+        '\u{8}' => return Some((Key::Backspace, false)),
+        '\n' => return Some((Key::Enter, false)),
+        _ => {}
+    }
+
+    layout_keys(layout).iter().find_map(|&(key, base, shift)| {
+        if ch == base {
+            Some((key, false))
+        } else if ch == shift {
+            Some((key, true))
+        } else {
+            None
+        }
+    })
+}
+
+fn key_event(
+    modifiers: &mut Modifiers,
+    key: Key,
+    text: Option<char>,
+    is_key_down: bool,
+) -> KeyboardEvent {
+    modifiers.update(key, is_key_down);
+    KeyboardEvent {
+        key,
+        modifiers: *modifiers,
+        text,
+        is_key_down,
+        origin: KeyOrigin::Physical,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FakeCall {
+    SendBackspaces(usize),
+    SendUnicodeText(String),
+    SetLayout(LayoutId),
+    SendClipboardShortcut(ClipboardShortcut),
+}
+
+#[derive(Debug)]
+struct FakeState {
+    text: String,
+    // Сколько символов в конце поля выделено; набор и вставка заменяют выделенное.
+    selected: usize,
+    clipboard: Option<String>,
+    // Растёт с каждой записью в буфер, как GetClipboardSequenceNumber.
+    clipboard_sequence: u64,
+    // В буфере не текст (картинка): clipboard_text видит только `clipboard`.
+    clipboard_non_text: bool,
+    primary: Option<String>,
+    layouts: Vec<LayoutId>,
+    active_layout: LayoutId,
+    // Как в Windows и X11: текст нажатия учитывает CapsLock.
+    caps_lock: bool,
+    focus: ActiveWindowInfo,
+    // Окна, в которых уже был фокус; id окна — его номер здесь + 1.
+    windows: Vec<(Option<String>, String)>,
+    // Текст полей окон не в фокусе.
+    background_texts: HashMap<u64, String>,
+    calls: Vec<FakeCall>,
+    hook_tx: Option<mpsc::Sender<AppEvent>>,
+}
+
+// Бэкенд для тестов: моделирует текстовое поле окна в фокусе, курсор всегда в конце.
+// У каждого окна своё поле.
+#[derive(Debug, Clone)]
+pub struct FakePlatform {
+    state: Arc<Mutex<FakeState>>,
+}
+
+impl Default for FakePlatform {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FakePlatform {
+    // Раскладки XKB `us` + `ru`, активна `us`, в фокусе безымянное окно.
+    pub fn new() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(FakeState {
+                text: String::new(),
+                selected: 0,
+                clipboard: None,
+                clipboard_sequence: 0,
+                clipboard_non_text: false,
+                primary: None,
+                layouts: vec![LayoutId::from_xkb("us"), LayoutId::from_xkb("ru")],
+                active_layout: LayoutId::from_xkb("us"),
+                caps_lock: false,
+                focus: ActiveWindowInfo {
+                    title: String::new(),
+                    process_name: None,
+                },
+                windows: vec![(None, String::new())],
+                background_texts: HashMap::new(),
+                calls: Vec::new(),
+                hook_tx: None,
+            })),
+        }
+    }
+
+    pub fn platform(&self) -> Platform {
+        Platform::from_backend(Arc::new(self.clone()))
+    }
+
+    fn lock(&self) -> MutexGuard<'_, FakeState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn text(&self) -> String {
+        self.lock().text.clone()
+    }
+
+    pub fn set_text(&self, text: &str) {
+        let mut state = self.lock();
+        state.text = text.to_string();
+        state.selected = 0;
+    }
+
+    // Выделяет `count` последних символов поля; выделенное попадает и в PRIMARY, как в X11.
+    pub fn select_last(&self, count: usize) {
+        let mut state = self.lock();
+        state.selected = count.min(state.text.chars().count());
+        let selected = state.selected_text();
+        state.primary = Some(selected);
+    }
+
+    pub fn clipboard(&self) -> Option<String> {
+        self.lock().clipboard.clone()
+    }
+
+    pub fn set_clipboard(&self, text: Option<&str>) {
+        self.lock().write_clipboard(text.map(str::to_string));
+    }
+
+    // Кладёт в буфер картинку: текста в нём нет, записать текст значит её потерять.
+    pub fn set_clipboard_image(&self) {
+        let mut state = self.lock();
+        state.write_clipboard(None);
+        state.clipboard_non_text = true;
+    }
+
+    // Картинка, положенная set_clipboard_image, всё ещё в буфере.
+    pub fn clipboard_has_image(&self) -> bool {
+        self.lock().clipboard_non_text
+    }
+
+    pub fn active_layout(&self) -> LayoutId {
+        self.lock().active_layout.clone()
+    }
+
+    pub fn set_active_layout(&self, layout: &LayoutId) {
+        self.lock().active_layout = layout.clone();
+    }
+
+    pub fn set_layouts(&self, layouts: &[LayoutId]) {
+        self.lock().layouts = layouts.to_vec();
+    }
+
+    // Переводит фокус в окно (то же окно — те же процесс и заголовок) и возвращает
+    // `FocusChanged`, как его прислал бы hook (туда событие тоже уходит).
+    pub fn set_focus(&self, process_name: Option<&str>, title: &str) -> AppEvent {
+        let mut state = self.lock();
+        let previous = state.window_id();
+        let text = std::mem::take(&mut state.text);
+        state.background_texts.insert(previous, text);
+        state.selected = 0;
+
+        state.focus = ActiveWindowInfo {
+            title: title.to_string(),
+            process_name: process_name.map(str::to_string),
+        };
+        let window_id = state.window_id();
+        state.text = state.background_texts.remove(&window_id).unwrap_or_default();
+
+        let event = AppEvent::FocusChanged {
+            process: state.focus.process_name.clone(),
+            title: state.focus.title.clone(),
+            window_id,
+        };
+        if let Some(tx) = state.hook_tx.as_ref() {
+            let _ = tx.send(event.clone());
+        }
+        event
+    }
+
+    // Нажимает CapsLock; следующие `type_text` набирают буквы с учётом него.
+    pub fn toggle_caps_lock(&self) -> Vec<KeyboardEvent> {
+        let mut state = self.lock();
+        state.caps_lock = !state.caps_lock;
+
+        let mut modifiers = Modifiers::default();
+        let events = vec![
+            key_event(&mut modifiers, Key::CapsLock, None, true),
+            key_event(&mut modifiers, Key::CapsLock, None, false),
+        ];
+        if let Some(tx) = state.hook_tx.as_ref() {
+            for event in &events {
+                let _ = tx.send(AppEvent::Keyboard(event.clone()));
+            }
+        }
+        events
+    }
+
+    // Клик в поле. Курсор модель оставляет в конце, но hook событие получает (и оно возвращается).
+    pub fn click(&self) -> AppEvent {
+        if let Some(tx) = self.lock().hook_tx.as_ref() {
+            let _ = tx.send(AppEvent::PointerActivity);
+        }
+        AppEvent::PointerActivity
+    }
+
+    pub fn calls(&self) -> Vec<FakeCall> {
+        self.lock().calls.clone()
+    }
+
+    pub fn clear_calls(&self) {
+        self.lock().calls.clear();
+    }
+
+    // Набирает `text` в активной раскладке так, как это сделал бы пользователь:
+    // поле меняется, а нажатия возвращаются (и уходят в запущенный hook).
+    // '\u{8}' — Backspace, '\n' — Enter. Символа нет в раскладке — panic, это ошибка теста.
+    pub fn type_text(&self, text: &str) -> Vec<KeyboardEvent> {
+        let mut state = self.lock();
+        let mut events = Vec::new();
+        let mut modifiers = Modifiers::default();
+
+        for ch in text.chars() {
+            let Some((key, shift)) = key_for_char(&state.active_layout, ch) else {
+                panic!("'{ch}' cannot be typed in layout {}", state.active_layout);
+            };
+            // С CapsLock заглавная буква набирается без Shift, строчная — с ним.
+            let shift = shift ^ (state.caps_lock && (ch.is_lowercase() || ch.is_uppercase()));
+            let text = Some(ch).filter(|ch| !ch.is_control());
+
+            if shift {
+                events.push(key_event(&mut modifiers, Key::ShiftLeft, None, true));
+            }
+            events.push(key_event(&mut modifiers, key, text, true));
+            events.push(key_event(&mut modifiers, key, None, false));
+            if shift {
+                events.push(key_event(&mut modifiers, Key::ShiftLeft, None, false));
+            }
+
+            match ch {
+                '\u{8}' => state.backspace(),
+                _ => state.insert(&ch.to_string()),
+            }
+        }
+
+        if let Some(tx) = state.hook_tx.as_ref() {
+            for event in &events {
+                let _ = tx.send(AppEvent::Keyboard(event.clone()));
+            }
+        }
+
+        events
+    }
+
+    fn is_forbidden(&self, forbidden: &ForbiddenContextsConfig) -> bool {
+        self.lock().focus.is_forbidden(forbidden)
+    }
+}
+
+impl FakeState {
+    fn window_id(&mut self) -> u64 {
+        let window = (self.focus.process_name.clone(), self.focus.title.clone());
+        let index = match self.windows.iter().position(|known| *known == window) {
+            Some(index) => index,
+            None => {
+                self.windows.push(window);
+                self.windows.len() - 1
+            }
+        };
+        index as u64 + 1
+    }
+
+    // Новое содержимое буфера — всегда текст: прежняя картинка пропадает.
+    fn write_clipboard(&mut self, text: Option<String>) {
+        self.clipboard = text;
+        self.clipboard_non_text = false;
+        self.clipboard_sequence += 1;
+    }
+
+    fn selected_text(&self) -> String {
+        let start = self.text.chars().count() - self.selected;
+        self.text.chars().skip(start).collect()
+    }
+
+    fn delete_selection(&mut self) {
+        for _ in 0..std::mem::take(&mut self.selected) {
+            self.text.pop();
+        }
+    }
+
+    fn insert(&mut self, text: &str) {
+        self.delete_selection();
+        self.text.push_str(text);
+    }
+
+    fn backspace(&mut self) {
+        if self.selected > 0 {
+            self.delete_selection();
+        } else {
+            self.text.pop();
+        }
+    }
+}
+
+impl PlatformBackend for FakePlatform {
+    fn name(&self) -> &'static str {
+        "fake"
+    }
+
+    fn start_keyboard_hook(&self) -> anyhow::Result<KeyboardHook> {
+        let (events_tx, events_rx) = mpsc::channel::<AppEvent>();
+        self.lock().hook_tx = Some(events_tx);
+
+        let state = self.state.clone();
+        let controller = KeyboardHookController::new(move || {
+            if let Ok(mut state) = state.lock() {
+                state.hook_tx = None;
+            }
+        });
+
+        Ok(KeyboardHook::new(controller, events_rx))
+    }
+
+    fn switch_to_next_layout(&self, forbidden: &ForbiddenContextsConfig) -> anyhow::Result<bool> {
+        if self.is_forbidden(forbidden) {
+            return Ok(false);
+        }
+
+        let mut state = self.lock();
+        let Some(current) = state
+            .layouts
+            .iter()
+            .position(|l| *l == state.active_layout)
+        else {
+            return Ok(false);
+        };
+        state.active_layout = state.layouts[(current + 1) % state.layouts.len()].clone();
+        Ok(true)
+    }
+
+    fn list_layouts(&self) -> anyhow::Result<Vec<LayoutId>> {
+        Ok(self.lock().layouts.clone())
+    }
+
+    fn active_layout(&self) -> anyhow::Result<LayoutId> {
+        Ok(self.lock().active_layout.clone())
+    }
+
+    fn set_layout(
+        &self,
+        forbidden: &ForbiddenContextsConfig,
+        layout: &LayoutId,
+    ) -> anyhow::Result<bool> {
+        self.lock().calls.push(FakeCall::SetLayout(layout.clone()));
+        if self.is_forbidden(forbidden) {
+            return Ok(false);
+        }
+
+        let mut state = self.lock();
+        if !state.layouts.contains(layout) {
+            return Ok(false);
+        }
+        state.active_layout = layout.clone();
+        Ok(true)
+    }
+
+    fn send_backspaces(
+        &self,
+        forbidden: &ForbiddenContextsConfig,
+        count: usize,
+    ) -> anyhow::Result<bool> {
+        self.lock().calls.push(FakeCall::SendBackspaces(count));
+        if self.is_forbidden(forbidden) {
+            return Ok(false);
+        }
+
+        let mut state = self.lock();
+        for _ in 0..count {
+            state.backspace();
+        }
+        Ok(true)
+    }
+
+    fn send_unicode_text(
+        &self,
+        forbidden: &ForbiddenContextsConfig,
+        text: &str,
+    ) -> anyhow::Result<bool> {
+        self.lock()
+            .calls
+            .push(FakeCall::SendUnicodeText(text.to_string()));
+        if self.is_forbidden(forbidden) {
+            return Ok(false);
+        }
+
+        self.lock().insert(text);
+        Ok(true)
+    }
+
+    fn is_forbidden_context(&self, forbidden: &ForbiddenContextsConfig) -> anyhow::Result<bool> {
+        Ok(self.is_forbidden(forbidden))
+    }
+
+    fn active_window(&self) -> anyhow::Result<Option<ActiveWindowInfo>> {
+        Ok(Some(self.lock().focus.clone()))
+    }
+
+    fn clipboard_text(&self, selection: Selection) -> anyhow::Result<Option<String>> {
+        let state = self.lock();
+        Ok(match selection {
+            Selection::Clipboard => state.clipboard.clone(),
+            Selection::Primary => state.primary.clone(),
+        })
+    }
+
+    fn clipboard_state(&self, selection: Selection) -> anyhow::Result<Option<ClipboardState>> {
+        let state = self.lock();
+        Ok(match selection {
+            Selection::Clipboard => Some(ClipboardState {
+                sequence: state.clipboard_sequence,
+                has_non_text: state.clipboard_non_text,
+            }),
+            Selection::Primary => None,
+        })
+    }
+
+    fn set_clipboard_text(&self, selection: Selection, text: &str) -> anyhow::Result<bool> {
+        let mut state = self.lock();
+        match selection {
+            Selection::Clipboard => state.write_clipboard(Some(text.to_string())),
+            Selection::Primary => state.primary = Some(text.to_string()),
+        }
+        Ok(true)
+    }
+
+    fn send_clipboard_shortcut(
+        &self,
+        forbidden: &ForbiddenContextsConfig,
+        shortcut: ClipboardShortcut,
+    ) -> anyhow::Result<bool> {
+        self.lock().calls.push(FakeCall::SendClipboardShortcut(shortcut));
+        if self.is_forbidden(forbidden) {
+            return Ok(false);
+        }
+
+        let mut state = self.lock();
+        match shortcut {
+            // Без выделения приложение ничего не копирует — буфер не меняется.
+            ClipboardShortcut::Copy if state.selected > 0 => {
+                let selected = state.selected_text();
+                state.write_clipboard(Some(selected));
+            }
+            ClipboardShortcut::Copy => {}
+            ClipboardShortcut::Paste => {
+                let text = state.clipboard.clone().unwrap_or_default();
+                state.insert(&text);
+            }
+        }
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn typing_and_injection_edit_the_field() {
+        let fake = FakePlatform::new();
+        let platform = fake.platform();
+        let forbidden = ForbiddenContextsConfig::default();
+        let ru = LayoutId::from_xkb("ru");
+
+        let events = fake.type_text("Ghbdtn ");
+        assert_eq!(fake.text(), "Ghbdtn ");
+        assert_eq!(events.first().map(|e| e.key), Some(Key::ShiftLeft));
+        assert_eq!(events[1].text, Some('G'));
+        assert!(events[1].modifiers.shift);
+
+        assert!(platform.send_backspaces(&forbidden, 7).unwrap());
+        assert!(platform.set_layout(&forbidden, &ru).unwrap());
+        assert!(platform.send_unicode_text(&forbidden, "Привет ").unwrap());
+        assert_eq!(fake.text(), "Привет ");
+
+        fake.type_text("мир");
+        assert_eq!(fake.text(), "Привет мир");
+
+        assert_eq!(
+            fake.calls(),
+            vec![
+                FakeCall::SendBackspaces(7),
+                FakeCall::SetLayout(ru),
+                FakeCall::SendUnicodeText("Привет ".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn copy_and_paste_replace_the_selection() {
+        let fake = FakePlatform::new();
+        let platform = fake.platform();
+        let forbidden = ForbiddenContextsConfig::default();
+
+        fake.type_text("say ghbdtn");
+        fake.select_last(6);
+        assert_eq!(
+            platform.clipboard_text(Selection::Primary).unwrap().as_deref(),
+            Some("ghbdtn")
+        );
+
+        assert!(platform.send_clipboard_shortcut(&forbidden, ClipboardShortcut::Copy).unwrap());
+        assert_eq!(fake.clipboard().as_deref(), Some("ghbdtn"));
+
+        assert!(platform.set_clipboard_text(Selection::Clipboard, "привет").unwrap());
+        assert!(platform.send_clipboard_shortcut(&forbidden, ClipboardShortcut::Paste).unwrap());
+        assert_eq!(fake.text(), "say привет");
+
+        // Выделение уже заменено — набор дописывает в конец.
+        fake.type_text("!");
+        assert_eq!(fake.text(), "say привет!");
+    }
+
+    #[test]
+    fn forbidden_focus_blocks_edits_but_records_calls() {
+        let fake = FakePlatform::new();
+        let platform = fake.platform();
+        let forbidden = ForbiddenContextsConfig {
+            blocked_processes: vec!["keepass".to_string()],
+            ..ForbiddenContextsConfig::default()
+        };
+
+        fake.set_focus(Some("KeePassXC"), "Database");
+        fake.type_text("secret");
+
+        assert!(platform.is_forbidden_context(&forbidden).unwrap());
+        assert!(!platform.send_backspaces(&forbidden, 6).unwrap());
+        assert_eq!(fake.text(), "secret");
+        assert_eq!(fake.calls(), vec![FakeCall::SendBackspaces(6)]);
+    }
+
+    #[test]
+    fn each_window_keeps_its_own_text() {
+        let fake = FakePlatform::new();
+        let hook = fake.platform().start_keyboard_hook().unwrap();
+        let (_controller, events) = hook.into_parts();
+
+        let editor = fake.set_focus(Some("code"), "main.rs");
+        fake.type_text("ghb");
+        let browser = fake.set_focus(Some("firefox"), "Search");
+        fake.type_text("hi");
+        assert_eq!(fake.text(), "hi");
+
+        let back = fake.set_focus(Some("code"), "main.rs");
+        assert_eq!(fake.text(), "ghb");
+
+        let window = |event: &AppEvent| match event {
+            AppEvent::FocusChanged { window_id, .. } => *window_id,
+            other => panic!("unexpected {other:?}"),
+        };
+        assert_eq!(window(&editor), window(&back));
+        assert_ne!(window(&editor), window(&browser));
+
+        // Смена фокуса уходит и в hook, вперемешку с нажатиями.
+        let focus_changes: Vec<u64> = events
+            .try_iter()
+            .filter(|event| matches!(event, AppEvent::FocusChanged { .. }))
+            .map(|event| window(&event))
+            .collect();
+        assert_eq!(focus_changes, vec![window(&editor), window(&browser), window(&back)]);
+    }
+}
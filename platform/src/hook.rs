@@ -0,0 +1,46 @@
+use std::sync::mpsc;
+
+use smart_switcher_shared_types::AppEvent;
+
+pub struct KeyboardHookController {
+    stop: Option<Box<dyn FnOnce() + Send>>,
+}
+
+impl KeyboardHookController {
+    pub fn new(stop: impl FnOnce() + Send + 'static) -> Self {
+        Self {
+            stop: Some(Box::new(stop)),
+        }
+    }
+
+    pub fn stop(mut self) {
+        if let Some(stop) = self.stop.take() {
+            stop();
+        }
+    }
+}
+
+impl Drop for KeyboardHookController {
+    fn drop(&mut self) {
+        if let Some(stop) = self.stop.take() {
+            stop();
+        }
+    }
+}
+
+// События из хука: нажатия (`AppEvent::Keyboard`) и, где бэкенд их видит, смена фокуса
+// и кнопки мыши.
+pub struct KeyboardHook {
+    controller: KeyboardHookController,
+    events: mpsc::Receiver<AppEvent>,
+}
+
+impl KeyboardHook {
+    pub fn new(controller: KeyboardHookController, events: mpsc::Receiver<AppEvent>) -> Self {
+        Self { controller, events }
+    }
+
+    pub fn into_parts(self) -> (KeyboardHookController, mpsc::Receiver<AppEvent>) {
+        (self.controller, self.events)
+    }
+}
@@ -1,110 +1,208 @@
-#[derive(Debug, Default, Clone)]
-pub struct Platform;
+mod backend;
+mod clipboard;
+mod context;
+mod fake;
+mod hook;
+
+use std::sync::Arc;
+
+use smart_switcher_shared_types::LayoutId;
+use smart_switcher_shared_types::config::ForbiddenContextsConfig;
+
+pub use backend::{NullBackend, PlatformBackend};
+pub use clipboard::{ClipboardShortcut, ClipboardState, Selection};
+pub use context::ActiveWindowInfo;
+pub use fake::{FakeCall, FakePlatform};
+pub use hook::{KeyboardHook, KeyboardHookController};
+
+#[derive(Clone)]
+pub struct Platform {
+    backend: Arc<dyn PlatformBackend>,
+}
 
 impl Platform {
+    // Бэкенд выбирается при запуске: на Linux по XDG_SESSION_TYPE с откатом на следующий.
     pub fn new() -> Self {
-        Self
+        Self {
+            backend: detect_backend(),
+        }
+    }
+
+    pub fn from_backend(backend: Arc<dyn PlatformBackend>) -> Self {
+        Self { backend }
     }
 
-    #[cfg(target_os = "windows")]
-    pub fn start_keyboard_hook(&self) -> anyhow::Result<windows::KeyboardHook> {
-        windows::start_keyboard_hook()
+    pub fn backend_name(&self) -> &'static str {
+        self.backend.name()
+    }
+
+    pub fn start_keyboard_hook(&self) -> anyhow::Result<KeyboardHook> {
+        self.backend.start_keyboard_hook()
     }
 
-    #[cfg(target_os = "windows")]
     pub fn switch_to_next_layout(
         &self,
-        forbidden: &smart_switcher_shared_types::config::ForbiddenContextsConfig,
+        forbidden: &ForbiddenContextsConfig,
     ) -> anyhow::Result<bool> {
-        windows::switch_to_next_layout(forbidden)
+        self.backend.switch_to_next_layout(forbidden)
+    }
+
+    pub fn list_layouts(&self) -> anyhow::Result<Vec<LayoutId>> {
+        self.backend.list_layouts()
     }
 
-    #[cfg(target_os = "windows")]
-    pub fn get_active_lang_id(&self) -> anyhow::Result<u16> {
-        windows::get_active_lang_id()
+    pub fn active_layout(&self) -> anyhow::Result<LayoutId> {
+        self.backend.active_layout()
     }
 
-    #[cfg(target_os = "windows")]
-    pub fn set_layout_by_lang_id(
+    pub fn set_layout(
         &self,
-        forbidden: &smart_switcher_shared_types::config::ForbiddenContextsConfig,
-        lang_id: u16,
+        forbidden: &ForbiddenContextsConfig,
+        layout: &LayoutId,
     ) -> anyhow::Result<bool> {
-        windows::set_layout_by_lang_id(forbidden, lang_id)
+        self.backend.set_layout(forbidden, layout)
     }
 
-    #[cfg(target_os = "windows")]
     pub fn send_backspaces(
         &self,
-        forbidden: &smart_switcher_shared_types::config::ForbiddenContextsConfig,
+        forbidden: &ForbiddenContextsConfig,
         count: usize,
     ) -> anyhow::Result<bool> {
-        windows::send_backspaces(forbidden, count)
+        self.backend.send_backspaces(forbidden, count)
     }
 
-    #[cfg(target_os = "windows")]
     pub fn send_unicode_text(
         &self,
-        forbidden: &smart_switcher_shared_types::config::ForbiddenContextsConfig,
+        forbidden: &ForbiddenContextsConfig,
         text: &str,
     ) -> anyhow::Result<bool> {
-        windows::send_unicode_text(forbidden, text)
+        self.backend.send_unicode_text(forbidden, text)
     }
 
-    #[cfg(target_os = "windows")]
     pub fn is_forbidden_context(
         &self,
-        forbidden: &smart_switcher_shared_types::config::ForbiddenContextsConfig,
+        forbidden: &ForbiddenContextsConfig,
     ) -> anyhow::Result<bool> {
-        windows::is_forbidden_context(forbidden)
+        self.backend.is_forbidden_context(forbidden)
     }
 
-    #[cfg(not(target_os = "windows"))]
-    pub fn switch_to_next_layout(
-        &self,
-        _forbidden: &smart_switcher_shared_types::config::ForbiddenContextsConfig,
-    ) -> anyhow::Result<bool> {
-        Ok(false)
+    pub fn active_window(&self) -> anyhow::Result<Option<ActiveWindowInfo>> {
+        self.backend.active_window()
     }
 
-    #[cfg(not(target_os = "windows"))]
-    pub fn get_active_lang_id(&self) -> anyhow::Result<u16> {
-        Ok(0)
+    pub fn clipboard_text(&self, selection: Selection) -> anyhow::Result<Option<String>> {
+        self.backend.clipboard_text(selection)
     }
 
-    #[cfg(not(target_os = "windows"))]
-    pub fn set_layout_by_lang_id(
-        &self,
-        _forbidden: &smart_switcher_shared_types::config::ForbiddenContextsConfig,
-        _lang_id: u16,
-    ) -> anyhow::Result<bool> {
-        Ok(false)
+    pub fn clipboard_state(&self, selection: Selection) -> anyhow::Result<Option<ClipboardState>> {
+        self.backend.clipboard_state(selection)
     }
 
-    #[cfg(not(target_os = "windows"))]
-    pub fn send_backspaces(
-        &self,
-        _forbidden: &smart_switcher_shared_types::config::ForbiddenContextsConfig,
-        _count: usize,
-    ) -> anyhow::Result<bool> {
-        Ok(false)
+    pub fn set_clipboard_text(&self, selection: Selection, text: &str) -> anyhow::Result<bool> {
+        self.backend.set_clipboard_text(selection, text)
     }
 
-    #[cfg(not(target_os = "windows"))]
-    pub fn send_unicode_text(
+    pub fn send_clipboard_shortcut(
         &self,
-        _forbidden: &smart_switcher_shared_types::config::ForbiddenContextsConfig,
-        _text: &str,
+        forbidden: &ForbiddenContextsConfig,
+        shortcut: ClipboardShortcut,
     ) -> anyhow::Result<bool> {
-        Ok(false)
+        self.backend.send_clipboard_shortcut(forbidden, shortcut)
     }
+}
 
-    #[cfg(not(target_os = "windows"))]
-    pub fn is_forbidden_context(
-        &self,
-        _forbidden: &smart_switcher_shared_types::config::ForbiddenContextsConfig,
-    ) -> anyhow::Result<bool> {
-        Ok(false)
+impl Default for Platform {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for Platform {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Platform")
+            .field("backend", &self.backend.name())
+            .finish()
+    }
+}
+
+fn detect_backend() -> Arc<dyn PlatformBackend> {
+    for name in backend_candidates() {
+        match connect_backend(name) {
+            Some(Ok(backend)) => {
+                tracing::info!(backend = name, "platform backend selected");
+                return backend;
+            }
+            Some(Err(e)) => {
+                tracing::warn!(backend = name, error = %e, "platform backend is unavailable, trying next");
+            }
+            None => {}
+        }
+    }
+
+    tracing::warn!("no platform backend is available, keyboard features are disabled");
+    Arc::new(NullBackend)
+}
+
+#[cfg(target_os = "windows")]
+fn backend_candidates() -> Vec<&'static str> {
+    vec!["windows"]
+}
+
+#[cfg(target_os = "linux")]
+fn backend_candidates() -> Vec<&'static str> {
+    let session = linux_session_type(
+        std::env::var("XDG_SESSION_TYPE").ok().as_deref(),
+        std::env::var_os("WAYLAND_DISPLAY").is_some(),
+        std::env::var_os("DISPLAY").is_some(),
+    );
+    linux_backend_order(session).to_vec()
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+fn backend_candidates() -> Vec<&'static str> {
+    Vec::new()
+}
+
+// XDG_SESSION_TYPE выставляет logind; без него (например, при запуске из ssh)
+// определяем сессию по переменным дисплея.
+#[cfg(any(target_os = "linux", test))]
+fn linux_session_type(
+    xdg_session_type: Option<&str>,
+    wayland_display: bool,
+    display: bool,
+) -> &'static str {
+    match xdg_session_type {
+        Some("wayland") => "wayland",
+        Some("x11") => "x11",
+        Some("tty") => "tty",
+        _ if wayland_display => "wayland",
+        _ if display => "x11",
+        _ => "tty",
+    }
+}
+
+// X11-бэкенд под Wayland видел бы только XWayland-клиентов, поэтому откатываемся сразу на evdev.
+#[cfg(any(target_os = "linux", test))]
+fn linux_backend_order(session: &str) -> &'static [&'static str] {
+    match session {
+        "wayland" => &["wayland", "evdev"],
+        "x11" => &["x11", "evdev"],
+        _ => &["evdev"],
+    }
+}
+
+// None — бэкенд не собран (нет нужной feature или другая ОС).
+fn connect_backend(name: &str) -> Option<anyhow::Result<Arc<dyn PlatformBackend>>> {
+    match name {
+        #[cfg(target_os = "windows")]
+        "windows" => Some(Ok(Arc::new(windows::WindowsBackend))),
+        #[cfg(all(target_os = "linux", feature = "x11"))]
+        "x11" => Some(linux_x11::X11Backend::connect().map(|b| Arc::new(b) as _)),
+        #[cfg(all(target_os = "linux", feature = "wayland"))]
+        "wayland" => Some(linux_wayland::WaylandBackend::connect().map(|b| Arc::new(b) as _)),
+        #[cfg(all(target_os = "linux", feature = "evdev"))]
+        "evdev" => Some(linux_evdev::EvdevBackend::connect().map(|b| Arc::new(b) as _)),
+        _ => None,
     }
 }
 
@@ -114,8 +212,54 @@ pub mod windows;
 #[cfg(target_os = "macos")]
 pub mod macos;
 
+#[cfg(all(
+    target_os = "linux",
+    any(feature = "x11", feature = "wayland", feature = "evdev")
+))]
+mod linux_keys;
+
 #[cfg(all(target_os = "linux", feature = "x11"))]
 pub mod linux_x11;
 
+#[cfg(all(target_os = "linux", feature = "x11"))]
+mod linux_x11_selection;
+
 #[cfg(all(target_os = "linux", feature = "wayland"))]
 pub mod linux_wayland;
+
+#[cfg(all(target_os = "linux", feature = "evdev"))]
+pub mod linux_evdev;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn session_type_prefers_xdg_then_display_variables() {
+        assert_eq!(linux_session_type(Some("wayland"), false, true), "wayland");
+        assert_eq!(linux_session_type(Some("x11"), true, true), "x11");
+        assert_eq!(linux_session_type(Some("tty"), true, true), "tty");
+        assert_eq!(linux_session_type(None, true, true), "wayland");
+        assert_eq!(linux_session_type(Some("unspecified"), false, true), "x11");
+        assert_eq!(linux_session_type(None, false, false), "tty");
+    }
+
+    #[test]
+    fn wayland_session_never_falls_back_to_x11() {
+        assert_eq!(linux_backend_order("wayland"), &["wayland", "evdev"]);
+        assert_eq!(linux_backend_order("x11"), &["x11", "evdev"]);
+        assert_eq!(linux_backend_order("tty"), &["evdev"]);
+    }
+
+    #[test]
+    fn platform_forwards_to_null_backend() {
+        let platform = Platform::from_backend(Arc::new(NullBackend));
+        let forbidden = ForbiddenContextsConfig::default();
+
+        assert_eq!(platform.backend_name(), "null");
+        assert!(platform.start_keyboard_hook().is_err());
+        assert!(!platform.send_unicode_text(&forbidden, "text").unwrap());
+        assert!(platform.active_layout().is_err());
+        assert!(platform.list_layouts().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,786 @@
+use std::{
+    collections::HashSet,
+    os::fd::AsRawFd,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc, Mutex, MutexGuard, Once,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use anyhow::Context;
+use evdev::{
+    uinput::VirtualDevice, AttributeSet, BusType, Device, EventSummary, EventType, InputEvent, KeyCode,
+};
+use smart_switcher_shared_types::config::ForbiddenContextsConfig;
+use smart_switcher_shared_types::{AppEvent, Key, KeyOrigin, KeyboardEvent, LayoutId, Modifiers};
+
+use crate::backend::PlatformBackend;
+use crate::clipboard::{ClipboardShortcut, ClipboardState, Selection};
+use crate::context::ActiveWindowInfo;
+use crate::linux_keys::xkb_layout_names;
+pub use crate::hook::{KeyboardHook, KeyboardHookController};
+
+const VIRTUAL_KEYBOARD_NAME: &str = "smart_switcher virtual keyboard";
+const POLL_TIMEOUT_MS: i32 = 100;
+const INPUT_DIR: &str = "/dev/input";
+// Как часто искать подключённые клавиатуры и мыши.
+const RESCAN_INTERVAL: Duration = Duration::from_secs(2);
+
+const KEY_TAB: u16 = 15;
+const KEY_ENTER: u16 = 28;
+const KEY_LEFTCTRL: u16 = 29;
+const KEY_LEFTSHIFT: u16 = 42;
+const KEY_RIGHTSHIFT: u16 = 54;
+const KEY_LEFTALT: u16 = 56;
+const KEY_SPACE: u16 = 57;
+const KEY_CAPSLOCK: u16 = 58;
+const KEY_RIGHTCTRL: u16 = 97;
+const KEY_RIGHTALT: u16 = 100;
+const KEY_LEFTMETA: u16 = 125;
+const KEY_RIGHTMETA: u16 = 126;
+const KEY_BACKSPACE: u16 = 14;
+
+// evdev code, base, shift для раскладок, которые умеем набирать через uinput.
+// uinput шлёт только физические клавиши, поэтому символ зависит от активной группы XKB.
+const US_KEYS: &[(u16, char, char)] = &[
+    (2, '1', '!'), (3, '2', '@'), (4, '3', '#'), (5, '4', '$'), (6, '5', '%'),
+    (7, '6', '^'), (8, '7', '&'), (9, '8', '*'), (10, '9', '('), (11, '0', ')'),
+    (12, '-', '_'), (13, '=', '+'),
+    (16, 'q', 'Q'), (17, 'w', 'W'), (18, 'e', 'E'), (19, 'r', 'R'), (20, 't', 'T'),
+    (21, 'y', 'Y'), (22, 'u', 'U'), (23, 'i', 'I'), (24, 'o', 'O'), (25, 'p', 'P'),
+    (26, '[', '{'), (27, ']', '}'),
+    (30, 'a', 'A'), (31, 's', 'S'), (32, 'd', 'D'), (33, 'f', 'F'), (34, 'g', 'G'),
+    (35, 'h', 'H'), (36, 'j', 'J'), (37, 'k', 'K'), (38, 'l', 'L'),
+    (39, ';', ':'), (40, '\'', '"'), (41, '`', '~'), (43, '\\', '|'),
+    (44, 'z', 'Z'), (45, 'x', 'X'), (46, 'c', 'C'), (47, 'v', 'V'), (48, 'b', 'B'),
+    (49, 'n', 'N'), (50, 'm', 'M'), (51, ',', '<'), (52, '.', '>'), (53, '/', '?'),
+    (57, ' ', ' '),
+];
+
+const RU_KEYS: &[(u16, char, char)] = &[
+    (2, '1', '!'), (3, '2', '"'), (4, '3', '№'), (5, '4', ';'), (6, '5', '%'),
+    (7, '6', ':'), (8, '7', '?'), (9, '8', '*'), (10, '9', '('), (11, '0', ')'),
+    (12, '-', '_'), (13, '=', '+'),
+    (16, 'й', 'Й'), (17, 'ц', 'Ц'), (18, 'у', 'У'), (19, 'к', 'К'), (20, 'е', 'Е'),
+    (21, 'н', 'Н'), (22, 'г', 'Г'), (23, 'ш', 'Ш'), (24, 'щ', 'Щ'), (25, 'з', 'З'),
+    (26, 'х', 'Х'), (27, 'ъ', 'Ъ'),
+    (30, 'ф', 'Ф'), (31, 'ы', 'Ы'), (32, 'в', 'В'), (33, 'а', 'А'), (34, 'п', 'П'),
+    (35, 'р', 'Р'), (36, 'о', 'О'), (37, 'л', 'Л'), (38, 'д', 'Д'),
+    (39, 'ж', 'Ж'), (40, 'э', 'Э'), (41, 'ё', 'Ё'), (43, '\\', '/'),
+    (44, 'я', 'Я'), (45, 'ч', 'Ч'), (46, 'с', 'С'), (47, 'м', 'М'), (48, 'и', 'И'),
+    (49, 'т', 'Т'), (50, 'ь', 'Ь'), (51, 'б', 'Б'), (52, 'ю', 'Ю'), (53, '.', ','),
+    (57, ' ', ' '),
+];
+
+// Таблицы только для базовых XKB раскладок без вариантов: "ru(phonetic)" сюда не попадает.
+fn layout_keys(layout: &str) -> Option<&'static [(u16, char, char)]> {
+    match layout {
+        "us" => Some(US_KEYS),
+        "ru" => Some(RU_KEYS),
+        _ => None,
+    }
+}
+
+fn key_for_char(layout: &str, ch: char) -> Option<(u16, bool)> {
+    match ch {
+        '\n' | '\r' => return Some((KEY_ENTER, false)),
+        '\t' => return Some((KEY_TAB, false)),
+        _ => {}
+    }
+
+    layout_keys(layout)?.iter().find_map(|&(code, base, shift)| {
+        if ch == base {
+            Some((code, false))
+        } else if ch == shift {
+            Some((code, true))
+        } else {
+            None
+        }
+    })
+}
+
+// Как XKB переключает группы (XKBOPTIONS grp:*). Без этого не можем
+// ни отследить текущую раскладку, ни переключить её через uinput.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum GroupToggle {
+    AltShift,
+    CtrlShift,
+    CapsLock,
+    WinSpace,
+}
+
+impl GroupToggle {
+    fn from_xkb_options(options: &str) -> Option<Self> {
+        options.split(',').find_map(|opt| match opt.trim() {
+            "grp:alt_shift_toggle" => Some(Self::AltShift),
+            "grp:ctrl_shift_toggle" => Some(Self::CtrlShift),
+            "grp:caps_toggle" => Some(Self::CapsLock),
+            "grp:win_space_toggle" => Some(Self::WinSpace),
+            _ => None,
+        })
+    }
+
+    fn chord(self) -> &'static [u16] {
+        match self {
+            Self::AltShift => &[KEY_LEFTALT, KEY_LEFTSHIFT],
+            Self::CtrlShift => &[KEY_LEFTCTRL, KEY_LEFTSHIFT],
+            Self::CapsLock => &[KEY_CAPSLOCK],
+            Self::WinSpace => &[KEY_LEFTMETA, KEY_SPACE],
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct HeldModifiers {
+    alt: bool,
+    shift: bool,
+    ctrl: bool,
+    meta: bool,
+}
+
+impl HeldModifiers {
+    fn update(&mut self, code: u16, is_down: bool) {
+        match code {
+            KEY_LEFTALT | KEY_RIGHTALT => self.alt = is_down,
+            KEY_LEFTSHIFT | KEY_RIGHTSHIFT => self.shift = is_down,
+            KEY_LEFTCTRL | KEY_RIGHTCTRL => self.ctrl = is_down,
+            KEY_LEFTMETA | KEY_RIGHTMETA => self.meta = is_down,
+            _ => {}
+        }
+    }
+
+    // Нажатие `code` при уже зажатых модификаторах `self` завершает аккорд переключения?
+    fn completes(&self, toggle: GroupToggle, code: u16) -> bool {
+        match toggle {
+            GroupToggle::AltShift => {
+                (matches!(code, KEY_LEFTALT | KEY_RIGHTALT) && self.shift)
+                    || (matches!(code, KEY_LEFTSHIFT | KEY_RIGHTSHIFT) && self.alt)
+            }
+            GroupToggle::CtrlShift => {
+                (matches!(code, KEY_LEFTCTRL | KEY_RIGHTCTRL) && self.shift)
+                    || (matches!(code, KEY_LEFTSHIFT | KEY_RIGHTSHIFT) && self.ctrl)
+            }
+            GroupToggle::CapsLock => code == KEY_CAPSLOCK,
+            GroupToggle::WinSpace => code == KEY_SPACE && self.meta,
+        }
+    }
+}
+
+// evdev не видит состояние XKB, поэтому активную группу отслеживаем сами
+// по аккорду переключения. Best-effort: переключение мышью/из трея не увидим.
+#[derive(Debug)]
+struct LayoutTracker {
+    layouts: Vec<LayoutId>,
+    toggle: Option<GroupToggle>,
+    group: usize,
+}
+
+impl LayoutTracker {
+    fn from_system() -> Self {
+        let mut layout = String::new();
+        let mut variant = String::new();
+        let mut options = String::new();
+
+        if let Ok(raw) = std::fs::read_to_string("/etc/default/keyboard") {
+            for line in raw.lines() {
+                let Some((key, value)) = line.split_once('=') else {
+                    continue;
+                };
+                let value = value.trim().trim_matches('"').to_string();
+                match key.trim() {
+                    "XKBLAYOUT" => layout = value,
+                    "XKBVARIANT" => variant = value,
+                    "XKBOPTIONS" => options = value,
+                    _ => {}
+                }
+            }
+        }
+
+        if let Ok(value) = std::env::var("XKB_DEFAULT_LAYOUT") {
+            layout = value;
+        }
+        if let Ok(value) = std::env::var("XKB_DEFAULT_VARIANT") {
+            variant = value;
+        }
+        if let Ok(value) = std::env::var("XKB_DEFAULT_OPTIONS") {
+            options = value;
+        }
+
+        let mut layouts: Vec<LayoutId> = xkb_layout_names(&layout, &variant)
+            .iter()
+            .map(|name| LayoutId::from_xkb(name))
+            .collect();
+        if layouts.is_empty() {
+            layouts.push(LayoutId::from_xkb("us"));
+        }
+
+        Self {
+            layouts,
+            toggle: GroupToggle::from_xkb_options(&options),
+            group: 0,
+        }
+    }
+
+    fn active_layout(&self) -> &LayoutId {
+        &self.layouts[self.group]
+    }
+
+    fn advance(&mut self) {
+        self.group = (self.group + 1) % self.layouts.len();
+    }
+}
+
+struct EvdevState {
+    device: Mutex<VirtualDevice>,
+    layout: Mutex<LayoutTracker>,
+}
+
+static STATE: Mutex<Option<Arc<EvdevState>>> = Mutex::new(None);
+
+fn state() -> anyhow::Result<Arc<EvdevState>> {
+    let mut guard = STATE
+        .lock()
+        .map_err(|_| anyhow::anyhow!("evdev state lock poisoned"))?;
+    if let Some(state) = guard.as_ref() {
+        return Ok(state.clone());
+    }
+
+    let mut keys = AttributeSet::<KeyCode>::new();
+    for code in 1..=127u16 {
+        keys.insert(KeyCode::new(code));
+    }
+
+    let device = VirtualDevice::builder()
+        .context("open /dev/uinput (needs write access, e.g. the `input` group or a udev rule)")?
+        .name(VIRTUAL_KEYBOARD_NAME)
+        .with_keys(&keys)?
+        .build()
+        .context("create uinput virtual keyboard")?;
+
+    let state = Arc::new(EvdevState {
+        device: Mutex::new(device),
+        layout: Mutex::new(LayoutTracker::from_system()),
+    });
+    *guard = Some(state.clone());
+    Ok(state)
+}
+
+fn is_keyboard(device: &Device) -> bool {
+    if device.name() == Some(VIRTUAL_KEYBOARD_NAME) {
+        return false;
+    }
+
+    device.supported_keys().is_some_and(|keys| {
+        keys.contains(KeyCode::KEY_A)
+            && keys.contains(KeyCode::KEY_Z)
+            && keys.contains(KeyCode::KEY_SPACE)
+            && keys.contains(KeyCode::KEY_ENTER)
+    })
+}
+
+// Мыши, тачпады и сенсорные экраны.
+fn is_pointer(device: &Device) -> bool {
+    device.supported_keys().is_some_and(|keys| {
+        keys.contains(KeyCode::BTN_LEFT) || keys.contains(KeyCode::BTN_TOUCH)
+    })
+}
+
+// Тап по тачпаду кнопкой становится только в libinput — здесь от него есть лишь BTN_TOUCH.
+// Касание без тапа курсор не двигает, но отличить их нельзя: считаем любое.
+fn is_pointer_button(code: u16) -> bool {
+    (KeyCode::BTN_LEFT.code()..=KeyCode::BTN_TASK.code()).contains(&code)
+        || code == KeyCode::BTN_TOUCH.code()
+}
+
+fn is_input_device(device: &Device) -> bool {
+    is_keyboard(device) || is_pointer(device)
+}
+
+fn event_node_paths() -> HashSet<PathBuf> {
+    std::fs::read_dir(INPUT_DIR)
+        .map(|entries| {
+            entries
+                .filter_map(Result::ok)
+                .map(|entry| entry.path())
+                .filter(|path| {
+                    path.file_name()
+                        .and_then(|name| name.to_str())
+                        .is_some_and(|name| name.starts_with("event"))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+// Открытые клавиатуры и мыши. Отключённые выбывают, подключённые подхватываются
+// при пересканировании `/dev/input`.
+struct InputDevices {
+    devices: Vec<(PathBuf, Device)>,
+    // Узлы, которые уже проверены (открыты или не подошли).
+    known: HashSet<PathBuf>,
+    scanned_at: Instant,
+}
+
+impl InputDevices {
+    // Без клавиатур хук бесполезен, без мышей — работает.
+    fn open() -> anyhow::Result<Self> {
+        let known = event_node_paths();
+        let devices: Vec<(PathBuf, Device)> = evdev::enumerate()
+            .filter(|(_, device)| is_input_device(device))
+            .collect();
+
+        if !devices.iter().any(|(_, device)| is_keyboard(device)) {
+            return Err(anyhow::anyhow!(
+                "no readable keyboards in /dev/input (needs read access, e.g. the `input` group)"
+            ));
+        }
+
+        for (_, device) in &devices {
+            device
+                .set_nonblocking(true)
+                .context("set evdev device non-blocking")?;
+        }
+
+        Ok(Self {
+            devices,
+            known,
+            scanned_at: Instant::now(),
+        })
+    }
+
+    fn poll_fds(&self) -> Vec<libc::pollfd> {
+        self.devices
+            .iter()
+            .map(|(_, device)| libc::pollfd {
+                fd: device.as_raw_fd(),
+                events: libc::POLLIN,
+                revents: 0,
+            })
+            .collect()
+    }
+
+    // Устройства с POLLHUP/POLLERR/POLLNVAL отключены: иначе poll возвращался бы сразу
+    // и поток крутился бы вхолостую. Возвращает, выбыло ли что-нибудь.
+    fn drop_lost(&mut self, fds: &[libc::pollfd]) -> bool {
+        let lost = libc::POLLHUP | libc::POLLERR | libc::POLLNVAL;
+        let before = self.devices.len();
+        let mut revents = fds.iter().map(|fd| fd.revents);
+        let known = &mut self.known;
+        self.devices.retain(|(path, device)| {
+            if revents.next().unwrap_or(0) & lost == 0 {
+                return true;
+            }
+            tracing::warn!(
+                device = device.name().unwrap_or(""),
+                path = %path.display(),
+                "evdev device disconnected"
+            );
+            // Узел может вернуться под тем же именем — проверим его заново.
+            known.remove(path);
+            false
+        });
+        self.devices.len() != before
+    }
+
+    fn rescan_if_due(&mut self) {
+        if self.scanned_at.elapsed() < RESCAN_INTERVAL {
+            return;
+        }
+        self.scanned_at = Instant::now();
+
+        let present = event_node_paths();
+        self.known.retain(|path| present.contains(path));
+        for path in present {
+            if !self.known.insert(path.clone()) {
+                continue;
+            }
+            let Ok(device) = Device::open(&path) else {
+                continue;
+            };
+            if !is_input_device(&device) || device.set_nonblocking(true).is_err() {
+                continue;
+            }
+            tracing::info!(
+                device = device.name().unwrap_or(""),
+                path = %path.display(),
+                "evdev device connected"
+            );
+            self.devices.push((path, device));
+        }
+    }
+}
+
+pub fn start_keyboard_hook() -> anyhow::Result<KeyboardHook> {
+    // Виртуальную клавиатуру создаём заранее: системе нужно время, чтобы её подхватить.
+    let state = state()?;
+    let mut devices = InputDevices::open()?;
+
+    let (events_tx, events_rx) = mpsc::channel::<AppEvent>();
+    let stop = Arc::new(AtomicBool::new(false));
+
+    let join = thread::spawn({
+        let stop = stop.clone();
+        move || {
+            let mut held = HeldModifiers::default();
+            let mut modifiers = Modifiers::default();
+
+            while !stop.load(Ordering::Relaxed) {
+                devices.rescan_if_due();
+                let mut fds = devices.poll_fds();
+
+                let ready = unsafe {
+                    libc::poll(fds.as_mut_ptr(), fds.len() as libc::nfds_t, POLL_TIMEOUT_MS)
+                };
+                if ready <= 0 {
+                    continue;
+                }
+
+                for ((_, device), fd) in devices.devices.iter_mut().zip(&fds) {
+                    if fd.revents & libc::POLLIN == 0 {
+                        continue;
+                    }
+
+                    // Своя виртуальная клавиатура сюда не попадает (`is_keyboard`),
+                    // значит, виртуальное устройство — чужое: ydotool, kmonad, ...
+                    let origin = if device.input_id().bus_type() == BusType::BUS_VIRTUAL {
+                        KeyOrigin::OtherInjection
+                    } else {
+                        KeyOrigin::Physical
+                    };
+                    let Ok(events) = device.fetch_events() else {
+                        continue;
+                    };
+
+                    for event in events {
+                        let EventSummary::Key(_, KeyCode(code), value) = event.destructure() else {
+                            continue;
+                        };
+
+                        if is_pointer_button(code) {
+                            if value == 1 {
+                                let _ = events_tx.send(AppEvent::PointerActivity);
+                            }
+                            continue;
+                        }
+
+                        // 0 = up, 1 = down, 2 = autorepeat (как повторный WM_KEYDOWN на Windows).
+                        let is_key_down = value != 0;
+
+                        if value == 1
+                            && let Ok(mut layout) = state.layout.lock()
+                            && let Some(toggle) = layout.toggle
+                            && held.completes(toggle, code)
+                        {
+                            layout.advance();
+                        }
+                        held.update(code, is_key_down);
+
+                        let Some(key) = Key::from_evdev(code) else {
+                            continue;
+                        };
+                        modifiers.update(key, is_key_down);
+
+                        // Раскладку evdev не знает, поэтому печатаемый символ не определяем.
+                        // Окна тоже: без композитора смены фокуса не видно.
+                        let _ = events_tx.send(AppEvent::Keyboard(KeyboardEvent {
+                            key,
+                            modifiers,
+                            text: None,
+                            is_key_down,
+                            origin,
+                        }));
+                    }
+                }
+
+                // Клавиша, зажатая на отключённой клавиатуре, уже не отпустится.
+                if devices.drop_lost(&fds) {
+                    held = HeldModifiers::default();
+                    modifiers = Modifiers::default();
+                }
+            }
+        }
+    });
+
+    let controller = KeyboardHookController::new(move || {
+        stop.store(true, Ordering::Relaxed);
+        let _ = join.join();
+    });
+
+    Ok(KeyboardHook::new(controller, events_rx))
+}
+
+fn key_event(code: u16, value: i32) -> InputEvent {
+    InputEvent::new(EventType::KEY.0, code, value)
+}
+
+fn emit_tap(device: &mut VirtualDevice, code: u16, shift: bool) -> anyhow::Result<()> {
+    if shift {
+        device.emit(&[key_event(KEY_LEFTSHIFT, 1)])?;
+    }
+    device.emit(&[key_event(code, 1)])?;
+    device.emit(&[key_event(code, 0)])?;
+    if shift {
+        device.emit(&[key_event(KEY_LEFTSHIFT, 0)])?;
+    }
+    Ok(())
+}
+
+fn emit_chord(device: &mut VirtualDevice, chord: &[u16]) -> anyhow::Result<()> {
+    for &code in chord {
+        device.emit(&[key_event(code, 1)])?;
+    }
+    for &code in chord.iter().rev() {
+        device.emit(&[key_event(code, 0)])?;
+    }
+    Ok(())
+}
+
+// evdev не знает активного окна. Fail-closed: если заданы запреты,
+// проверить их нельзя — значит контекст запрещён.
+pub fn is_forbidden_context(forbidden: &ForbiddenContextsConfig) -> anyhow::Result<bool> {
+    let forbidden =
+        !forbidden.blocked_processes.is_empty() || !forbidden.blocked_windows.is_empty();
+    // Иначе молчание выглядит как поломка: хук работает, а исправлений нет.
+    if forbidden {
+        static POLICY_WARNING: Once = Once::new();
+        POLICY_WARNING.call_once(|| {
+            tracing::warn!(
+                "evdev backend cannot see the active window: corrections are disabled while \
+                 blocked_processes or blocked_windows is not empty"
+            );
+        });
+    }
+    Ok(forbidden)
+}
+
+fn lock_layout(state: &EvdevState) -> anyhow::Result<MutexGuard<'_, LayoutTracker>> {
+    state
+        .layout
+        .lock()
+        .map_err(|_| anyhow::anyhow!("evdev layout lock poisoned"))
+}
+
+pub fn switch_to_next_layout(forbidden: &ForbiddenContextsConfig) -> anyhow::Result<bool> {
+    if is_forbidden_context(forbidden)? {
+        return Ok(false);
+    }
+
+    let state = state()?;
+    let mut layout = lock_layout(&state)?;
+    let Some(toggle) = layout.toggle else {
+        return Ok(false);
+    };
+    if layout.layouts.len() < 2 {
+        return Ok(false);
+    }
+
+    let mut device = state
+        .device
+        .lock()
+        .map_err(|_| anyhow::anyhow!("evdev device lock poisoned"))?;
+    emit_chord(&mut device, toggle.chord())?;
+    layout.advance();
+
+    Ok(true)
+}
+
+pub fn list_layouts() -> anyhow::Result<Vec<LayoutId>> {
+    let state = state()?;
+    Ok(lock_layout(&state)?.layouts.clone())
+}
+
+pub fn active_layout() -> anyhow::Result<LayoutId> {
+    let state = state()?;
+    Ok(lock_layout(&state)?.active_layout().clone())
+}
+
+pub fn set_layout(forbidden: &ForbiddenContextsConfig, target: &LayoutId) -> anyhow::Result<bool> {
+    if is_forbidden_context(forbidden)? {
+        return Ok(false);
+    }
+
+    let state = state()?;
+    let mut layout = lock_layout(&state)?;
+    let Some(toggle) = layout.toggle else {
+        return Ok(false);
+    };
+
+    let target = layout.layouts.iter().position(|l| l == target);
+    let Some(target) = target else {
+        return Ok(false);
+    };
+
+    let mut device = state
+        .device
+        .lock()
+        .map_err(|_| anyhow::anyhow!("evdev device lock poisoned"))?;
+    while layout.group != target {
+        emit_chord(&mut device, toggle.chord())?;
+        layout.advance();
+    }
+
+    Ok(true)
+}
+
+pub fn send_backspaces(forbidden: &ForbiddenContextsConfig, count: usize) -> anyhow::Result<bool> {
+    if is_forbidden_context(forbidden)? {
+        return Ok(false);
+    }
+
+    if count == 0 {
+        return Ok(true);
+    }
+
+    let state = state()?;
+    let mut device = state
+        .device
+        .lock()
+        .map_err(|_| anyhow::anyhow!("evdev device lock poisoned"))?;
+    for _ in 0..count {
+        emit_tap(&mut device, KEY_BACKSPACE, false)?;
+    }
+
+    Ok(true)
+}
+
+pub fn send_unicode_text(
+    forbidden: &ForbiddenContextsConfig,
+    text: &str,
+) -> anyhow::Result<bool> {
+    if is_forbidden_context(forbidden)? {
+        return Ok(false);
+    }
+
+    if text.is_empty() {
+        return Ok(true);
+    }
+
+    let state = state()?;
+    let keys: Option<Vec<(u16, bool)>> = {
+        let layout = state
+            .layout
+            .lock()
+            .map_err(|_| anyhow::anyhow!("evdev layout lock poisoned"))?;
+        text.chars()
+            .map(|ch| key_for_char(&layout.active_layout().variant, ch))
+            .collect()
+    };
+
+    // Символа нет в активной раскладке — ничего не печатаем, чтобы не оставить полслова.
+    let Some(keys) = keys else {
+        return Ok(false);
+    };
+
+    let mut device = state
+        .device
+        .lock()
+        .map_err(|_| anyhow::anyhow!("evdev device lock poisoned"))?;
+    for (code, shift) in keys {
+        emit_tap(&mut device, code, shift)?;
+    }
+
+    Ok(true)
+}
+
+pub struct EvdevBackend;
+
+impl EvdevBackend {
+    // Без доступа к /dev/uinput бэкенд бесполезен — проверяем это при выборе.
+    pub fn connect() -> anyhow::Result<Self> {
+        state()?;
+        Ok(Self)
+    }
+}
+
+impl PlatformBackend for EvdevBackend {
+    fn name(&self) -> &'static str {
+        "evdev"
+    }
+
+    fn start_keyboard_hook(&self) -> anyhow::Result<KeyboardHook> {
+        start_keyboard_hook()
+    }
+
+    fn switch_to_next_layout(&self, forbidden: &ForbiddenContextsConfig) -> anyhow::Result<bool> {
+        switch_to_next_layout(forbidden)
+    }
+
+    fn list_layouts(&self) -> anyhow::Result<Vec<LayoutId>> {
+        list_layouts()
+    }
+
+    fn active_layout(&self) -> anyhow::Result<LayoutId> {
+        active_layout()
+    }
+
+    fn set_layout(
+        &self,
+        forbidden: &ForbiddenContextsConfig,
+        layout: &LayoutId,
+    ) -> anyhow::Result<bool> {
+        set_layout(forbidden, layout)
+    }
+
+    fn send_backspaces(
+        &self,
+        forbidden: &ForbiddenContextsConfig,
+        count: usize,
+    ) -> anyhow::Result<bool> {
+        send_backspaces(forbidden, count)
+    }
+
+    fn send_unicode_text(
+        &self,
+        forbidden: &ForbiddenContextsConfig,
+        text: &str,
+    ) -> anyhow::Result<bool> {
+        send_unicode_text(forbidden, text)
+    }
+
+    fn is_forbidden_context(&self, forbidden: &ForbiddenContextsConfig) -> anyhow::Result<bool> {
+        is_forbidden_context(forbidden)
+    }
+
+    fn active_window(&self) -> anyhow::Result<Option<ActiveWindowInfo>> {
+        Ok(None)
+    }
+
+    // Буфер обмена без композитора (или без wlr-data-control) недоступен.
+    fn clipboard_text(&self, _selection: Selection) -> anyhow::Result<Option<String>> {
+        Ok(None)
+    }
+
+    fn clipboard_state(&self, _selection: Selection) -> anyhow::Result<Option<ClipboardState>> {
+        Ok(None)
+    }
+
+    fn set_clipboard_text(&self, _selection: Selection, _text: &str) -> anyhow::Result<bool> {
+        Ok(false)
+    }
+
+    fn send_clipboard_shortcut(
+        &self,
+        _forbidden: &ForbiddenContextsConfig,
+        _shortcut: ClipboardShortcut,
+    ) -> anyhow::Result<bool> {
+        Ok(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clicks_and_touches_are_pointer_buttons() {
+        assert!(is_pointer_button(KeyCode::BTN_LEFT.code()));
+        assert!(is_pointer_button(KeyCode::BTN_SIDE.code()));
+        assert!(is_pointer_button(KeyCode::BTN_TOUCH.code()));
+        // Сколько пальцев на тачпаде — не касание и не клик.
+        assert!(!is_pointer_button(KeyCode::BTN_TOOL_FINGER.code()));
+        assert!(!is_pointer_button(KeyCode::KEY_A.code()));
+    }
+}
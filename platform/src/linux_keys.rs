@@ -0,0 +1,28 @@
+// Списки XKBLAYOUT/XKBVARIANT ("us,ru" + ",phonetic") -> имена раскладок
+// с вариантом ("us", "ru(phonetic)"), как их понимает `LayoutId::from_xkb`.
+#[cfg(any(feature = "x11", feature = "evdev"))]
+pub(crate) fn xkb_layout_names(layouts: &str, variants: &str) -> Vec<String> {
+    let mut variants = variants.split(',').map(str::trim);
+    layouts
+        .split(',')
+        .map(str::trim)
+        .map(|layout| match variants.next() {
+            Some(variant) if !variant.is_empty() => format!("{layout}({variant})"),
+            _ => layout.to_string(),
+        })
+        .filter(|name| !name.is_empty())
+        .collect()
+}
+
+#[cfg(all(test, any(feature = "x11", feature = "evdev")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn variants_are_matched_to_layouts_by_position() {
+        assert_eq!(xkb_layout_names("us,ru", ""), vec!["us", "ru"]);
+        assert_eq!(xkb_layout_names("us,ru", ",phonetic"), vec!["us", "ru(phonetic)"]);
+        assert_eq!(xkb_layout_names("de", "nodeadkeys,extra"), vec!["de(nodeadkeys)"]);
+        assert!(xkb_layout_names("", "").is_empty());
+    }
+}
@@ -1,34 +1,659 @@
+use std::{
+    fs::File,
+    os::{fd::AsFd, unix::fs::FileExt},
+    sync::{mpsc, Arc, Mutex, Once},
+    thread,
+    time::Instant,
+};
+
+use anyhow::Context;
 use smart_switcher_shared_types::config::ForbiddenContextsConfig;
+use smart_switcher_shared_types::{AppEvent, Key, KeyOrigin, KeyboardEvent, LayoutId, Modifiers};
+use wayland_client::{
+    globals::{registry_queue_init, GlobalListContents},
+    protocol::{wl_registry, wl_seat},
+    Connection, Dispatch, QueueHandle,
+};
+use wayland_protocols_misc::{
+    zwp_input_method_v2::client::{
+        zwp_input_method_keyboard_grab_v2::{self, ZwpInputMethodKeyboardGrabV2},
+        zwp_input_method_manager_v2::{self, ZwpInputMethodManagerV2},
+        zwp_input_method_v2::{self, ZwpInputMethodV2},
+    },
+    zwp_virtual_keyboard_v1::client::{
+        zwp_virtual_keyboard_manager_v1::{self, ZwpVirtualKeyboardManagerV1},
+        zwp_virtual_keyboard_v1::{self, ZwpVirtualKeyboardV1},
+    },
+};
+
+use crate::backend::PlatformBackend;
+use crate::clipboard::{ClipboardShortcut, ClipboardState, Selection};
+use crate::context::ActiveWindowInfo;
+pub use crate::hook::{KeyboardHook, KeyboardHookController};
+
+const KEY_BACKSPACE: u32 = 14;
+const KEY_STATE_RELEASED: u32 = 0;
+const KEY_STATE_PRESSED: u32 = 1;
+
+// zwp_text_input_v3.content_hint
+const CONTENT_HINT_HIDDEN_TEXT: u32 = 0x40;
+const CONTENT_HINT_SENSITIVE_DATA: u32 = 0x80;
+
+// Символьные файлы XKB, которые подключаются к keymap, но раскладками не являются.
+const XKB_NON_LAYOUT_SYMBOLS: &[&str] = &[
+    "pc", "inet", "group", "level3", "level5", "compose", "ctrl", "altwin", "capslock",
+    "terminate", "keypad", "kpdl", "nbsp", "shift", "eurosign", "srvr_ctrl",
+];
+
+// Состояние текстового поля по zwp_input_method_v2. Все поля double-buffered:
+// применяются только по событию done.
+#[derive(Debug, Default, Clone)]
+struct TextInputState {
+    active: bool,
+    // Текст вокруг курсора и позиция курсора в байтах.
+    surrounding: Option<(String, u32)>,
+    content_hint: u32,
+    content_purpose: u32,
+}
+
+#[derive(Default)]
+struct ImState {
+    current: TextInputState,
+    pending: TextInputState,
+    // Количество полученных done — его требует commit(serial).
+    serial: u32,
+    unavailable: bool,
+
+    grab: Option<ZwpInputMethodKeyboardGrabV2>,
+    events_tx: Option<mpsc::Sender<AppEvent>>,
+    modifiers: Modifiers,
+
+    // Раскладки и активная группа из keymap/modifiers keyboard grab'а.
+    layouts: Vec<LayoutId>,
+    group: u32,
+    // Виртуальной клавиатуре нельзя слать клавиши, пока ей не передан keymap.
+    has_keymap: bool,
+}
+
+struct WaylandState {
+    conn: Connection,
+    qh: QueueHandle<Handler>,
+    input_method: ZwpInputMethodV2,
+    virtual_keyboard: ZwpVirtualKeyboardV1,
+    started: Instant,
+    im: Arc<Mutex<ImState>>,
+}
+
+impl WaylandState {
+    fn lock_im(&self) -> anyhow::Result<std::sync::MutexGuard<'_, ImState>> {
+        self.im
+            .lock()
+            .map_err(|_| anyhow::anyhow!("wayland input method lock poisoned"))
+    }
+
+    fn timestamp(&self) -> u32 {
+        self.started.elapsed().as_millis() as u32
+    }
+}
+
+static STATE: Mutex<Option<Arc<WaylandState>>> = Mutex::new(None);
+
+// Обработчик событий, живёт в потоке диспетчеризации.
+struct Handler {
+    im: Arc<Mutex<ImState>>,
+    virtual_keyboard: ZwpVirtualKeyboardV1,
+}
+
+fn state() -> anyhow::Result<Arc<WaylandState>> {
+    let mut guard = STATE
+        .lock()
+        .map_err(|_| anyhow::anyhow!("wayland state lock poisoned"))?;
+    if let Some(state) = guard.as_ref() {
+        return Ok(state.clone());
+    }
+
+    let conn = Connection::connect_to_env().context("connect to Wayland compositor")?;
+    let (globals, mut queue) =
+        registry_queue_init::<Handler>(&conn).context("Wayland registry init failed")?;
+    let qh = queue.handle();
+
+    let seat: wl_seat::WlSeat = globals.bind(&qh, 1..=1, ()).context("wl_seat is not available")?;
+    let im_manager: ZwpInputMethodManagerV2 = globals
+        .bind(&qh, 1..=1, ())
+        .context("zwp_input_method_manager_v2 is not supported by the compositor")?;
+    let vk_manager: ZwpVirtualKeyboardManagerV1 = globals
+        .bind(&qh, 1..=1, ())
+        .context("zwp_virtual_keyboard_manager_v1 is not supported by the compositor")?;
+
+    let input_method = im_manager.get_input_method(&seat, &qh, ());
+    let virtual_keyboard = vk_manager.create_virtual_keyboard(&seat, &qh, ());
+
+    let im = Arc::new(Mutex::new(ImState::default()));
+    let mut handler = Handler {
+        im: im.clone(),
+        virtual_keyboard: virtual_keyboard.clone(),
+    };
+
+    queue
+        .roundtrip(&mut handler)
+        .context("Wayland roundtrip failed")?;
+    if im.lock().is_ok_and(|im| im.unavailable) {
+        return Err(anyhow::anyhow!(
+            "another input method is already running on this seat"
+        ));
+    }
+
+    thread::spawn(move || {
+        while queue.blocking_dispatch(&mut handler).is_ok() {}
+    });
+
+    let state = Arc::new(WaylandState {
+        conn,
+        qh,
+        input_method,
+        virtual_keyboard,
+        started: Instant::now(),
+        im,
+    });
+    *guard = Some(state.clone());
+    Ok(state)
+}
+
+pub fn start_keyboard_hook() -> anyhow::Result<KeyboardHook> {
+    let state = state()?;
+    // Кнопки мыши и смену окна композитор клиентам не показывает — в hook только клавиши.
+    let (events_tx, events_rx) = mpsc::channel::<AppEvent>();
+
+    {
+        let mut im = state.lock_im()?;
+        if im.grab.is_some() {
+            return Err(anyhow::anyhow!("keyboard hook is already running"));
+        }
+        // Пока grab активен, клавиши идут только нам: пересылаем их через виртуальную клавиатуру.
+        im.grab = Some(state.input_method.grab_keyboard(&state.qh, ()));
+        im.events_tx = Some(events_tx);
+    }
+    state.conn.flush().context("Wayland flush failed")?;
+
+    let controller = KeyboardHookController::new(move || {
+        if let Ok(mut im) = state.im.lock() {
+            if let Some(grab) = im.grab.take() {
+                grab.release();
+            }
+            im.events_tx = None;
+        }
+        let _ = state.conn.flush();
+    });
+
+    Ok(KeyboardHook::new(controller, events_rx))
+}
+
+fn input_type_name(purpose: u32) -> &'static str {
+    match purpose {
+        1 => "alpha",
+        2 => "digits",
+        3 => "number",
+        4 => "phone",
+        5 => "url",
+        6 => "email",
+        7 => "name",
+        8 => "password",
+        9 => "pin",
+        10 => "date",
+        11 => "time",
+        12 => "datetime",
+        13 => "terminal",
+        _ => "normal",
+    }
+}
+
+fn is_blocked_input(input: &TextInputState, blocked_input_types: &[String]) -> bool {
+    let hidden =
+        input.content_hint & (CONTENT_HINT_HIDDEN_TEXT | CONTENT_HINT_SENSITIVE_DATA) != 0;
+    let name = input_type_name(input.content_purpose);
+
+    blocked_input_types.iter().any(|blocked| {
+        blocked.eq_ignore_ascii_case(name) || (hidden && blocked.eq_ignore_ascii_case("password"))
+    })
+}
+
+// Wayland не сообщает клиентам активное окно. Fail-closed: если заданы запреты
+// по окнам/процессам, проверить их нельзя — значит контекст запрещён.
+// Без активного текстового поля (input method не активирован) тоже ничего не делаем.
+fn is_forbidden_im(im: &ImState, forbidden: &ForbiddenContextsConfig) -> bool {
+    if !forbidden.blocked_processes.is_empty() || !forbidden.blocked_windows.is_empty() {
+        // Иначе молчание выглядит как поломка: хук работает, а исправлений нет.
+        static POLICY_WARNING: Once = Once::new();
+        POLICY_WARNING.call_once(|| {
+            tracing::warn!(
+                "wayland backend cannot see the active window: corrections are disabled while \
+                 blocked_processes or blocked_windows is not empty"
+            );
+        });
+        return true;
+    }
+
+    !im.current.active || is_blocked_input(&im.current, &forbidden.blocked_input_types)
+}
 
+pub fn is_forbidden_context(forbidden: &ForbiddenContextsConfig) -> anyhow::Result<bool> {
+    let state = state()?;
+    let im = state.lock_im()?;
+    Ok(is_forbidden_im(&im, forbidden))
+}
+
+// Переключение раскладки клиентом в протоколах wlroots не предусмотрено:
+// группа виртуальной клавиатуры не влияет на физическую.
 pub fn switch_to_next_layout(_forbidden: &ForbiddenContextsConfig) -> anyhow::Result<bool> {
     Ok(false)
 }
 
-pub fn get_active_lang_id() -> anyhow::Result<u16> {
-    Ok(0)
+pub fn list_layouts() -> anyhow::Result<Vec<LayoutId>> {
+    let state = state()?;
+    Ok(state.lock_im()?.layouts.clone())
 }
 
-pub fn set_layout_by_lang_id(
-    _forbidden: &ForbiddenContextsConfig,
-    _lang_id: u16,
-) -> anyhow::Result<bool> {
-    Ok(false)
+pub fn active_layout() -> anyhow::Result<LayoutId> {
+    let state = state()?;
+    let im = state.lock_im()?;
+
+    if im.layouts.is_empty() {
+        return Err(anyhow::anyhow!(
+            "keyboard layout is unknown until the keyboard grab receives a keymap"
+        ));
+    }
+
+    let group = im.group as usize;
+    im.layouts
+        .get(group)
+        .cloned()
+        .ok_or_else(|| anyhow::anyhow!("active XKB group {group} is out of range"))
 }
 
-pub fn send_backspaces(
-    _forbidden: &ForbiddenContextsConfig,
-    _count: usize,
-) -> anyhow::Result<bool> {
+pub fn set_layout(_forbidden: &ForbiddenContextsConfig, _layout: &LayoutId) -> anyhow::Result<bool> {
     Ok(false)
 }
 
+// Сколько байт занимают последние `count` символов перед курсором.
+fn bytes_before_cursor(text: &str, cursor: u32, count: usize) -> Option<u32> {
+    let before = text.get(..cursor as usize)?;
+    let mut chars = before.char_indices().rev().take(count);
+    let start = chars.by_ref().last().map(|(i, _)| i)?;
+    if before[start..].chars().count() < count {
+        return None;
+    }
+    Some((before.len() - start) as u32)
+}
+
+pub fn send_backspaces(forbidden: &ForbiddenContextsConfig, count: usize) -> anyhow::Result<bool> {
+    let state = state()?;
+    let im = state.lock_im()?;
+    if is_forbidden_im(&im, forbidden) {
+        return Ok(false);
+    }
+
+    if count == 0 {
+        return Ok(true);
+    }
+
+    let surrounding = im
+        .current
+        .surrounding
+        .as_ref()
+        .and_then(|(text, cursor)| bytes_before_cursor(text, *cursor, count));
+
+    if let Some(bytes) = surrounding {
+        state.input_method.delete_surrounding_text(bytes, 0);
+        state.input_method.commit(im.serial);
+    } else if im.has_keymap {
+        // Приложение не сообщает surrounding text — стираем обычным Backspace.
+        for _ in 0..count {
+            let time = state.timestamp();
+            state.virtual_keyboard.key(time, KEY_BACKSPACE, KEY_STATE_PRESSED);
+            state.virtual_keyboard.key(time, KEY_BACKSPACE, KEY_STATE_RELEASED);
+        }
+    } else {
+        return Ok(false);
+    }
+
+    state.conn.flush().context("Wayland flush failed")?;
+    Ok(true)
+}
+
 pub fn send_unicode_text(
-    _forbidden: &ForbiddenContextsConfig,
-    _text: &str,
+    forbidden: &ForbiddenContextsConfig,
+    text: &str,
 ) -> anyhow::Result<bool> {
-    Ok(false)
+    let state = state()?;
+    let im = state.lock_im()?;
+    if is_forbidden_im(&im, forbidden) {
+        return Ok(false);
+    }
+
+    if text.is_empty() {
+        return Ok(true);
+    }
+
+    // commit_string вставляет текст как есть, независимо от раскладки.
+    state.input_method.commit_string(text.to_string());
+    state.input_method.commit(im.serial);
+    state.conn.flush().context("Wayland flush failed")?;
+
+    Ok(true)
 }
 
-pub fn is_forbidden_context(_forbidden: &ForbiddenContextsConfig) -> anyhow::Result<bool> {
-    Ok(false)
+fn read_keymap(file: &File, size: u32) -> Option<String> {
+    let mut buf = vec![0u8; size as usize];
+    // read_at не сдвигает общий с композитором offset.
+    file.read_at(&mut buf, 0).ok()?;
+    let end = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+    String::from_utf8(buf[..end].to_vec()).ok()
+}
+
+// Раскладки по группам из строки xkb_symbols, например "pc+us+ru(phonetic):2+inet(evdev)".
+// Вариант сохраняется: "ru(phonetic)".
+fn parse_keymap_layouts(keymap: &str) -> Vec<String> {
+    let Some(symbols) = keymap
+        .split_once("xkb_symbols")
+        .and_then(|(_, rest)| rest.split('"').nth(1))
+    else {
+        return Vec::new();
+    };
+
+    let mut layouts: Vec<String> = Vec::new();
+    for part in symbols.split('+') {
+        let (name, index) = match part.split_once(':') {
+            Some((name, index)) => (name, index.parse::<usize>().ok()),
+            None => (part, None),
+        };
+        let name = name.trim();
+        let base = name.split('(').next().unwrap_or_default();
+        if base.is_empty() || XKB_NON_LAYOUT_SYMBOLS.contains(&base) {
+            continue;
+        }
+
+        let group = index.unwrap_or(1).max(1) - 1;
+        if layouts.len() <= group {
+            layouts.resize(group + 1, String::new());
+        }
+        if layouts[group].is_empty() {
+            layouts[group] = name.to_string();
+        }
+    }
+
+    layouts
+}
+
+impl Dispatch<wl_registry::WlRegistry, GlobalListContents> for Handler {
+    fn event(
+        _: &mut Self,
+        _: &wl_registry::WlRegistry,
+        _: wl_registry::Event,
+        _: &GlobalListContents,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+impl Dispatch<wl_seat::WlSeat, ()> for Handler {
+    fn event(
+        _: &mut Self,
+        _: &wl_seat::WlSeat,
+        _: wl_seat::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+impl Dispatch<ZwpInputMethodManagerV2, ()> for Handler {
+    fn event(
+        _: &mut Self,
+        _: &ZwpInputMethodManagerV2,
+        _: zwp_input_method_manager_v2::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+impl Dispatch<ZwpVirtualKeyboardManagerV1, ()> for Handler {
+    fn event(
+        _: &mut Self,
+        _: &ZwpVirtualKeyboardManagerV1,
+        _: zwp_virtual_keyboard_manager_v1::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+impl Dispatch<ZwpVirtualKeyboardV1, ()> for Handler {
+    fn event(
+        _: &mut Self,
+        _: &ZwpVirtualKeyboardV1,
+        _: zwp_virtual_keyboard_v1::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+impl Dispatch<ZwpInputMethodV2, ()> for Handler {
+    fn event(
+        handler: &mut Self,
+        _: &ZwpInputMethodV2,
+        event: zwp_input_method_v2::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        let Ok(mut im) = handler.im.lock() else {
+            return;
+        };
+
+        match event {
+            zwp_input_method_v2::Event::Activate => {
+                im.pending = TextInputState {
+                    active: true,
+                    ..TextInputState::default()
+                };
+            }
+            zwp_input_method_v2::Event::Deactivate => im.pending.active = false,
+            zwp_input_method_v2::Event::SurroundingText { text, cursor, .. } => {
+                im.pending.surrounding = Some((text, cursor));
+            }
+            zwp_input_method_v2::Event::ContentType { hint, purpose } => {
+                im.pending.content_hint = hint.into();
+                im.pending.content_purpose = purpose.into();
+            }
+            zwp_input_method_v2::Event::Done => {
+                im.current = im.pending.clone();
+                im.serial = im.serial.wrapping_add(1);
+            }
+            zwp_input_method_v2::Event::Unavailable => {
+                im.unavailable = true;
+                im.current = TextInputState::default();
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<ZwpInputMethodKeyboardGrabV2, ()> for Handler {
+    fn event(
+        handler: &mut Self,
+        _: &ZwpInputMethodKeyboardGrabV2,
+        event: zwp_input_method_keyboard_grab_v2::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        let Ok(mut im) = handler.im.lock() else {
+            return;
+        };
+        let virtual_keyboard = &handler.virtual_keyboard;
+
+        match event {
+            zwp_input_method_keyboard_grab_v2::Event::Keymap { format, fd, size } => {
+                virtual_keyboard.keymap(format.into(), fd.as_fd(), size);
+                im.has_keymap = true;
+                im.layouts = read_keymap(&File::from(fd), size)
+                    .map(|keymap| parse_keymap_layouts(&keymap))
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|name| LayoutId::from_xkb(name))
+                    .collect();
+            }
+            zwp_input_method_keyboard_grab_v2::Event::Key {
+                time, key, state, ..
+            } => {
+                let state: u32 = state.into();
+                virtual_keyboard.key(time, key, state);
+
+                if let Some(key) = Key::from_evdev(key as u16) {
+                    let is_key_down = state == KEY_STATE_PRESSED;
+                    im.modifiers.update(key, is_key_down);
+
+                    if let Some(events_tx) = im.events_tx.as_ref() {
+                        // Свою виртуальную клавиатуру композитор в grab не возвращает,
+                        // а чужие от физической через input method не отличить.
+                        let _ = events_tx.send(AppEvent::Keyboard(KeyboardEvent {
+                            key,
+                            modifiers: im.modifiers,
+                            text: None,
+                            is_key_down,
+                            origin: KeyOrigin::Physical,
+                        }));
+                    }
+                }
+            }
+            zwp_input_method_keyboard_grab_v2::Event::Modifiers {
+                mods_depressed,
+                mods_latched,
+                mods_locked,
+                group,
+                ..
+            } => {
+                virtual_keyboard.modifiers(mods_depressed, mods_latched, mods_locked, group);
+                im.group = group;
+            }
+            _ => {}
+        }
+    }
+}
+
+pub struct WaylandBackend;
+
+impl WaylandBackend {
+    // Регистрируемся как input method сразу: композитор может не поддерживать протоколы.
+    pub fn connect() -> anyhow::Result<Self> {
+        state()?;
+        Ok(Self)
+    }
+}
+
+impl PlatformBackend for WaylandBackend {
+    fn name(&self) -> &'static str {
+        "wayland"
+    }
+
+    fn start_keyboard_hook(&self) -> anyhow::Result<KeyboardHook> {
+        start_keyboard_hook()
+    }
+
+    fn switch_to_next_layout(&self, forbidden: &ForbiddenContextsConfig) -> anyhow::Result<bool> {
+        switch_to_next_layout(forbidden)
+    }
+
+    fn list_layouts(&self) -> anyhow::Result<Vec<LayoutId>> {
+        list_layouts()
+    }
+
+    fn active_layout(&self) -> anyhow::Result<LayoutId> {
+        active_layout()
+    }
+
+    fn set_layout(
+        &self,
+        forbidden: &ForbiddenContextsConfig,
+        layout: &LayoutId,
+    ) -> anyhow::Result<bool> {
+        set_layout(forbidden, layout)
+    }
+
+    fn send_backspaces(
+        &self,
+        forbidden: &ForbiddenContextsConfig,
+        count: usize,
+    ) -> anyhow::Result<bool> {
+        send_backspaces(forbidden, count)
+    }
+
+    fn send_unicode_text(
+        &self,
+        forbidden: &ForbiddenContextsConfig,
+        text: &str,
+    ) -> anyhow::Result<bool> {
+        send_unicode_text(forbidden, text)
+    }
+
+    fn is_forbidden_context(&self, forbidden: &ForbiddenContextsConfig) -> anyhow::Result<bool> {
+        is_forbidden_context(forbidden)
+    }
+
+    fn active_window(&self) -> anyhow::Result<Option<ActiveWindowInfo>> {
+        Ok(None)
+    }
+
+    // Буфер обмена без композитора (или без wlr-data-control) недоступен.
+    fn clipboard_text(&self, _selection: Selection) -> anyhow::Result<Option<String>> {
+        Ok(None)
+    }
+
+    fn clipboard_state(&self, _selection: Selection) -> anyhow::Result<Option<ClipboardState>> {
+        Ok(None)
+    }
+
+    fn set_clipboard_text(&self, _selection: Selection, _text: &str) -> anyhow::Result<bool> {
+        Ok(false)
+    }
+
+    fn send_clipboard_shortcut(
+        &self,
+        _forbidden: &ForbiddenContextsConfig,
+        _shortcut: ClipboardShortcut,
+    ) -> anyhow::Result<bool> {
+        Ok(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_layouts_from_keymap_symbols() {
+        let keymap = r#"xkb_keymap {
+            xkb_keycodes "evdev+aliases(qwerty)" { };
+            xkb_symbols "pc+us+ru:2+inet(evdev)+group(alt_shift_toggle)" { };
+        };"#;
+        assert_eq!(parse_keymap_layouts(keymap), vec!["us", "ru"]);
+
+        let keymap = r#"xkb_symbols "pc+de(nodeadkeys)+us:2+ua:3+inet(evdev)" { };"#;
+        assert_eq!(parse_keymap_layouts(keymap), vec!["de(nodeadkeys)", "us", "ua"]);
+    }
+
+    #[test]
+    fn backspaces_count_utf8_bytes_before_cursor() {
+        assert_eq!(bytes_before_cursor("ghbdtn", 6, 6), Some(6));
+        assert_eq!(bytes_before_cursor("привет мир", 19, 3), Some(6));
+        assert_eq!(bytes_before_cursor("abc", 3, 4), None);
+        assert_eq!(bytes_before_cursor("abc", 10, 1), None);
+    }
 }
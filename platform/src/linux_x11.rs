@@ -1,34 +1,1046 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicU8, Ordering},
+        mpsc, Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use anyhow::Context;
 use smart_switcher_shared_types::config::ForbiddenContextsConfig;
+use smart_switcher_shared_types::{AppEvent, Key, KeyOrigin, KeyboardEvent, LayoutId, Modifiers};
+use x11rb::{
+    connection::Connection,
+    protocol::{
+        xinput::{self, ConnectionExt as _},
+        xkb::{self, ConnectionExt as _},
+        xproto::{
+            self, AtomEnum, ChangeWindowAttributesAux, ConnectionExt as _, EventMask, ModMask,
+            Window,
+        },
+        xtest::ConnectionExt as _,
+        Event,
+    },
+    rust_connection::RustConnection,
+};
+
+use crate::backend::PlatformBackend;
+use crate::clipboard::{ClipboardShortcut, ClipboardState, Selection};
+use crate::linux_keys::xkb_layout_names;
+use crate::linux_x11_selection::{selection_state, selection_text, set_selection_text};
+pub use crate::context::ActiveWindowInfo;
+pub use crate::hook::{KeyboardHook, KeyboardHookController};
+
+// X11 keycode = evdev keycode + 8.
+const X11_KEYCODE_OFFSET: u32 = 8;
+
+const HOOK_POLL_INTERVAL: Duration = Duration::from_millis(5);
+const INJECT_KEY_DELAY: Duration = Duration::from_millis(2);
+
+const XK_BACKSPACE: u32 = 0xFF08;
+const XK_TAB: u32 = 0xFF09;
+const XK_RETURN: u32 = 0xFF0D;
+const XK_CONTROL_L: u32 = 0xFFE3;
+const NO_SYMBOL: u32 = 0;
+
+// Свои нажатия, не вернувшиеся за это время, считаем потерянными.
+const OWN_INJECTION_TIMEOUT: Duration = Duration::from_secs(1);
 
-pub fn switch_to_next_layout(_forbidden: &ForbiddenContextsConfig) -> anyhow::Result<bool> {
-    Ok(false)
+// XTEST-устройство у всех программ одно, поэтому свои нажатия от чужих отличаем счётчиком:
+// сколько мы отправили и ещё не увидели raw-событием.
+struct OwnInjections {
+    pending: usize,
+    sent_at: Option<Instant>,
 }
 
-pub fn get_active_lang_id() -> anyhow::Result<u16> {
-    Ok(0)
+static OWN_INJECTIONS: Mutex<OwnInjections> = Mutex::new(OwnInjections {
+    pending: 0,
+    sent_at: None,
+});
+
+impl OwnInjections {
+    fn sent(&mut self) {
+        self.pending += 1;
+        self.sent_at = Some(Instant::now());
+    }
+
+    // Происхождение нажатия, пришедшего с XTEST-устройства.
+    fn take(&mut self) -> KeyOrigin {
+        let fresh = self
+            .sent_at
+            .is_some_and(|sent_at| sent_at.elapsed() < OWN_INJECTION_TIMEOUT);
+        if !fresh {
+            self.pending = 0;
+        }
+        match self.pending.checked_sub(1) {
+            Some(pending) => {
+                self.pending = pending;
+                KeyOrigin::OwnInjection
+            }
+            None => KeyOrigin::OtherInjection,
+        }
+    }
 }
 
-pub fn set_layout_by_lang_id(
-    _forbidden: &ForbiddenContextsConfig,
-    _lang_id: u16,
-) -> anyhow::Result<bool> {
-    Ok(false)
+// Keycode, который send_unicode_text переназначает под каждый символ; 0 — ещё не было.
+static SCRATCH_KEYCODE: AtomicU8 = AtomicU8::new(0);
+
+fn is_own_remap(first_keycode: u8, count: u8) -> bool {
+    let scratch = SCRATCH_KEYCODE.load(Ordering::Relaxed);
+    scratch != 0 && first_keycode == scratch && count == 1
 }
 
-pub fn send_backspaces(
-    _forbidden: &ForbiddenContextsConfig,
-    _count: usize,
-) -> anyhow::Result<bool> {
-    Ok(false)
+struct X11State {
+    conn: RustConnection,
+    root: Window,
+    // Инъекция текста переназначает scratch keycode, поэтому выполняется строго по одной.
+    inject_lock: Mutex<()>,
+}
+
+static STATE: Mutex<Option<Arc<X11State>>> = Mutex::new(None);
+
+fn connect() -> anyhow::Result<(RustConnection, Window)> {
+    let (conn, screen_num) = x11rb::connect(None).context("connect to X server")?;
+    let root = conn.setup().roots[screen_num].root;
+    Ok((conn, root))
+}
+
+fn state() -> anyhow::Result<Arc<X11State>> {
+    let mut guard = STATE
+        .lock()
+        .map_err(|_| anyhow::anyhow!("x11 state lock poisoned"))?;
+    if let Some(state) = guard.as_ref() {
+        return Ok(state.clone());
+    }
+
+    let (conn, root) = connect()?;
+    let xkb = conn
+        .xkb_use_extension(1, 0)?
+        .reply()
+        .context("XKB extension is not available")?;
+    if !xkb.supported {
+        return Err(anyhow::anyhow!("XKB 1.0 is not supported by the X server"));
+    }
+    conn.xtest_get_version(2, 2)?
+        .reply()
+        .context("XTEST extension is not available")?;
+
+    let state = Arc::new(X11State {
+        conn,
+        root,
+        inject_lock: Mutex::new(()),
+    });
+    *guard = Some(state.clone());
+    Ok(state)
+}
+
+// Соединение хука: raw-нажатия клавиш и кнопок мыши, смена _NET_ACTIVE_WINDOW на root.
+struct HookConnection {
+    conn: RustConnection,
+    // XTEST-клавиатуры: их нажатия вставлены программами.
+    xtest_keyboards: Vec<xinput::DeviceId>,
+    net_active_window: xproto::Atom,
+}
+
+fn open_hook_connection() -> anyhow::Result<HookConnection> {
+    let (conn, root) = connect()?;
+
+    // XKB нужен, чтобы по активной группе определить печатаемый символ.
+    conn.xkb_use_extension(1, 0)?
+        .reply()
+        .context("XKB extension is not available")?;
+    // Смена раскладок (setxkbmap) и новая клавиатура меняют таблицу keysym,
+    // а группу и модификаторы хук узнаёт из StateNotify, не спрашивая на каждое нажатие.
+    let keymap_parts = xkb::MapPart::KEY_TYPES | xkb::MapPart::KEY_SYMS;
+    let state_parts = xkb::StatePart::GROUP_STATE | xkb::StatePart::MODIFIER_STATE;
+    conn.xkb_select_events(
+        xkb::ID::USE_CORE_KBD.into(),
+        xkb::EventType::from(0u16),
+        xkb::EventType::NEW_KEYBOARD_NOTIFY | xkb::EventType::MAP_NOTIFY,
+        keymap_parts,
+        keymap_parts,
+        &xkb::SelectEventsAux::new().state_notify(xkb::SelectEventsAuxStateNotify {
+            affect_state: state_parts,
+            state_details: state_parts,
+        }),
+    )?
+    .check()
+    .context("XkbSelectEvents(NewKeyboardNotify | MapNotify | StateNotify) failed")?;
+
+    let version = conn
+        .xinput_xi_query_version(2, 2)?
+        .reply()
+        .context("XInput2 extension is not available")?;
+    if version.major_version < 2 {
+        return Err(anyhow::anyhow!(
+            "XInput {}.{} is too old, 2.0+ required",
+            version.major_version,
+            version.minor_version
+        ));
+    }
+
+    // Raw-события приходят на root окно независимо от фокуса и grab'ов.
+    conn.xinput_xi_select_events(
+        root,
+        &[xinput::EventMask {
+            deviceid: xinput::Device::ALL_MASTER.into(),
+            mask: vec![
+                xinput::XIEventMask::RAW_KEY_PRESS
+                    | xinput::XIEventMask::RAW_KEY_RELEASE
+                    | xinput::XIEventMask::RAW_BUTTON_PRESS,
+            ],
+        }],
+    )?
+    .check()
+    .context("XISelectEvents(RawKeyPress | RawKeyRelease | RawButtonPress) failed")?;
+
+    let xtest_keyboards = conn
+        .xinput_xi_query_device(xinput::Device::ALL)?
+        .reply()
+        .context("XIQueryDevice failed")?
+        .infos
+        .into_iter()
+        .filter(|info| {
+            info.type_ == xinput::DeviceType::SLAVE_KEYBOARD
+                && info.name.windows(5).any(|part| part == b"XTEST")
+        })
+        .map(|info| info.deviceid)
+        .collect();
+
+    // Активное окно WM объявляет свойством root окна.
+    conn.change_window_attributes(
+        root,
+        &ChangeWindowAttributesAux::new().event_mask(EventMask::PROPERTY_CHANGE),
+    )?
+    .check()
+    .context("select PropertyChange on the root window failed")?;
+    let net_active_window = conn.intern_atom(false, b"_NET_ACTIVE_WINDOW")?.reply()?.atom;
+
+    Ok(HookConnection {
+        conn,
+        xtest_keyboards,
+        net_active_window,
+    })
+}
+
+pub fn start_keyboard_hook() -> anyhow::Result<KeyboardHook> {
+    let (events_tx, events_rx) = mpsc::channel::<AppEvent>();
+    let (ready_tx, ready_rx) = mpsc::channel::<anyhow::Result<()>>();
+    let stop = Arc::new(AtomicBool::new(false));
+
+    let join = thread::spawn({
+        let stop = stop.clone();
+        move || {
+            let HookConnection {
+                conn,
+                xtest_keyboards,
+                net_active_window,
+            } = match open_hook_connection() {
+                Ok(opened) => opened,
+                Err(e) => {
+                    let _ = ready_tx.send(Err(e));
+                    return;
+                }
+            };
+
+            let _ = ready_tx.send(Ok(()));
+
+            // Без таблицы keysym события всё равно идут, просто без `text`.
+            let mut mapping = KeyboardMapping::load_with_groups(&conn).ok();
+            // Raw-события состояния не несут: группу и модификаторы ведём по StateNotify.
+            let (mut group, mut mods) = conn
+                .xkb_get_state(xkb::ID::USE_CORE_KBD.into())
+                .ok()
+                .and_then(|cookie| cookie.reply().ok())
+                .map_or((0, ModMask::from(0u16)), |state| (state.group.into(), state.mods));
+            let mut alt_gr = false;
+            let mut modifiers = Modifiers::default();
+            // WM обновляет свойство и без смены окна — событие только на новое окно.
+            let mut focused: Option<Window> = None;
+
+            while !stop.load(Ordering::Relaxed) {
+                let event = match conn.poll_for_event() {
+                    Ok(Some(event)) => event,
+                    Ok(None) => {
+                        thread::sleep(HOOK_POLL_INTERVAL);
+                        continue;
+                    }
+                    Err(_) => break,
+                };
+
+                let (detail, sourceid, is_key_down) = match event {
+                    Event::XinputRawKeyPress(ev) => (ev.detail, ev.sourceid, true),
+                    Event::XinputRawKeyRelease(ev) => (ev.detail, ev.sourceid, false),
+                    // Кнопки 4–7 — колесо: прокрутка курсор в тексте не двигает.
+                    Event::XinputRawButtonPress(ev) if !(4..=7).contains(&ev.detail) => {
+                        let _ = events_tx.send(AppEvent::PointerActivity);
+                        continue;
+                    }
+                    Event::PropertyNotify(ev) if ev.atom == net_active_window => {
+                        if let Some(event) = focus_changed(&mut focused) {
+                            let _ = events_tx.send(event);
+                        }
+                        continue;
+                    }
+                    Event::XkbStateNotify(ev) => {
+                        group = ev.group.into();
+                        mods = ev.mods;
+                        continue;
+                    }
+                    // Вставка текста переназначает scratch keycode на каждый символ —
+                    // таблицу из-за этого не перечитываем.
+                    Event::MappingNotify(ev) if is_own_remap(ev.first_keycode, ev.count) => {
+                        continue;
+                    }
+                    Event::XkbMapNotify(ev)
+                        if ev.n_types == 0 && is_own_remap(ev.first_key_sym, ev.n_key_syms) =>
+                    {
+                        continue;
+                    }
+                    Event::MappingNotify(_)
+                    | Event::XkbMapNotify(_)
+                    | Event::XkbNewKeyboardNotify(_) => {
+                        mapping = KeyboardMapping::load_with_groups(&conn).ok();
+                        continue;
+                    }
+                    _ => continue,
+                };
+                // До фильтра по клавише: свой scratch keycode тоже надо вычесть из счётчика.
+                let origin = if xtest_keyboards.contains(&sourceid) {
+                    OWN_INJECTIONS
+                        .lock()
+                        .map_or(KeyOrigin::OtherInjection, |mut own| own.take())
+                } else {
+                    KeyOrigin::Physical
+                };
+
+                let Some(code) = detail.checked_sub(X11_KEYCODE_OFFSET) else {
+                    continue;
+                };
+                let Some(key) = Key::from_evdev(code as u16) else {
+                    continue;
+                };
+                modifiers.update(key, is_key_down);
+                if key == Key::AltRight {
+                    alt_gr = is_key_down;
+                }
+
+                // AltGr (ISO_Level3_Shift) не ставит Mod1: с ним нажатие — текст третьего
+                // уровня, а правый Alt, который остался просто Alt, — сочетание.
+                let level3 = alt_gr && !mods.contains(ModMask::M1);
+                let produces_text = is_key_down
+                    && !(modifiers.ctrl || modifiers.meta)
+                    && (!modifiers.alt || level3);
+                let text = mapping
+                    .as_ref()
+                    .filter(|_| produces_text)
+                    .and_then(|mapping| mapping.keysym(detail as u8, group, mods))
+                    .and_then(keysym_to_char);
+
+                let _ = events_tx.send(AppEvent::Keyboard(KeyboardEvent {
+                    key,
+                    modifiers,
+                    text,
+                    is_key_down,
+                    origin,
+                }));
+            }
+        }
+    });
+
+    ready_rx
+        .recv()
+        .context("keyboard hook thread did not report status")??;
+
+    let controller = KeyboardHookController::new(move || {
+        stop.store(true, Ordering::Relaxed);
+        let _ = join.join();
+    });
+
+    Ok(KeyboardHook::new(controller, events_rx))
+}
+
+// Окно из _NET_ACTIVE_WINDOW, если оно сменилось; None на месте окна — фокус ни у кого.
+fn focus_changed(focused: &mut Option<Window>) -> Option<AppEvent> {
+    let state = state().ok()?;
+    let window = get_active_window(&state).ok()?;
+    if *focused == window {
+        return None;
+    }
+    *focused = window;
+
+    let info = window
+        .and_then(|window| window_info(&state, window).ok())
+        .unwrap_or(ActiveWindowInfo {
+            title: String::new(),
+            process_name: None,
+        });
+    Some(AppEvent::FocusChanged {
+        process: info.process_name,
+        title: info.title,
+        window_id: window.map_or(0, u64::from),
+    })
+}
+
+fn intern_atom(state: &X11State, name: &[u8]) -> anyhow::Result<xproto::Atom> {
+    Ok(state.conn.intern_atom(false, name)?.reply()?.atom)
+}
+
+fn get_active_window(state: &X11State) -> anyhow::Result<Option<Window>> {
+    let atom = intern_atom(state, b"_NET_ACTIVE_WINDOW")?;
+    let reply = state
+        .conn
+        .get_property(false, state.root, atom, AtomEnum::WINDOW, 0, 1)?
+        .reply()?;
+
+    Ok(reply
+        .value32()
+        .and_then(|mut values| values.next())
+        .filter(|&window| window != x11rb::NONE))
+}
+
+fn get_window_title(state: &X11State, window: Window) -> anyhow::Result<String> {
+    let net_wm_name = intern_atom(state, b"_NET_WM_NAME")?;
+    let utf8_string = intern_atom(state, b"UTF8_STRING")?;
+    let reply = state
+        .conn
+        .get_property(false, window, net_wm_name, utf8_string, 0, 1024)?
+        .reply()?;
+    if !reply.value.is_empty() {
+        return Ok(String::from_utf8_lossy(&reply.value).into_owned());
+    }
+
+    let reply = state
+        .conn
+        .get_property(false, window, AtomEnum::WM_NAME, AtomEnum::ANY, 0, 1024)?
+        .reply()?;
+    Ok(String::from_utf8_lossy(&reply.value).into_owned())
+}
+
+fn get_process_name(state: &X11State, window: Window) -> Option<String> {
+    let atom = intern_atom(state, b"_NET_WM_PID").ok()?;
+    let reply = state
+        .conn
+        .get_property(false, window, atom, AtomEnum::CARDINAL, 0, 1)
+        .ok()?
+        .reply()
+        .ok()?;
+    let pid = reply.value32()?.next()?;
+
+    std::fs::read_to_string(format!("/proc/{pid}/comm"))
+        .ok()
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+}
+
+fn window_info(state: &X11State, window: Window) -> anyhow::Result<ActiveWindowInfo> {
+    Ok(ActiveWindowInfo {
+        title: get_window_title(state, window)?,
+        process_name: get_process_name(state, window),
+    })
+}
+
+pub fn get_active_window_info() -> anyhow::Result<ActiveWindowInfo> {
+    let state = state()?;
+    let Some(window) = get_active_window(&state)? else {
+        return Err(anyhow::anyhow!("_NET_ACTIVE_WINDOW is not set"));
+    };
+
+    window_info(&state, window)
+}
+
+// Fail-closed: без активного окна считаем контекст запрещённым.
+fn is_forbidden_active(state: &X11State, forbidden: &ForbiddenContextsConfig) -> anyhow::Result<bool> {
+    let Some(window) = get_active_window(state)? else {
+        return Ok(true);
+    };
+
+    let info = window_info(state, window)?;
+    Ok(info.is_forbidden(forbidden))
+}
+
+pub fn is_forbidden_context(forbidden: &ForbiddenContextsConfig) -> anyhow::Result<bool> {
+    let state = state()?;
+    is_forbidden_active(&state, forbidden)
+}
+
+// Раскладки по XKB группам из _XKB_RULES_NAMES: "rules\0model\0us,ru\0,phonetic\0options".
+fn get_group_layouts(state: &X11State) -> anyhow::Result<Vec<LayoutId>> {
+    let atom = intern_atom(state, b"_XKB_RULES_NAMES")?;
+    let reply = state
+        .conn
+        .get_property(false, state.root, atom, AtomEnum::STRING, 0, 1024)?
+        .reply()?;
+
+    let mut fields = reply
+        .value
+        .split(|&b| b == 0)
+        .map(|raw| String::from_utf8_lossy(raw).into_owned());
+    let layouts = fields.nth(2).unwrap_or_default();
+    let variants = fields.next().unwrap_or_default();
+
+    let layouts = xkb_layout_names(&layouts, &variants);
+    if layouts.is_empty() {
+        return Err(anyhow::anyhow!("_XKB_RULES_NAMES does not list any layouts"));
+    }
+
+    Ok(layouts.iter().map(|name| LayoutId::from_xkb(name)).collect())
+}
+
+fn active_group(conn: &RustConnection) -> anyhow::Result<u8> {
+    let reply = conn.xkb_get_state(xkb::ID::USE_CORE_KBD.into())?.reply()?;
+    Ok(reply.group.into())
+}
+
+fn get_active_group(state: &X11State) -> anyhow::Result<u8> {
+    active_group(&state.conn)
+}
+
+fn lock_group(state: &X11State, group: u8) -> anyhow::Result<bool> {
+    let none = ModMask::from(0u16);
+    state
+        .conn
+        .xkb_latch_lock_state(
+            xkb::ID::USE_CORE_KBD.into(),
+            none,
+            none,
+            true,
+            xkb::Group::from(group),
+            none,
+            false,
+            0,
+        )?
+        .check()
+        .context("XkbLatchLockState failed")?;
+    Ok(true)
+}
+
+pub fn switch_to_next_layout(forbidden: &ForbiddenContextsConfig) -> anyhow::Result<bool> {
+    let state = state()?;
+    if is_forbidden_active(&state, forbidden)? {
+        return Ok(false);
+    }
+
+    let count = get_group_layouts(&state)?.len();
+    if count < 2 {
+        return Ok(false);
+    }
+
+    let current = get_active_group(&state)? as usize;
+    lock_group(&state, ((current + 1) % count) as u8)
+}
+
+pub fn list_layouts() -> anyhow::Result<Vec<LayoutId>> {
+    let state = state()?;
+    get_group_layouts(&state)
+}
+
+pub fn active_layout() -> anyhow::Result<LayoutId> {
+    let state = state()?;
+    let layouts = get_group_layouts(&state)?;
+    let group = get_active_group(&state)? as usize;
+
+    layouts
+        .into_iter()
+        .nth(group)
+        .ok_or_else(|| anyhow::anyhow!("active XKB group {group} is out of range"))
+}
+
+pub fn set_layout(forbidden: &ForbiddenContextsConfig, layout: &LayoutId) -> anyhow::Result<bool> {
+    let state = state()?;
+    if is_forbidden_active(&state, forbidden)? {
+        return Ok(false);
+    }
+
+    let target = get_group_layouts(&state)?.iter().position(|l| l == layout);
+
+    let Some(target) = target else {
+        return Ok(false);
+    };
+
+    lock_group(&state, target as u8)
+}
+
+struct KeyboardMapping {
+    min_keycode: u8,
+    keysyms_per_keycode: u8,
+    keysyms: Vec<u32>,
+    // Группы 3 и 4 есть только в XKB-таблице; она нужна хуку, вставке хватает core.
+    groups: Option<XkbKeyMap>,
+}
+
+impl KeyboardMapping {
+    fn load(conn: &RustConnection) -> anyhow::Result<Self> {
+        let setup = conn.setup();
+        let (min_keycode, max_keycode) = (setup.min_keycode, setup.max_keycode);
+        let reply = conn
+            .get_keyboard_mapping(min_keycode, max_keycode - min_keycode + 1)?
+            .reply()?;
+
+        Ok(Self {
+            min_keycode,
+            keysyms_per_keycode: reply.keysyms_per_keycode,
+            keysyms: reply.keysyms,
+            groups: None,
+        })
+    }
+
+    fn load_with_groups(conn: &RustConnection) -> anyhow::Result<Self> {
+        let groups = XkbKeyMap::load(conn)?;
+        Ok(Self {
+            groups: Some(groups),
+            ..Self::load(conn)?
+        })
+    }
+
+    fn keycodes(&self) -> impl Iterator<Item = (u8, &[u32])> {
+        self.keysyms
+            .chunks(self.keysyms_per_keycode.max(1) as usize)
+            .enumerate()
+            .map(|(i, syms)| (self.min_keycode.saturating_add(i as u8), syms))
+    }
+
+    // Символ по XKB-таблице: все группы и уровни, включая третий (AltGr).
+    // Без неё — core-таблица: группы 1 и 2 в колонках 0..4 (base, shift). Нет shift-символа —
+    // как в Xlib, берём base (для букв — в верхнем регистре). CapsLock (Lock) у букв меняет
+    // регистр на противоположный Shift.
+    fn keysym(&self, keycode: u8, group: u8, mods: ModMask) -> Option<u32> {
+        if let Some(groups) = &self.groups {
+            return groups.keysym(keycode, group, mods);
+        }
+        if group > 1 {
+            return None;
+        }
+        let shift = mods.contains(ModMask::SHIFT);
+        let caps_lock = mods.contains(ModMask::LOCK);
+
+        let syms = self
+            .keycodes()
+            .find(|&(code, _)| code == keycode)
+            .map(|(_, syms)| syms)?;
+        let column = group as usize * 2;
+        let base = syms.get(column).copied().filter(|&sym| sym != NO_SYMBOL)?;
+        let shift = shift ^ (caps_lock && keysym_to_char(base).is_some_and(char::is_lowercase));
+        if !shift {
+            return Some(base);
+        }
+
+        match syms.get(column + 1).copied().filter(|&sym| sym != NO_SYMBOL) {
+            Some(shifted) => Some(shifted),
+            None => Some(
+                keysym_to_char(base)
+                    .and_then(|ch| ch.to_uppercase().next())
+                    .map(char_to_keysym)
+                    .unwrap_or(base),
+            ),
+        }
+    }
+
+    fn find_keycode(&self, keysym: u32) -> Option<u8> {
+        self.keycodes()
+            .find(|(_, syms)| syms.first() == Some(&keysym))
+            .map(|(keycode, _)| keycode)
+    }
+
+    // Свободный keycode без символов — на него временно назначаем нужный keysym.
+    fn find_scratch_keycode(&self) -> Option<u8> {
+        self.keycodes()
+            .filter(|(_, syms)| syms.iter().all(|&sym| sym == NO_SYMBOL))
+            .map(|(keycode, _)| keycode)
+            .last()
+    }
+}
+
+// XKB-таблица клавиш: у каждой клавиши свои группы, а уровень в группе выбирает тип клавиши
+// по модификаторам (ALPHABETIC: Shift или Lock — второй уровень; FOUR_LEVEL: AltGr — третий).
+struct XkbKeyMap {
+    first_keycode: u8,
+    keys: Vec<xkb::KeySymMap>,
+    types: Vec<xkb::KeyType>,
+}
+
+impl XkbKeyMap {
+    fn load(conn: &RustConnection) -> anyhow::Result<Self> {
+        let reply = conn
+            .xkb_get_map(
+                xkb::ID::USE_CORE_KBD.into(),
+                xkb::MapPart::KEY_TYPES | xkb::MapPart::KEY_SYMS,
+                xkb::MapPart::from(0u16),
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                xkb::VMod::from(0u16),
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+            )?
+            .reply()
+            .context("XkbGetMap(KeyTypes | KeySyms) failed")?;
+
+        Ok(Self {
+            first_keycode: reply.first_key_sym,
+            keys: reply.map.syms_rtrn.unwrap_or_default(),
+            types: reply.map.types_rtrn.unwrap_or_default(),
+        })
+    }
+
+    fn keysym(&self, keycode: u8, group: u8, mods: ModMask) -> Option<u32> {
+        let key = self.keys.get(keycode.checked_sub(self.first_keycode)? as usize)?;
+        let groups = key.group_info & 0x0F;
+        if groups == 0 {
+            return None;
+        }
+        // Группы больше, чем у клавиши: как в XKB, по её правилу (по умолчанию — по кругу).
+        let group = if group < groups {
+            group
+        } else {
+            match key.group_info & 0xC0 {
+                0x40 => groups - 1,
+                0x80 => Some((key.group_info >> 4) & 0x03).filter(|&g| g < groups).unwrap_or(0),
+                _ => group % groups,
+            }
+        };
+
+        let key_type = self.types.get(*key.kt_index.get(group as usize)? as usize)?;
+        let active = mods & key_type.mods_mask;
+        let level = key_type
+            .map
+            .iter()
+            .find(|entry| entry.active && entry.mods_mask == active)
+            .map_or(0, |entry| entry.level);
+
+        let index = group as usize * key.width as usize + level as usize;
+        let sym = key.syms.get(index).copied().filter(|&sym| sym != NO_SYMBOL)?;
+        // Lock, который тип клавиши не учитывает, как в Xlib, поднимает регистр буквы.
+        if mods.contains(ModMask::LOCK) && !key_type.mods_mask.contains(ModMask::LOCK) {
+            return Some(
+                keysym_to_char(sym)
+                    .and_then(|ch| ch.to_uppercase().next())
+                    .map(char_to_keysym)
+                    .unwrap_or(sym),
+            );
+        }
+        Some(sym)
+    }
+}
+
+fn char_to_keysym(ch: char) -> u32 {
+    match ch {
+        '\n' | '\r' => XK_RETURN,
+        '\t' => XK_TAB,
+        '\u{20}'..='\u{7E}' | '\u{A0}'..='\u{FF}' => ch as u32,
+        _ => 0x0100_0000 | ch as u32,
+    }
+}
+
+// Кириллица в XKB-раскладках задана legacy keysym 0x06xx (порядок KOI8).
+const CYRILLIC_KEYSYMS: &str = "юабцдефгхийклмнопярстужвьызшэщчъЮАБЦДЕФГХИЙКЛМНОПЯРСТУЖВЬЫЗШЭЩЧЪ";
+
+fn keysym_to_char(keysym: u32) -> Option<char> {
+    match keysym {
+        0x20..=0x7E | 0xA0..=0xFF => char::from_u32(keysym),
+        0x06C0..=0x06FF => CYRILLIC_KEYSYMS.chars().nth((keysym - 0x06C0) as usize),
+        0x06A3 => Some('ё'),
+        0x06A4 => Some('є'),
+        0x06A6 => Some('і'),
+        0x06A7 => Some('ї'),
+        0x06AD => Some('ґ'),
+        0x06AE => Some('ў'),
+        0x06B0 => Some('№'),
+        0x06B3 => Some('Ё'),
+        0x06B4 => Some('Є'),
+        0x06B6 => Some('І'),
+        0x06B7 => Some('Ї'),
+        0x06BD => Some('Ґ'),
+        0x06BE => Some('Ў'),
+        0x0100_0100..=0x0110_FFFF => char::from_u32(keysym - 0x0100_0000),
+        _ => None,
+    }
+}
+
+fn fake_key(state: &X11State, event: u8, keycode: u8) -> anyhow::Result<()> {
+    if let Ok(mut own) = OWN_INJECTIONS.lock() {
+        own.sent();
+    }
+    state
+        .conn
+        .xtest_fake_input(event, keycode, x11rb::CURRENT_TIME, state.root, 0, 0, 0)?;
+    Ok(())
+}
+
+fn fake_key_tap(state: &X11State, keycode: u8) -> anyhow::Result<()> {
+    fake_key(state, xproto::KEY_PRESS_EVENT, keycode)?;
+    fake_key(state, xproto::KEY_RELEASE_EVENT, keycode)
+}
+
+fn sync(state: &X11State) -> anyhow::Result<()> {
+    state.conn.get_input_focus()?.reply()?;
+    Ok(())
+}
+
+pub fn send_backspaces(forbidden: &ForbiddenContextsConfig, count: usize) -> anyhow::Result<bool> {
+    let state = state()?;
+    if is_forbidden_active(&state, forbidden)? {
+        return Ok(false);
+    }
+
+    if count == 0 {
+        return Ok(true);
+    }
+
+    let _guard = state
+        .inject_lock
+        .lock()
+        .map_err(|_| anyhow::anyhow!("x11 inject lock poisoned"))?;
+
+    let mapping = KeyboardMapping::load(&state.conn)?;
+    let Some(keycode) = mapping.find_keycode(XK_BACKSPACE) else {
+        return Err(anyhow::anyhow!("no keycode is mapped to BackSpace"));
+    };
+
+    for _ in 0..count {
+        fake_key_tap(&state, keycode)?;
+    }
+    sync(&state)?;
+
+    Ok(true)
 }
 
 pub fn send_unicode_text(
-    _forbidden: &ForbiddenContextsConfig,
-    _text: &str,
+    forbidden: &ForbiddenContextsConfig,
+    text: &str,
 ) -> anyhow::Result<bool> {
-    Ok(false)
+    let state = state()?;
+    if is_forbidden_active(&state, forbidden)? {
+        return Ok(false);
+    }
+
+    if text.is_empty() {
+        return Ok(true);
+    }
+
+    let _guard = state
+        .inject_lock
+        .lock()
+        .map_err(|_| anyhow::anyhow!("x11 inject lock poisoned"))?;
+
+    let mapping = KeyboardMapping::load(&state.conn)?;
+    let Some(scratch) = mapping.find_scratch_keycode() else {
+        return Err(anyhow::anyhow!("no free keycode available for text injection"));
+    };
+    let per_keycode = mapping.keysyms_per_keycode.max(1);
+    SCRATCH_KEYCODE.store(scratch, Ordering::Relaxed);
+
+    let result = (|| -> anyhow::Result<()> {
+        for ch in text.chars() {
+            // Один и тот же keysym на всех уровнях: результат не зависит от Shift/группы.
+            let keysyms = vec![char_to_keysym(ch); per_keycode as usize];
+            state
+                .conn
+                .change_keyboard_mapping(1, scratch, per_keycode, &keysyms)?;
+            sync(&state)?;
+
+            fake_key_tap(&state, scratch)?;
+            sync(&state)?;
+            thread::sleep(INJECT_KEY_DELAY);
+        }
+        Ok(())
+    })();
+
+    let restore = vec![NO_SYMBOL; per_keycode as usize];
+    state
+        .conn
+        .change_keyboard_mapping(1, scratch, per_keycode, &restore)?;
+    state.conn.flush()?;
+
+    result.map(|_| true)
+}
+
+pub fn send_clipboard_shortcut(
+    forbidden: &ForbiddenContextsConfig,
+    shortcut: ClipboardShortcut,
+) -> anyhow::Result<bool> {
+    let state = state()?;
+    if is_forbidden_active(&state, forbidden)? {
+        return Ok(false);
+    }
+
+    let _guard = state
+        .inject_lock
+        .lock()
+        .map_err(|_| anyhow::anyhow!("x11 inject lock poisoned"))?;
+
+    let mapping = KeyboardMapping::load(&state.conn)?;
+    let letter = match shortcut {
+        ClipboardShortcut::Copy => 'c',
+        ClipboardShortcut::Paste => 'v',
+    };
+    let Some(control) = mapping.find_keycode(XK_CONTROL_L) else {
+        return Err(anyhow::anyhow!("no keycode is mapped to Control_L"));
+    };
+    let Some(keycode) = mapping.find_keycode(char_to_keysym(letter)) else {
+        return Err(anyhow::anyhow!("no keycode is mapped to '{letter}'"));
+    };
+
+    fake_key(&state, xproto::KEY_PRESS_EVENT, control)?;
+    fake_key_tap(&state, keycode)?;
+    fake_key(&state, xproto::KEY_RELEASE_EVENT, control)?;
+    sync(&state)?;
+
+    Ok(true)
 }
 
-pub fn is_forbidden_context(_forbidden: &ForbiddenContextsConfig) -> anyhow::Result<bool> {
-    Ok(false)
+pub struct X11Backend;
+
+impl X11Backend {
+    // Проверяем доступность X-сервера и расширений заранее, чтобы можно было выбрать другой бэкенд.
+    pub fn connect() -> anyhow::Result<Self> {
+        state()?;
+        Ok(Self)
+    }
+}
+
+impl PlatformBackend for X11Backend {
+    fn name(&self) -> &'static str {
+        "x11"
+    }
+
+    fn start_keyboard_hook(&self) -> anyhow::Result<KeyboardHook> {
+        start_keyboard_hook()
+    }
+
+    fn switch_to_next_layout(&self, forbidden: &ForbiddenContextsConfig) -> anyhow::Result<bool> {
+        switch_to_next_layout(forbidden)
+    }
+
+    fn list_layouts(&self) -> anyhow::Result<Vec<LayoutId>> {
+        list_layouts()
+    }
+
+    fn active_layout(&self) -> anyhow::Result<LayoutId> {
+        active_layout()
+    }
+
+    fn set_layout(
+        &self,
+        forbidden: &ForbiddenContextsConfig,
+        layout: &LayoutId,
+    ) -> anyhow::Result<bool> {
+        set_layout(forbidden, layout)
+    }
+
+    fn send_backspaces(
+        &self,
+        forbidden: &ForbiddenContextsConfig,
+        count: usize,
+    ) -> anyhow::Result<bool> {
+        send_backspaces(forbidden, count)
+    }
+
+    fn send_unicode_text(
+        &self,
+        forbidden: &ForbiddenContextsConfig,
+        text: &str,
+    ) -> anyhow::Result<bool> {
+        send_unicode_text(forbidden, text)
+    }
+
+    fn is_forbidden_context(&self, forbidden: &ForbiddenContextsConfig) -> anyhow::Result<bool> {
+        is_forbidden_context(forbidden)
+    }
+
+    fn active_window(&self) -> anyhow::Result<Option<ActiveWindowInfo>> {
+        get_active_window_info().map(Some)
+    }
+
+    fn clipboard_text(&self, selection: Selection) -> anyhow::Result<Option<String>> {
+        selection_text(selection)
+    }
+
+    fn clipboard_state(&self, selection: Selection) -> anyhow::Result<Option<ClipboardState>> {
+        selection_state(selection).map(Some)
+    }
+
+    fn set_clipboard_text(&self, selection: Selection, text: &str) -> anyhow::Result<bool> {
+        set_selection_text(selection, text)
+    }
+
+    fn send_clipboard_shortcut(
+        &self,
+        forbidden: &ForbiddenContextsConfig,
+        shortcut: ClipboardShortcut,
+    ) -> anyhow::Result<bool> {
+        send_clipboard_shortcut(forbidden, shortcut)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(mods: ModMask, level: u8) -> xkb::KTMapEntry {
+        xkb::KTMapEntry {
+            active: true,
+            mods_mask: mods,
+            level,
+            mods_mods: mods,
+            mods_vmods: xkb::VMod::from(0u16),
+        }
+    }
+
+    // Клавиша Z в польской раскладке: z Z ż Ż, тип FOUR_LEVEL_ALPHABETIC (LevelThree = Mod5).
+    fn polish_z() -> XkbKeyMap {
+        let (shift, lock, level3) = (ModMask::SHIFT, ModMask::LOCK, ModMask::M5);
+        let four_level = xkb::KeyType {
+            mods_mask: shift | lock | level3,
+            mods_mods: shift | lock | level3,
+            mods_vmods: xkb::VMod::from(0u16),
+            num_levels: 4,
+            has_preserve: false,
+            map: vec![
+                entry(shift, 1),
+                entry(lock, 1),
+                entry(level3, 2),
+                entry(shift | level3, 3),
+                entry(lock | level3, 3),
+            ],
+            preserve: Vec::new(),
+        };
+        let syms = ['z', 'Z', 'ż', 'Ż'].map(char_to_keysym).to_vec();
+        XkbKeyMap {
+            first_keycode: 52,
+            keys: vec![xkb::KeySymMap {
+                kt_index: [0, 0, 0, 0],
+                group_info: 1,
+                width: 4,
+                syms,
+            }],
+            types: vec![four_level],
+        }
+    }
+
+    #[test]
+    fn alt_gr_selects_the_third_level() {
+        let map = polish_z();
+        let char_at = |group, mods| map.keysym(52, group, mods).and_then(keysym_to_char);
+
+        assert_eq!(char_at(0, ModMask::from(0u16)), Some('z'));
+        assert_eq!(char_at(0, ModMask::M5), Some('ż'));
+        assert_eq!(char_at(0, ModMask::M5 | ModMask::SHIFT), Some('Ż'));
+        assert_eq!(char_at(0, ModMask::M5 | ModMask::LOCK), Some('Ż'));
+        // NumLock (Mod2) тип клавиши не учитывает.
+        assert_eq!(char_at(0, ModMask::M5 | ModMask::M2), Some('ż'));
+        // Одна группа у клавиши: третья группа раскладки заворачивается в неё.
+        assert_eq!(char_at(2, ModMask::M5), Some('ż'));
+        assert_eq!(map.keysym(53, 0, ModMask::M5), None);
+    }
 }
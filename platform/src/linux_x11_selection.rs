@@ -0,0 +1,401 @@
+// Буфер обмена X11: CLIPBOARD и PRIMARY — это selections, текст живёт у владельца.
+// Читаем через ConvertSelection на одном соединении со своим окном, а для записи держим
+// своё окно-владелец и отдельный поток, который отвечает на SelectionRequest.
+
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use anyhow::Context;
+use x11rb::{
+    connection::Connection,
+    protocol::{
+        xproto::{
+            self, Atom, AtomEnum, ConnectionExt as _, CreateWindowAux, EventMask,
+            GetPropertyReply, PropMode, SelectionNotifyEvent, SelectionRequestEvent, Window,
+            WindowClass,
+        },
+        Event,
+    },
+    rust_connection::RustConnection,
+    wrapper::ConnectionExt as _,
+};
+
+use crate::clipboard::{ClipboardState, Selection};
+
+// Сколько ждём ответа владельца; INCR (передача частями) не поддерживаем.
+const CONVERT_TIMEOUT: Duration = Duration::from_millis(500);
+const CONVERT_POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+// Цели, в которых владелец отдаёт только простой текст; всё прочее (text/html, image/png,
+// text/uri-list) пропадёт, если записать в буфер свой текст.
+const TEXT_TARGETS: &[&str] = &[
+    "TARGETS",
+    "TIMESTAMP",
+    "MULTIPLE",
+    "SAVE_TARGETS",
+    "UTF8_STRING",
+    "STRING",
+    "TEXT",
+    "COMPOUND_TEXT",
+];
+
+fn is_text_target(name: &str) -> bool {
+    TEXT_TARGETS.contains(&name) || name.to_ascii_lowercase().starts_with("text/plain")
+}
+
+#[derive(Clone, Copy)]
+struct Atoms {
+    clipboard: Atom,
+    targets: Atom,
+    timestamp: Atom,
+    text: Atom,
+    utf8_string: Atom,
+    incr: Atom,
+    property: Atom,
+}
+
+impl Atoms {
+    fn intern(conn: &RustConnection) -> anyhow::Result<Self> {
+        let intern = |name: &[u8]| -> anyhow::Result<Atom> {
+            Ok(conn.intern_atom(false, name)?.reply()?.atom)
+        };
+        Ok(Self {
+            clipboard: intern(b"CLIPBOARD")?,
+            targets: intern(b"TARGETS")?,
+            timestamp: intern(b"TIMESTAMP")?,
+            text: intern(b"TEXT")?,
+            utf8_string: intern(b"UTF8_STRING")?,
+            incr: intern(b"INCR")?,
+            property: intern(b"SMART_SWITCHER_SELECTION")?,
+        })
+    }
+
+    fn selection(&self, selection: Selection) -> Atom {
+        match selection {
+            Selection::Clipboard => self.clipboard,
+            Selection::Primary => AtomEnum::PRIMARY.into(),
+        }
+    }
+}
+
+// Невидимое окно: нужно и чтобы получать текст, и чтобы владеть selection.
+fn create_window(conn: &RustConnection, screen_num: usize) -> anyhow::Result<Window> {
+    let root = conn.setup().roots[screen_num].root;
+    let window = conn.generate_id()?;
+    conn.create_window(
+        x11rb::COPY_DEPTH_FROM_PARENT,
+        window,
+        root,
+        0,
+        0,
+        1,
+        1,
+        0,
+        WindowClass::INPUT_ONLY,
+        x11rb::COPY_FROM_PARENT,
+        &CreateWindowAux::new().event_mask(EventMask::PROPERTY_CHANGE),
+    )?;
+    Ok(window)
+}
+
+// Окно, на которое владельцы присылают содержимое selection. Одно на процесс:
+// ожидание копирования читает буфер много раз подряд.
+struct SelectionReader {
+    conn: RustConnection,
+    window: Window,
+    atoms: Atoms,
+}
+
+static READER: Mutex<Option<SelectionReader>> = Mutex::new(None);
+
+impl SelectionReader {
+    fn connect() -> anyhow::Result<Self> {
+        let (conn, screen_num) = x11rb::connect(None).context("connect to X server")?;
+        let atoms = Atoms::intern(&conn)?;
+        let window = create_window(&conn, screen_num)?;
+        Ok(Self { conn, window, atoms })
+    }
+
+    fn text(&self, selection: Selection) -> anyhow::Result<Option<String>> {
+        let selection = self.atoms.selection(selection);
+        if self.conn.get_selection_owner(selection)?.reply()?.owner == x11rb::NONE {
+            return Ok(None);
+        }
+        let reply = self.convert(selection, self.atoms.utf8_string)?;
+        Ok(reply.map(|reply| String::from_utf8_lossy(&reply.value).into_owned()))
+    }
+
+    fn state(&self, selection: Selection) -> anyhow::Result<ClipboardState> {
+        let selection = self.atoms.selection(selection);
+        let owner = self.conn.get_selection_owner(selection)?.reply()?.owner;
+        if owner == x11rb::NONE {
+            return Ok(ClipboardState {
+                sequence: 0,
+                has_non_text: false,
+            });
+        }
+
+        // Новое содержимое — это новый владелец или новое время захвата (TIMESTAMP).
+        // Наше окно-владелец времени не знает: считаем свои записи.
+        let stamp = if own_window() == Some(owner) {
+            WRITES.load(Ordering::Relaxed)
+        } else {
+            self.convert(selection, self.atoms.timestamp)?
+                .and_then(|reply| reply.value32()?.next())
+                .unwrap_or(0)
+        };
+        let targets: Vec<Atom> = self
+            .convert(selection, self.atoms.targets)?
+            .and_then(|reply| Some(reply.value32()?.collect()))
+            .unwrap_or_default();
+
+        Ok(ClipboardState {
+            sequence: (u64::from(owner) << 32) | u64::from(stamp),
+            has_non_text: self.has_non_text(&targets)?,
+        })
+    }
+
+    fn has_non_text(&self, targets: &[Atom]) -> anyhow::Result<bool> {
+        let cookies = targets
+            .iter()
+            .map(|&atom| self.conn.get_atom_name(atom))
+            .collect::<Result<Vec<_>, _>>()?;
+        for cookie in cookies {
+            let name = cookie.reply()?.name;
+            if !is_text_target(&String::from_utf8_lossy(&name)) {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    // None — владелец не ответил, отказал в этой цели или начал передачу частями.
+    fn convert(&self, selection: Atom, target: Atom) -> anyhow::Result<Option<GetPropertyReply>> {
+        // Ответ на прошлый запрос, пришедший после таймаута, за этот не примем.
+        while self.conn.poll_for_event()?.is_some() {}
+
+        self.conn.convert_selection(
+            self.window,
+            selection,
+            target,
+            self.atoms.property,
+            x11rb::CURRENT_TIME,
+        )?;
+        self.conn.flush()?;
+
+        let deadline = Instant::now() + CONVERT_TIMEOUT;
+        let notify = loop {
+            match self.conn.poll_for_event()? {
+                Some(Event::SelectionNotify(event))
+                    if event.requestor == self.window && event.selection == selection =>
+                {
+                    break event;
+                }
+                Some(_) => {}
+                None if Instant::now() >= deadline => return Ok(None),
+                None => thread::sleep(CONVERT_POLL_INTERVAL),
+            }
+        };
+        if notify.property == x11rb::NONE {
+            return Ok(None);
+        }
+
+        let reply = self
+            .conn
+            .get_property(true, self.window, self.atoms.property, AtomEnum::ANY, 0, u32::MAX)?
+            .reply()?;
+        if reply.type_ == self.atoms.incr {
+            return Ok(None);
+        }
+        Ok(Some(reply))
+    }
+}
+
+fn with_reader<T>(read: impl FnOnce(&SelectionReader) -> anyhow::Result<T>) -> anyhow::Result<T> {
+    let mut guard = READER
+        .lock()
+        .map_err(|_| anyhow::anyhow!("x11 selection reader lock poisoned"))?;
+    let reader = match guard.take() {
+        Some(reader) => reader,
+        None => SelectionReader::connect()?,
+    };
+    // Соединение с ошибкой не храним — следующий вызов подключится заново.
+    let value = read(&reader)?;
+    *guard = Some(reader);
+    Ok(value)
+}
+
+pub fn selection_text(selection: Selection) -> anyhow::Result<Option<String>> {
+    with_reader(|reader| reader.text(selection))
+}
+
+pub fn selection_state(selection: Selection) -> anyhow::Result<ClipboardState> {
+    with_reader(|reader| reader.state(selection))
+}
+
+struct SelectionOwner {
+    conn: RustConnection,
+    window: Window,
+    atoms: Atoms,
+    // Текст, который мы отдаём, по selection; пропадает, когда владение перехватили.
+    texts: Mutex<HashMap<Atom, String>>,
+}
+
+static OWNER: Mutex<Option<Arc<SelectionOwner>>> = Mutex::new(None);
+
+// Сколько раз мы сами записывали selection.
+static WRITES: AtomicU32 = AtomicU32::new(0);
+
+fn own_window() -> Option<Window> {
+    OWNER.lock().ok()?.as_ref().map(|owner| owner.window)
+}
+
+fn owner() -> anyhow::Result<Arc<SelectionOwner>> {
+    let mut guard = OWNER
+        .lock()
+        .map_err(|_| anyhow::anyhow!("x11 selection owner lock poisoned"))?;
+    if let Some(owner) = guard.as_ref() {
+        return Ok(owner.clone());
+    }
+
+    let (conn, screen_num) = x11rb::connect(None).context("connect to X server")?;
+    let atoms = Atoms::intern(&conn)?;
+    let window = create_window(&conn, screen_num)?;
+    conn.flush()?;
+
+    let owner = Arc::new(SelectionOwner {
+        conn,
+        window,
+        atoms,
+        texts: Mutex::new(HashMap::new()),
+    });
+    let serving = owner.clone();
+    thread::Builder::new()
+        .name("x11-selection-owner".to_string())
+        .spawn(move || {
+            if let Err(err) = serve(&serving) {
+                tracing::warn!(error = %err, "x11 selection owner stopped");
+            }
+            if let Ok(mut guard) = OWNER.lock() {
+                *guard = None;
+            }
+        })
+        .context("spawn x11 selection owner thread")?;
+
+    *guard = Some(owner.clone());
+    Ok(owner)
+}
+
+fn serve(owner: &SelectionOwner) -> anyhow::Result<()> {
+    loop {
+        match owner.conn.wait_for_event()? {
+            Event::SelectionRequest(request) => answer(owner, &request)?,
+            Event::SelectionClear(event) => {
+                if let Ok(mut texts) = owner.texts.lock() {
+                    texts.remove(&event.selection);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn answer(owner: &SelectionOwner, request: &SelectionRequestEvent) -> anyhow::Result<()> {
+    let conn = &owner.conn;
+    let atoms = &owner.atoms;
+    // Старые клиенты присылают property = None — тогда кладём в атом цели.
+    let property = if request.property == x11rb::NONE {
+        request.target
+    } else {
+        request.property
+    };
+    let text = owner
+        .texts
+        .lock()
+        .ok()
+        .and_then(|texts| texts.get(&request.selection).cloned());
+
+    let string: Atom = AtomEnum::STRING.into();
+    let stored = match text {
+        Some(_) if request.target == atoms.targets => {
+            // STRING — только Latin-1, кириллицу в нём не передать; его не предлагаем.
+            let targets = [atoms.targets, atoms.utf8_string, atoms.text];
+            conn.change_property32(
+                PropMode::REPLACE,
+                request.requestor,
+                property,
+                AtomEnum::ATOM,
+                &targets,
+            )?;
+            true
+        }
+        // TEXT — «в любой кодировке», отвечаем UTF-8.
+        Some(text) if request.target == atoms.utf8_string || request.target == atoms.text => {
+            conn.change_property8(
+                PropMode::REPLACE,
+                request.requestor,
+                property,
+                atoms.utf8_string,
+                text.as_bytes(),
+            )?;
+            true
+        }
+        // Старые клиенты просят STRING и без TARGETS: отдаём, если текст есть в Latin-1.
+        Some(text) if request.target == string => match latin1(&text) {
+            Some(bytes) => {
+                conn.change_property8(
+                    PropMode::REPLACE,
+                    request.requestor,
+                    property,
+                    string,
+                    &bytes,
+                )?;
+                true
+            }
+            None => false,
+        },
+        _ => false,
+    };
+
+    let notify = SelectionNotifyEvent {
+        response_type: xproto::SELECTION_NOTIFY_EVENT,
+        sequence: 0,
+        time: request.time,
+        requestor: request.requestor,
+        selection: request.selection,
+        target: request.target,
+        property: if stored { property } else { x11rb::NONE },
+    };
+    conn.send_event(false, request.requestor, EventMask::NO_EVENT, notify)?;
+    conn.flush()?;
+    Ok(())
+}
+
+fn latin1(text: &str) -> Option<Vec<u8>> {
+    text.chars().map(|ch| u8::try_from(u32::from(ch)).ok()).collect()
+}
+
+// false — X-сервер не отдал нам владение selection.
+pub fn set_selection_text(selection: Selection, text: &str) -> anyhow::Result<bool> {
+    let owner = owner()?;
+    let selection = owner.atoms.selection(selection);
+    owner
+        .texts
+        .lock()
+        .map_err(|_| anyhow::anyhow!("x11 selection texts lock poisoned"))?
+        .insert(selection, text.to_string());
+    WRITES.fetch_add(1, Ordering::Relaxed);
+
+    owner
+        .conn
+        .set_selection_owner(owner.window, selection, x11rb::CURRENT_TIME)?;
+    let current = owner.conn.get_selection_owner(selection)?.reply()?.owner;
+    Ok(current == owner.window)
+}
@@ -1,16 +1,21 @@
+use smart_switcher_shared_types::LayoutId;
 use smart_switcher_shared_types::config::ForbiddenContextsConfig;
 
 pub fn switch_to_next_layout(_forbidden: &ForbiddenContextsConfig) -> anyhow::Result<bool> {
     Ok(false)
 }
 
-pub fn get_active_lang_id() -> anyhow::Result<u16> {
-    Ok(0)
+pub fn list_layouts() -> anyhow::Result<Vec<LayoutId>> {
+    Ok(Vec::new())
 }
 
-pub fn set_layout_by_lang_id(
+pub fn active_layout() -> anyhow::Result<LayoutId> {
+    Err(anyhow::anyhow!("keyboard layout is unknown: macOS backend is a stub"))
+}
+
+pub fn set_layout(
     _forbidden: &ForbiddenContextsConfig,
-    _lang_id: u16,
+    _layout: &LayoutId,
 ) -> anyhow::Result<bool> {
     Ok(false)
 }
@@ -1,37 +1,81 @@
 use std::{
-    sync::{mpsc, Mutex},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Mutex,
+    },
     thread,
     time::{Duration, Instant},
 };
 
 use anyhow::Context;
 use smart_switcher_shared_types::config::ForbiddenContextsConfig;
-use smart_switcher_shared_types::KeyboardEvent;
+use smart_switcher_shared_types::{AppEvent, Key, KeyOrigin, KeyboardEvent, LayoutId, Modifiers};
 use windows_sys::Win32::{
-    Foundation::{CloseHandle, GetLastError, HINSTANCE, LPARAM, LRESULT, WPARAM},
+    Foundation::{CloseHandle, GetLastError, GlobalFree, HINSTANCE, LPARAM, LRESULT, WPARAM},
     System::{
         Threading::{OpenProcess, QueryFullProcessImageNameW, PROCESS_QUERY_LIMITED_INFORMATION},
     },
+    System::DataExchange::{
+        CloseClipboard, EmptyClipboard, EnumClipboardFormats, GetClipboardData,
+        GetClipboardSequenceNumber, IsClipboardFormatAvailable, OpenClipboard, SetClipboardData,
+    },
     System::LibraryLoader::GetModuleHandleW,
+    System::Memory::{GlobalAlloc, GlobalLock, GlobalUnlock, GMEM_MOVEABLE},
+    UI::Accessibility::{SetWinEventHook, UnhookWinEvent, HWINEVENTHOOK},
     UI::Input::KeyboardAndMouse::{
-        GetKeyboardLayout, GetKeyboardLayoutList, SendInput, INPUT, INPUT_0, INPUT_KEYBOARD,
-        KEYBDINPUT,
-        KEYEVENTF_KEYUP, KEYEVENTF_UNICODE, VK_BACK,
+        GetKeyState, GetKeyboardLayout, GetKeyboardLayoutList, SendInput, ToUnicodeEx, INPUT,
+        INPUT_0, INPUT_KEYBOARD, KEYBDINPUT,
+        KEYEVENTF_KEYUP, KEYEVENTF_UNICODE, VK_BACK, VK_CAPITAL, VK_CONTROL, VK_MENU,
+        VK_SHIFT,
     },
     UI::WindowsAndMessaging::{
         CallNextHookEx, DispatchMessageW, GetForegroundWindow, GetMessageW,
         GetWindowTextLengthW, GetWindowTextW, GetWindowThreadProcessId,
         PostMessageW, PostThreadMessageW, SetWindowsHookExW, TranslateMessage,
-        UnhookWindowsHookEx, HC_ACTION, KBDLLHOOKSTRUCT, MSG, WH_KEYBOARD_LL,
-        WM_INPUTLANGCHANGEREQUEST, WM_KEYDOWN, WM_KEYUP, WM_QUIT, WM_SYSKEYDOWN,
+        UnhookWindowsHookEx, CHILDID_SELF, EVENT_SYSTEM_FOREGROUND, HC_ACTION, KBDLLHOOKSTRUCT,
+        LLKHF_INJECTED, MSG, OBJID_WINDOW, WH_KEYBOARD_LL, WH_MOUSE_LL, WINEVENT_OUTOFCONTEXT,
+        WINEVENT_SKIPOWNPROCESS, WM_INPUTLANGCHANGEREQUEST, WM_LBUTTONDOWN, WM_MBUTTONDOWN,
+        WM_RBUTTONDOWN, WM_XBUTTONDOWN, WM_KEYDOWN, WM_KEYUP, WM_QUIT, WM_SYSKEYDOWN,
         WM_SYSKEYUP,
     },
 };
 
-static KEY_TX: Mutex<Option<mpsc::Sender<KeyboardEvent>>> = Mutex::new(None);
+use crate::backend::PlatformBackend;
+use crate::clipboard::{ClipboardShortcut, ClipboardState, Selection};
+pub use crate::context::ActiveWindowInfo;
+pub use crate::hook::{KeyboardHook, KeyboardHookController};
+
+static HOOK_TX: Mutex<Option<mpsc::Sender<AppEvent>>> = Mutex::new(None);
+
+// Хук вызывается только в своём потоке, но состояние живёт между вызовами.
+static MODIFIERS: Mutex<Modifiers> = Mutex::new(Modifiers {
+    shift: false,
+    ctrl: false,
+    alt: false,
+    meta: false,
+});
+// Правый Alt: в раскладках с AltGr он печатает символы третьего уровня.
+static ALT_GR: AtomicBool = AtomicBool::new(false);
+
+// ToUnicodeEx без изменения состояния клавиатуры (Windows 10 1607+),
+// иначе вызов из хука съедал бы dead keys в активном окне.
+const TO_UNICODE_NO_STATE_CHANGE: u32 = 0x4;
 
 const ACTIVE_WINDOW_CACHE_TTL: Duration = Duration::from_millis(250);
 
+// Метка в dwExtraInfo наших SendInput: по ней хук узнаёт свои нажатия среди вставленных.
+const INJECTION_TAG: usize = 0x5353_5754;
+
+// CF_UNICODETEXT из winuser.h: UTF-16 с завершающим нулём.
+const CF_UNICODETEXT: u32 = 13;
+// Форматы простого текста: CF_TEXT, CF_OEMTEXT, CF_UNICODETEXT и CF_LOCALE — Windows
+// синтезирует их друг из друга.
+const PLAIN_TEXT_FORMATS: [u32; 4] = [1, 7, CF_UNICODETEXT, 16];
+
+// Буфер обмена на мгновение занимают другие процессы — OpenClipboard повторяем.
+const CLIPBOARD_OPEN_ATTEMPTS: usize = 10;
+const CLIPBOARD_OPEN_RETRY: Duration = Duration::from_millis(10);
+
 #[derive(Clone)]
 struct ActiveWindowCache {
     hwnd_key: usize,
@@ -49,15 +93,39 @@ unsafe extern "system" fn keyboard_proc(code: i32, wparam: WPARAM, lparam: LPARA
 
         if is_key_down || is_key_up {
             let kb = unsafe { *(lparam as *const KBDLLHOOKSTRUCT) };
-            if let Ok(guard) = KEY_TX.lock() {
-                if let Some(tx) = guard.as_ref() {
-                    let _ = tx.send(KeyboardEvent {
-                        vk_code: kb.vkCode,
-                        scan_code: kb.scanCode,
-                        flags: kb.flags,
-                        is_key_down,
-                    });
+            if let Some(key) = Key::from_windows_vk(kb.vkCode) {
+                let modifiers = match MODIFIERS.lock() {
+                    Ok(mut modifiers) => {
+                        modifiers.update(key, is_key_down);
+                        *modifiers
+                    }
+                    Err(_) => Modifiers::default(),
+                };
+                if key == Key::AltRight {
+                    ALT_GR.store(is_key_down, Ordering::Relaxed);
                 }
+                let text = if is_key_down {
+                    let alt_gr = ALT_GR.load(Ordering::Relaxed);
+                    typed_char(kb.vkCode, kb.scanCode, modifiers, alt_gr)
+                } else {
+                    None
+                };
+
+                let origin = if kb.flags & LLKHF_INJECTED == 0 {
+                    KeyOrigin::Physical
+                } else if kb.dwExtraInfo == INJECTION_TAG {
+                    KeyOrigin::OwnInjection
+                } else {
+                    KeyOrigin::OtherInjection
+                };
+
+                send_hook_event(AppEvent::Keyboard(KeyboardEvent {
+                    key,
+                    modifiers,
+                    text,
+                    is_key_down,
+                    origin,
+                }));
             }
         }
     }
@@ -65,51 +133,97 @@ unsafe extern "system" fn keyboard_proc(code: i32, wparam: WPARAM, lparam: LPARA
     unsafe { CallNextHookEx(std::ptr::null_mut(), code, wparam, lparam) }
 }
 
-pub struct KeyboardHookController {
-    thread_id: u32,
-    join: Option<thread::JoinHandle<()>>,
+// Колесо и движение курсор в тексте не двигают — только кнопки.
+unsafe extern "system" fn mouse_proc(code: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+    if code == HC_ACTION as i32
+        && matches!(
+            wparam as u32,
+            WM_LBUTTONDOWN | WM_RBUTTONDOWN | WM_MBUTTONDOWN | WM_XBUTTONDOWN
+        )
+    {
+        send_hook_event(AppEvent::PointerActivity);
+    }
+
+    unsafe { CallNextHookEx(std::ptr::null_mut(), code, wparam, lparam) }
 }
 
-impl KeyboardHookController {
-    pub fn stop(mut self) {
-        unsafe {
-            let _ = PostThreadMessageW(self.thread_id, WM_QUIT, 0, 0);
-        }
-        if let Some(join) = self.join.take() {
-            let _ = join.join();
-        }
-    }
+// EVENT_SYSTEM_FOREGROUND: приходит в поток хука через его цикл сообщений.
+unsafe extern "system" fn foreground_proc(
+    _hook: HWINEVENTHOOK,
+    _event: u32,
+    hwnd: *mut core::ffi::c_void,
+    id_object: i32,
+    id_child: i32,
+    _event_thread: u32,
+    _event_time: u32,
+) {
+    if hwnd.is_null() || id_object != OBJID_WINDOW || id_child != CHILDID_SELF as i32 {
+        return;
+    }
+
+    send_hook_event(AppEvent::FocusChanged {
+        process: get_process_name(hwnd),
+        title: get_window_title(hwnd).unwrap_or_default(),
+        window_id: hwnd as u64,
+    });
 }
 
-impl Drop for KeyboardHookController {
-    fn drop(&mut self) {
-        unsafe {
-            let _ = PostThreadMessageW(self.thread_id, WM_QUIT, 0, 0);
-        }
-        if let Some(join) = self.join.take() {
-            let _ = join.join();
-        }
+fn send_hook_event(event: AppEvent) {
+    if let Ok(guard) = HOOK_TX.lock()
+        && let Some(tx) = guard.as_ref()
+    {
+        let _ = tx.send(event);
     }
 }
 
-pub struct KeyboardHook {
-    controller: KeyboardHookController,
-    events: mpsc::Receiver<KeyboardEvent>,
-}
+// Символ, который нажатие напечатает в раскладке активного окна.
+fn typed_char(vk_code: u32, scan_code: u32, modifiers: Modifiers, alt_gr: bool) -> Option<char> {
+    let mut key_state = [0u8; 256];
+    if alt_gr && !modifiers.meta {
+        // Для раскладки AltGr — это Ctrl+Alt (Windows и сама добавляет к нему левый Ctrl).
+        // Раскладка без третьего уровня на Ctrl+Alt ничего не печатает.
+        key_state[VK_CONTROL as usize] = 0x80;
+        key_state[VK_MENU as usize] = 0x80;
+    } else if modifiers.ctrl || modifiers.alt || modifiers.meta {
+        return None;
+    }
+    if modifiers.shift {
+        key_state[VK_SHIFT as usize] = 0x80;
+    }
+    // Младший бит — CapsLock включён: без него ToUnicodeEx вернул бы строчную букву.
+    key_state[VK_CAPITAL as usize] = (unsafe { GetKeyState(VK_CAPITAL as i32) } & 1) as u8;
 
-impl KeyboardHook {
-    pub fn into_parts(self) -> (KeyboardHookController, mpsc::Receiver<KeyboardEvent>) {
-        (self.controller, self.events)
+    let hwnd = unsafe { GetForegroundWindow() };
+    let mut pid: u32 = 0;
+    let thread_id = unsafe { GetWindowThreadProcessId(hwnd, &mut pid) };
+    let hkl = unsafe { GetKeyboardLayout(thread_id) };
+
+    let mut buf = [0u16; 4];
+    let written = unsafe {
+        ToUnicodeEx(
+            vk_code,
+            scan_code,
+            key_state.as_ptr(),
+            buf.as_mut_ptr(),
+            buf.len() as i32,
+            TO_UNICODE_NO_STATE_CHANGE,
+            hkl,
+        )
+    };
+    if written != 1 {
+        return None;
     }
+
+    char::from_u32(buf[0] as u32).filter(|ch| !ch.is_control())
 }
 
 pub fn start_keyboard_hook() -> anyhow::Result<KeyboardHook> {
-    let (events_tx, events_rx) = mpsc::channel::<KeyboardEvent>();
+    let (events_tx, events_rx) = mpsc::channel::<AppEvent>();
     let (ready_tx, ready_rx) = mpsc::channel::<anyhow::Result<u32>>();
 
     let join = thread::spawn(move || {
         {
-            let mut guard = KEY_TX.lock().expect("keyboard hook sender lock");
+            let mut guard = HOOK_TX.lock().expect("keyboard hook sender lock");
             *guard = Some(events_tx);
         }
 
@@ -119,7 +233,7 @@ pub fn start_keyboard_hook() -> anyhow::Result<KeyboardHook> {
         let hook = unsafe { SetWindowsHookExW(WH_KEYBOARD_LL, Some(keyboard_proc), hmod, 0) };
         if hook.is_null() {
             let err = unsafe { GetLastError() };
-            let mut guard = KEY_TX.lock().expect("keyboard hook sender lock");
+            let mut guard = HOOK_TX.lock().expect("keyboard hook sender lock");
             *guard = None;
             let _ = ready_tx.send(Err(anyhow::anyhow!(
                 "SetWindowsHookExW(WH_KEYBOARD_LL) failed, GetLastError={err}"
@@ -127,6 +241,37 @@ pub fn start_keyboard_hook() -> anyhow::Result<KeyboardHook> {
             return;
         }
 
+        // Кнопки мыши. Не вышло — работаем без них: о клике в текст буфер не узнает.
+        let mouse = unsafe { SetWindowsHookExW(WH_MOUSE_LL, Some(mouse_proc), hmod, 0) };
+        if mouse.is_null() {
+            let err = unsafe { GetLastError() };
+            tracing::warn!(
+                error = err,
+                "SetWindowsHookExW(WH_MOUSE_LL) failed, mouse clicks are not tracked"
+            );
+        }
+
+        // Смена активного окна. Не вышло — работаем без неё: буферы декодера
+        // тогда не делятся по окнам.
+        let foreground = unsafe {
+            SetWinEventHook(
+                EVENT_SYSTEM_FOREGROUND,
+                EVENT_SYSTEM_FOREGROUND,
+                std::ptr::null_mut(),
+                Some(foreground_proc),
+                0,
+                0,
+                WINEVENT_OUTOFCONTEXT | WINEVENT_SKIPOWNPROCESS,
+            )
+        };
+        if foreground.is_null() {
+            let err = unsafe { GetLastError() };
+            tracing::warn!(
+                error = err,
+                "SetWinEventHook(EVENT_SYSTEM_FOREGROUND) failed, focus changes are not tracked"
+            );
+        }
+
         let _ = ready_tx.send(Ok(thread_id));
 
         let mut msg: MSG = unsafe { std::mem::zeroed() };
@@ -142,10 +287,16 @@ pub fn start_keyboard_hook() -> anyhow::Result<KeyboardHook> {
         }
 
         unsafe {
+            if !foreground.is_null() {
+                UnhookWinEvent(foreground);
+            }
+            if !mouse.is_null() {
+                UnhookWindowsHookEx(mouse);
+            }
             UnhookWindowsHookEx(hook);
         }
 
-        let mut guard = KEY_TX.lock().expect("keyboard hook sender lock");
+        let mut guard = HOOK_TX.lock().expect("keyboard hook sender lock");
         *guard = None;
     });
 
@@ -153,41 +304,14 @@ pub fn start_keyboard_hook() -> anyhow::Result<KeyboardHook> {
         .recv()
         .context("keyboard hook thread did not report status")??;
 
-    Ok(KeyboardHook {
-        controller: KeyboardHookController {
-            thread_id,
-            join: Some(join),
-        },
-        events: events_rx,
-    })
-}
-
-#[derive(Debug, Clone)]
-pub struct ActiveWindowInfo {
-    pub title: String,
-    pub process_name: Option<String>,
-}
-
-fn contains_any(haystack: &str, needles: &[String]) -> bool {
-    let haystack = haystack.to_lowercase();
-    needles
-        .iter()
-        .map(|s| s.to_lowercase())
-        .any(|needle| !needle.is_empty() && haystack.contains(&needle))
-}
-
-fn is_forbidden(info: &ActiveWindowInfo, forbidden: &ForbiddenContextsConfig) -> bool {
-    if contains_any(&info.title, &forbidden.blocked_windows) {
-        return true;
-    }
-
-    if let Some(proc_name) = info.process_name.as_ref() {
-        if contains_any(proc_name, &forbidden.blocked_processes) {
-            return true;
+    let controller = KeyboardHookController::new(move || {
+        unsafe {
+            let _ = PostThreadMessageW(thread_id, WM_QUIT, 0, 0);
         }
-    }
+        let _ = join.join();
+    });
 
-    false
+    Ok(KeyboardHook::new(controller, events_rx))
 }
 
 fn get_window_title(hwnd: *mut core::ffi::c_void) -> anyhow::Result<String> {
@@ -231,12 +355,10 @@ fn get_process_name(hwnd: *mut core::ffi::c_void) -> Option<String> {
     }
 
     let full = String::from_utf16_lossy(&buf[..size as usize]);
-    let name = std::path::Path::new(&full)
+    std::path::Path::new(&full)
         .file_name()
         .and_then(|s| s.to_str())
-        .map(|s| s.to_string());
-
-    name
+        .map(|s| s.to_string())
 }
 
 pub fn get_active_window_info() -> anyhow::Result<ActiveWindowInfo> {
@@ -250,14 +372,12 @@ pub fn get_active_window_info() -> anyhow::Result<ActiveWindowInfo> {
 
 fn get_window_info_cached(hwnd: *mut core::ffi::c_void) -> anyhow::Result<ActiveWindowInfo> {
     let hwnd_key = hwnd as usize;
-    if let Ok(guard) = ACTIVE_WINDOW_CACHE.lock() {
-        if let Some(entry) = guard.as_ref() {
-            if entry.hwnd_key == hwnd_key
-                && entry.updated_at.elapsed() <= ACTIVE_WINDOW_CACHE_TTL
-            {
-                return Ok(entry.info.clone());
-            }
-        }
+    if let Ok(guard) = ACTIVE_WINDOW_CACHE.lock()
+        && let Some(entry) = guard.as_ref()
+        && entry.hwnd_key == hwnd_key
+        && entry.updated_at.elapsed() <= ACTIVE_WINDOW_CACHE_TTL
+    {
+        return Ok(entry.info.clone());
     }
 
     let info = ActiveWindowInfo {
@@ -283,7 +403,7 @@ pub fn switch_to_next_layout(forbidden: &ForbiddenContextsConfig) -> anyhow::Res
     }
 
     let info = get_window_info_cached(hwnd)?;
-    if is_forbidden(&info, forbidden) {
+    if info.is_forbidden(forbidden) {
         return Ok(false);
     }
 
@@ -294,18 +414,11 @@ pub fn switch_to_next_layout(forbidden: &ForbiddenContextsConfig) -> anyhow::Res
     }
 
     let current = unsafe { GetKeyboardLayout(thread_id) };
-    let count = unsafe { GetKeyboardLayoutList(0, std::ptr::null_mut()) };
-    if count <= 0 {
+    let layouts = installed_hkls();
+    if layouts.is_empty() {
         return Ok(false);
     }
 
-    let mut layouts: Vec<*mut core::ffi::c_void> = vec![std::ptr::null_mut(); count as usize];
-    let filled = unsafe { GetKeyboardLayoutList(count, layouts.as_mut_ptr()) };
-    if filled <= 0 {
-        return Ok(false);
-    }
-    layouts.truncate(filled as usize);
-
     let next = match layouts.iter().position(|&hkl| hkl == current) {
         Some(idx) => layouts[(idx + 1) % layouts.len()],
         None => layouts[0],
@@ -323,14 +436,34 @@ pub fn is_forbidden_context(forbidden: &ForbiddenContextsConfig) -> anyhow::Resu
     }
 
     let info = get_window_info_cached(hwnd)?;
-    Ok(is_forbidden(&info, forbidden))
+    Ok(info.is_forbidden(forbidden))
+}
+
+// HKL — указатель, значимы младшие 32 бита (LANGID + раскладка).
+fn layout_id(hkl: *mut core::ffi::c_void) -> LayoutId {
+    LayoutId::from_windows_hkl(hkl as usize as u32)
+}
+
+fn installed_hkls() -> Vec<*mut core::ffi::c_void> {
+    let count = unsafe { GetKeyboardLayoutList(0, std::ptr::null_mut()) };
+    if count <= 0 {
+        return Vec::new();
+    }
+
+    let mut layouts: Vec<*mut core::ffi::c_void> = vec![std::ptr::null_mut(); count as usize];
+    let filled = unsafe { GetKeyboardLayoutList(count, layouts.as_mut_ptr()) };
+    if filled <= 0 {
+        return Vec::new();
+    }
+    layouts.truncate(filled as usize);
+    layouts
 }
 
-fn lo_word(value: isize) -> u16 {
-    (value as usize & 0xFFFF) as u16
+pub fn list_layouts() -> anyhow::Result<Vec<LayoutId>> {
+    Ok(installed_hkls().into_iter().map(layout_id).collect())
 }
 
-pub fn get_active_lang_id() -> anyhow::Result<u16> {
+pub fn active_layout() -> anyhow::Result<LayoutId> {
     let hwnd = unsafe { GetForegroundWindow() };
     if hwnd.is_null() {
         return Err(anyhow::anyhow!("GetForegroundWindow returned null"));
@@ -343,38 +476,23 @@ pub fn get_active_lang_id() -> anyhow::Result<u16> {
     }
 
     let hkl = unsafe { GetKeyboardLayout(thread_id) };
-    Ok(lo_word(hkl as isize))
+    Ok(layout_id(hkl))
 }
 
-pub fn set_layout_by_lang_id(
-    forbidden: &ForbiddenContextsConfig,
-    lang_id: u16,
-) -> anyhow::Result<bool> {
+pub fn set_layout(forbidden: &ForbiddenContextsConfig, layout: &LayoutId) -> anyhow::Result<bool> {
     let hwnd = unsafe { GetForegroundWindow() };
     if hwnd.is_null() {
         return Ok(false);
     }
 
     let info = get_window_info_cached(hwnd)?;
-    if is_forbidden(&info, forbidden) {
+    if info.is_forbidden(forbidden) {
         return Ok(false);
     }
 
-    let count = unsafe { GetKeyboardLayoutList(0, std::ptr::null_mut()) };
-    if count <= 0 {
-        return Ok(false);
-    }
-
-    let mut layouts: Vec<*mut core::ffi::c_void> = vec![std::ptr::null_mut(); count as usize];
-    let filled = unsafe { GetKeyboardLayoutList(count, layouts.as_mut_ptr()) };
-    if filled <= 0 {
-        return Ok(false);
-    }
-    layouts.truncate(filled as usize);
-
-    let target = layouts
+    let target = installed_hkls()
         .into_iter()
-        .find(|&hkl| lo_word(hkl as isize) == lang_id);
+        .find(|&hkl| layout_id(hkl) == *layout);
 
     let Some(target) = target else {
         return Ok(false);
@@ -386,7 +504,7 @@ pub fn set_layout_by_lang_id(
 
 pub fn send_backspaces(forbidden: &ForbiddenContextsConfig, count: usize) -> anyhow::Result<bool> {
     let info = get_active_window_info()?;
-    if is_forbidden(&info, forbidden) {
+    if info.is_forbidden(forbidden) {
         return Ok(false);
     }
 
@@ -400,11 +518,11 @@ pub fn send_backspaces(forbidden: &ForbiddenContextsConfig, count: usize) -> any
             r#type: INPUT_KEYBOARD,
             Anonymous: INPUT_0 {
                 ki: KEYBDINPUT {
-                    wVk: VK_BACK as u16,
+                    wVk: VK_BACK,
                     wScan: 0,
                     dwFlags: 0,
                     time: 0,
-                    dwExtraInfo: 0,
+                    dwExtraInfo: INJECTION_TAG,
                 },
             },
         };
@@ -413,11 +531,11 @@ pub fn send_backspaces(forbidden: &ForbiddenContextsConfig, count: usize) -> any
             r#type: INPUT_KEYBOARD,
             Anonymous: INPUT_0 {
                 ki: KEYBDINPUT {
-                    wVk: VK_BACK as u16,
+                    wVk: VK_BACK,
                     wScan: 0,
                     dwFlags: KEYEVENTF_KEYUP,
                     time: 0,
-                    dwExtraInfo: 0,
+                    dwExtraInfo: INJECTION_TAG,
                 },
             },
         };
@@ -435,7 +553,7 @@ pub fn send_unicode_text(
     text: &str,
 ) -> anyhow::Result<bool> {
     let info = get_active_window_info()?;
-    if is_forbidden(&info, forbidden) {
+    if info.is_forbidden(forbidden) {
         return Ok(false);
     }
 
@@ -453,7 +571,7 @@ pub fn send_unicode_text(
                     wScan: ch,
                     dwFlags: KEYEVENTF_UNICODE,
                     time: 0,
-                    dwExtraInfo: 0,
+                    dwExtraInfo: INJECTION_TAG,
                 },
             },
         };
@@ -466,7 +584,7 @@ pub fn send_unicode_text(
                     wScan: ch,
                     dwFlags: KEYEVENTF_UNICODE | KEYEVENTF_KEYUP,
                     time: 0,
-                    dwExtraInfo: 0,
+                    dwExtraInfo: INJECTION_TAG,
                 },
             },
         };
@@ -478,3 +596,228 @@ pub fn send_unicode_text(
     let sent = unsafe { SendInput(inputs.len() as u32, inputs.as_ptr(), std::mem::size_of::<INPUT>() as i32) };
     Ok(sent == inputs.len() as u32)
 }
+
+// Буфер обмена открыт, пока жив guard.
+struct OpenedClipboard;
+
+impl OpenedClipboard {
+    fn open() -> anyhow::Result<Self> {
+        for _ in 0..CLIPBOARD_OPEN_ATTEMPTS {
+            if unsafe { OpenClipboard(std::ptr::null_mut()) } != 0 {
+                return Ok(Self);
+            }
+            thread::sleep(CLIPBOARD_OPEN_RETRY);
+        }
+        Err(anyhow::anyhow!("OpenClipboard failed: {}", unsafe { GetLastError() }))
+    }
+}
+
+impl Drop for OpenedClipboard {
+    fn drop(&mut self) {
+        unsafe { CloseClipboard() };
+    }
+}
+
+pub fn clipboard_text() -> anyhow::Result<Option<String>> {
+    let _clipboard = OpenedClipboard::open()?;
+    if unsafe { IsClipboardFormatAvailable(CF_UNICODETEXT) } == 0 {
+        return Ok(None);
+    }
+
+    let handle = unsafe { GetClipboardData(CF_UNICODETEXT) };
+    if handle.is_null() {
+        return Ok(None);
+    }
+    let ptr = unsafe { GlobalLock(handle) } as *const u16;
+    if ptr.is_null() {
+        return Err(anyhow::anyhow!("GlobalLock on clipboard data failed"));
+    }
+
+    let mut len = 0;
+    while unsafe { *ptr.add(len) } != 0 {
+        len += 1;
+    }
+    let text = String::from_utf16_lossy(unsafe { std::slice::from_raw_parts(ptr, len) });
+    unsafe { GlobalUnlock(handle) };
+
+    Ok(Some(text))
+}
+
+pub fn clipboard_state() -> anyhow::Result<ClipboardState> {
+    let sequence = unsafe { GetClipboardSequenceNumber() } as u64;
+
+    let _clipboard = OpenedClipboard::open()?;
+    let mut has_non_text = false;
+    let mut format = 0;
+    loop {
+        format = unsafe { EnumClipboardFormats(format) };
+        if format == 0 {
+            break;
+        }
+        if !PLAIN_TEXT_FORMATS.contains(&format) {
+            has_non_text = true;
+            break;
+        }
+    }
+
+    Ok(ClipboardState {
+        sequence,
+        has_non_text,
+    })
+}
+
+pub fn set_clipboard_text(text: &str) -> anyhow::Result<bool> {
+    let utf16: Vec<u16> = text.encode_utf16().chain(std::iter::once(0)).collect();
+
+    let _clipboard = OpenedClipboard::open()?;
+    if unsafe { EmptyClipboard() } == 0 {
+        return Ok(false);
+    }
+
+    let handle = unsafe { GlobalAlloc(GMEM_MOVEABLE, utf16.len() * std::mem::size_of::<u16>()) };
+    if handle.is_null() {
+        return Err(anyhow::anyhow!("GlobalAlloc for clipboard data failed"));
+    }
+    let ptr = unsafe { GlobalLock(handle) } as *mut u16;
+    if ptr.is_null() {
+        unsafe { GlobalFree(handle) };
+        return Err(anyhow::anyhow!("GlobalLock on clipboard data failed"));
+    }
+    unsafe {
+        std::ptr::copy_nonoverlapping(utf16.as_ptr(), ptr, utf16.len());
+        GlobalUnlock(handle);
+    }
+
+    // После успешного SetClipboardData памятью владеет система.
+    if unsafe { SetClipboardData(CF_UNICODETEXT, handle) }.is_null() {
+        unsafe { GlobalFree(handle) };
+        return Ok(false);
+    }
+    Ok(true)
+}
+
+fn vk_input(vk: u16, flags: u32) -> INPUT {
+    INPUT {
+        r#type: INPUT_KEYBOARD,
+        Anonymous: INPUT_0 {
+            ki: KEYBDINPUT {
+                wVk: vk,
+                wScan: 0,
+                dwFlags: flags,
+                time: 0,
+                dwExtraInfo: INJECTION_TAG,
+            },
+        },
+    }
+}
+
+pub fn send_clipboard_shortcut(
+    forbidden: &ForbiddenContextsConfig,
+    shortcut: ClipboardShortcut,
+) -> anyhow::Result<bool> {
+    let info = get_active_window_info()?;
+    if info.is_forbidden(forbidden) {
+        return Ok(false);
+    }
+
+    // VK для букв совпадает с ASCII заглавной буквы.
+    let letter = match shortcut {
+        ClipboardShortcut::Copy => b'C' as u16,
+        ClipboardShortcut::Paste => b'V' as u16,
+    };
+    let inputs = [
+        vk_input(VK_CONTROL, 0),
+        vk_input(letter, 0),
+        vk_input(letter, KEYEVENTF_KEYUP),
+        vk_input(VK_CONTROL, KEYEVENTF_KEYUP),
+    ];
+
+    let sent = unsafe { SendInput(inputs.len() as u32, inputs.as_ptr(), std::mem::size_of::<INPUT>() as i32) };
+    Ok(sent == inputs.len() as u32)
+}
+
+pub struct WindowsBackend;
+
+impl PlatformBackend for WindowsBackend {
+    fn name(&self) -> &'static str {
+        "windows"
+    }
+
+    fn start_keyboard_hook(&self) -> anyhow::Result<KeyboardHook> {
+        start_keyboard_hook()
+    }
+
+    fn switch_to_next_layout(&self, forbidden: &ForbiddenContextsConfig) -> anyhow::Result<bool> {
+        switch_to_next_layout(forbidden)
+    }
+
+    fn list_layouts(&self) -> anyhow::Result<Vec<LayoutId>> {
+        list_layouts()
+    }
+
+    fn active_layout(&self) -> anyhow::Result<LayoutId> {
+        active_layout()
+    }
+
+    fn set_layout(
+        &self,
+        forbidden: &ForbiddenContextsConfig,
+        layout: &LayoutId,
+    ) -> anyhow::Result<bool> {
+        set_layout(forbidden, layout)
+    }
+
+    fn send_backspaces(
+        &self,
+        forbidden: &ForbiddenContextsConfig,
+        count: usize,
+    ) -> anyhow::Result<bool> {
+        send_backspaces(forbidden, count)
+    }
+
+    fn send_unicode_text(
+        &self,
+        forbidden: &ForbiddenContextsConfig,
+        text: &str,
+    ) -> anyhow::Result<bool> {
+        send_unicode_text(forbidden, text)
+    }
+
+    fn is_forbidden_context(&self, forbidden: &ForbiddenContextsConfig) -> anyhow::Result<bool> {
+        is_forbidden_context(forbidden)
+    }
+
+    fn active_window(&self) -> anyhow::Result<Option<ActiveWindowInfo>> {
+        get_active_window_info().map(Some)
+    }
+
+    // PRIMARY в Windows нет.
+    fn clipboard_text(&self, selection: Selection) -> anyhow::Result<Option<String>> {
+        match selection {
+            Selection::Clipboard => clipboard_text(),
+            Selection::Primary => Ok(None),
+        }
+    }
+
+    fn clipboard_state(&self, selection: Selection) -> anyhow::Result<Option<ClipboardState>> {
+        match selection {
+            Selection::Clipboard => clipboard_state().map(Some),
+            Selection::Primary => Ok(None),
+        }
+    }
+
+    fn set_clipboard_text(&self, selection: Selection, text: &str) -> anyhow::Result<bool> {
+        match selection {
+            Selection::Clipboard => set_clipboard_text(text),
+            Selection::Primary => Ok(false),
+        }
+    }
+
+    fn send_clipboard_shortcut(
+        &self,
+        forbidden: &ForbiddenContextsConfig,
+        shortcut: ClipboardShortcut,
+    ) -> anyhow::Result<bool> {
+        send_clipboard_shortcut(forbidden, shortcut)
+    }
+}
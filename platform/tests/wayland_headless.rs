@@ -0,0 +1,44 @@
+#![cfg(all(target_os = "linux", feature = "wayland"))]
+
+// Тесты требуют wlroots-композитор с input-method-v2 и virtual-keyboard-v1,
+// например headless sway:
+//   WLR_BACKENDS=headless WLR_LIBINPUT_NO_DEVICES=1 sway &
+//   WAYLAND_DISPLAY=wayland-1 cargo test -p smart_switcher_platform --features wayland -- --ignored --test-threads=1
+// Текстовое поле в фокусе не нужно: проверяем fail-closed поведение без него.
+
+use smart_switcher_platform::linux_wayland;
+use smart_switcher_shared_types::config::ForbiddenContextsConfig;
+
+#[test]
+#[ignore = "requires a wlroots compositor (headless sway)"]
+fn no_focused_text_field_is_forbidden() {
+    let forbidden = ForbiddenContextsConfig::default();
+
+    assert!(linux_wayland::is_forbidden_context(&forbidden).unwrap());
+    assert!(!linux_wayland::send_unicode_text(&forbidden, "привет").unwrap());
+    assert!(!linux_wayland::send_backspaces(&forbidden, 3).unwrap());
+}
+
+#[test]
+#[ignore = "requires a wlroots compositor (headless sway)"]
+fn blocked_windows_are_fail_closed() {
+    let forbidden = ForbiddenContextsConfig {
+        blocked_windows: vec!["KeePass".to_string()],
+        ..ForbiddenContextsConfig::default()
+    };
+
+    assert!(linux_wayland::is_forbidden_context(&forbidden).unwrap());
+}
+
+#[test]
+#[ignore = "requires a wlroots compositor (headless sway)"]
+fn keyboard_hook_grabs_and_releases_keyboard() {
+    let hook = linux_wayland::start_keyboard_hook().expect("start keyboard hook");
+    let (controller, _events) = hook.into_parts();
+    controller.stop();
+
+    // После release grab можно взять снова.
+    let hook = linux_wayland::start_keyboard_hook().expect("restart keyboard hook");
+    let (controller, _events) = hook.into_parts();
+    controller.stop();
+}
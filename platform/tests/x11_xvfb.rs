@@ -0,0 +1,221 @@
+#![cfg(all(target_os = "linux", feature = "x11"))]
+
+// Тесты требуют X-сервер с XTEST/XKB/XInput2, например Xvfb:
+//   xvfb-run -a cargo test -p smart_switcher_platform --features x11 -- --ignored --test-threads=1
+// Окно менеджера окон не нужно: тест сам выставляет _NET_ACTIVE_WINDOW.
+
+use std::time::{Duration, Instant};
+
+use smart_switcher_platform::linux_x11;
+use smart_switcher_shared_types::{AppEvent, Key, LayoutId};
+use smart_switcher_shared_types::config::ForbiddenContextsConfig;
+use x11rb::{
+    connection::Connection,
+    protocol::xproto::{
+        AtomEnum, ConnectionExt as _, CreateWindowAux, PropMode, Window, WindowClass,
+        BUTTON_PRESS_EVENT, BUTTON_RELEASE_EVENT, KEY_PRESS_EVENT, KEY_RELEASE_EVENT,
+    },
+    protocol::xtest::ConnectionExt as _,
+    rust_connection::RustConnection,
+    wrapper::ConnectionExt as _,
+    COPY_DEPTH_FROM_PARENT,
+};
+
+fn make_active_window(title: &str) -> (RustConnection, Window) {
+    let (conn, screen_num) = x11rb::connect(None).expect("connect to X server");
+    let root = conn.setup().roots[screen_num].root;
+
+    let window = conn.generate_id().unwrap();
+    conn.create_window(
+        COPY_DEPTH_FROM_PARENT,
+        window,
+        root,
+        0,
+        0,
+        100,
+        100,
+        0,
+        WindowClass::INPUT_OUTPUT,
+        0,
+        &CreateWindowAux::new(),
+    )
+    .unwrap();
+    conn.change_property8(
+        PropMode::REPLACE,
+        window,
+        AtomEnum::WM_NAME,
+        AtomEnum::STRING,
+        title.as_bytes(),
+    )
+    .unwrap();
+    conn.map_window(window).unwrap();
+
+    let net_active_window = conn
+        .intern_atom(false, b"_NET_ACTIVE_WINDOW")
+        .unwrap()
+        .reply()
+        .unwrap()
+        .atom;
+    conn.change_property32(
+        PropMode::REPLACE,
+        root,
+        net_active_window,
+        AtomEnum::WINDOW,
+        &[window],
+    )
+    .unwrap();
+    conn.get_input_focus().unwrap().reply().unwrap();
+
+    (conn, window)
+}
+
+#[test]
+#[ignore = "requires an X server (Xvfb)"]
+fn hook_receives_injected_backspaces() {
+    let _window = make_active_window("smart_switcher xvfb test");
+
+    let (controller, events) = linux_x11::start_keyboard_hook()
+        .expect("start keyboard hook")
+        .into_parts();
+
+    assert!(linux_x11::send_backspaces(&ForbiddenContextsConfig::default(), 2).unwrap());
+
+    let deadline = Instant::now() + Duration::from_secs(2);
+    let mut backspaces = 0;
+    while backspaces < 2 && Instant::now() < deadline {
+        if let Ok(AppEvent::Keyboard(ev)) = events.recv_timeout(Duration::from_millis(100))
+            && ev.key == Key::Backspace
+            && ev.is_key_down
+        {
+            backspaces += 1;
+        }
+    }
+
+    controller.stop();
+    assert_eq!(backspaces, 2);
+}
+
+#[test]
+#[ignore = "requires an X server (Xvfb)"]
+fn hook_reports_focus_changes() {
+    let (controller, events) = linux_x11::start_keyboard_hook()
+        .expect("start keyboard hook")
+        .into_parts();
+
+    let (_conn, window) = make_active_window("smart_switcher focus test");
+
+    let deadline = Instant::now() + Duration::from_secs(2);
+    let mut focused = None;
+    while focused.is_none() && Instant::now() < deadline {
+        if let Ok(AppEvent::FocusChanged {
+            title, window_id, ..
+        }) = events.recv_timeout(Duration::from_millis(100))
+        {
+            focused = Some((title, window_id));
+        }
+    }
+
+    controller.stop();
+    assert_eq!(
+        focused,
+        Some(("smart_switcher focus test".to_string(), u64::from(window)))
+    );
+}
+
+#[test]
+#[ignore = "requires an X server (Xvfb)"]
+fn hook_reports_mouse_buttons_but_not_the_wheel() {
+    let (conn, _window) = make_active_window("smart_switcher click test");
+
+    let (controller, events) = linux_x11::start_keyboard_hook()
+        .expect("start keyboard hook")
+        .into_parts();
+
+    // Колесо (кнопка 4), затем левая кнопка.
+    for button in [4, 1] {
+        for kind in [BUTTON_PRESS_EVENT, BUTTON_RELEASE_EVENT] {
+            conn.xtest_fake_input(kind, button, 0, x11rb::NONE, 0, 0, 0).unwrap();
+        }
+    }
+    conn.sync().unwrap();
+
+    let deadline = Instant::now() + Duration::from_secs(2);
+    let mut clicks = 0;
+    while Instant::now() < deadline {
+        if let Ok(AppEvent::PointerActivity) = events.recv_timeout(Duration::from_millis(100)) {
+            clicks += 1;
+        }
+    }
+
+    controller.stop();
+    assert_eq!(clicks, 1);
+}
+
+#[test]
+#[ignore = "requires an X server (Xvfb)"]
+fn hook_picks_up_keymap_changes() {
+    // Клавиша Q: evdev 16 + 8.
+    const KEYCODE_Q: u8 = 24;
+    let (conn, _window) = make_active_window("smart_switcher keymap test");
+
+    let (controller, events) = linux_x11::start_keyboard_hook()
+        .expect("start keyboard hook")
+        .into_parts();
+
+    // Хук уже загрузил таблицу — переназначаем Q на z, как сделал бы xmodmap.
+    let original = conn.get_keyboard_mapping(KEYCODE_Q, 1).unwrap().reply().unwrap();
+    let mut remapped = vec![0; original.keysyms.len()];
+    remapped[0] = u32::from('z');
+    remapped[1] = u32::from('Z');
+    conn.change_keyboard_mapping(1, KEYCODE_Q, original.keysyms_per_keycode, &remapped)
+        .unwrap();
+    conn.sync().unwrap();
+    std::thread::sleep(Duration::from_millis(200));
+
+    for kind in [KEY_PRESS_EVENT, KEY_RELEASE_EVENT] {
+        conn.xtest_fake_input(kind, KEYCODE_Q, 0, x11rb::NONE, 0, 0, 0).unwrap();
+    }
+    conn.sync().unwrap();
+
+    let deadline = Instant::now() + Duration::from_secs(2);
+    let mut text = None;
+    while text.is_none() && Instant::now() < deadline {
+        if let Ok(AppEvent::Keyboard(ev)) = events.recv_timeout(Duration::from_millis(100))
+            && ev.key == Key::Q
+            && ev.is_key_down
+        {
+            text = Some(ev.text);
+        }
+    }
+
+    controller.stop();
+    conn.change_keyboard_mapping(1, KEYCODE_Q, original.keysyms_per_keycode, &original.keysyms)
+        .unwrap();
+    conn.sync().unwrap();
+    assert_eq!(text, Some(Some('z')));
+}
+
+#[test]
+#[ignore = "requires an X server (Xvfb)"]
+fn forbidden_window_blocks_injection() {
+    let _window = make_active_window("KeePass - vault");
+    let forbidden = ForbiddenContextsConfig {
+        blocked_windows: vec!["KeePass".to_string()],
+        ..Default::default()
+    };
+
+    assert!(linux_x11::is_forbidden_context(&forbidden).unwrap());
+    assert!(!linux_x11::send_unicode_text(&forbidden, "привет").unwrap());
+    assert!(!linux_x11::send_backspaces(&forbidden, 1).unwrap());
+}
+
+#[test]
+#[ignore = "requires an X server (Xvfb)"]
+fn default_xvfb_layout_is_us() {
+    let _window = make_active_window("smart_switcher xvfb test");
+
+    assert_eq!(linux_x11::active_layout().unwrap(), LayoutId::from_xkb("us"));
+    assert_eq!(linux_x11::list_layouts().unwrap().len(), 1);
+    // Одна группа: переключать некуда.
+    assert!(!linux_x11::switch_to_next_layout(&ForbiddenContextsConfig::default()).unwrap());
+}
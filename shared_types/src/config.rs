@@ -1,25 +1,16 @@
 use serde::Deserialize;
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Default, Deserialize)]
 #[serde(default, rename_all = "snake_case")]
 pub struct Config {
     pub logging: LoggingConfig,
+    pub input: InputConfig,
     pub layout_switcher: LayoutSwitcherConfig,
     pub spell_checker: SpellCheckerConfig,
+    pub layouts: LayoutsConfig,
     pub modules: ModulesConfig,
 }
 
-impl Default for Config {
-    fn default() -> Self {
-        Self {
-            logging: LoggingConfig::default(),
-            layout_switcher: LayoutSwitcherConfig::default(),
-            spell_checker: SpellCheckerConfig::default(),
-            modules: ModulesConfig::default(),
-        }
-    }
-}
-
 #[derive(Debug, Clone, Deserialize)]
 #[serde(default, rename_all = "snake_case")]
 pub struct LoggingConfig {
@@ -36,13 +27,43 @@ impl Default for LoggingConfig {
     }
 }
 
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, rename_all = "snake_case")]
+pub struct InputConfig {
+    // Нажатия, вставленные другими программами: "process" — как набранные с клавиатуры,
+    // "reset" — текст поменяла программа, буфер слов сбрасывается.
+    pub other_injections: String,
+}
+
+impl Default for InputConfig {
+    fn default() -> Self {
+        Self {
+            other_injections: "process".to_string(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Deserialize)]
 #[serde(default, rename_all = "snake_case")]
 pub struct LayoutSwitcherConfig {
     pub enabled: bool,
     pub hotkey: String,
     pub auto_detect: bool,
-    pub detect_threshold: u8,
+    // Насколько (в натах) n-граммная модель должна предпочесть другую раскладку,
+    // чтобы исправить слово, которого нет в словарях.
+    pub confidence: f64,
+    // Сколько слов перед исправленным проверить заново как одну фразу; 0 — только само слово.
+    pub phrase_words: usize,
+    // Клавиша отмены последнего авто-исправления ("pause", "scrolllock"); "" — выключено.
+    pub undo_hotkey: String,
+    // Клавиша конвертации последнего слова (повторное нажатие — ещё одного); "" — выключено.
+    pub convert_hotkey: String,
+    // Сочетание конвертации выделенного текста ("shift+scrolllock"); "" — выключено.
+    pub convert_selection_hotkey: String,
+    // Файл выученных исключений; "" — в данных пользователя.
+    pub exceptions_file: String,
+    // Вести исключения отдельно для каждого приложения.
+    pub exceptions_per_app: bool,
     pub forbidden_contexts: ForbiddenContextsConfig,
 }
 
@@ -52,13 +73,19 @@ impl Default for LayoutSwitcherConfig {
             enabled: false,
             hotkey: "alt+shift".to_string(),
             auto_detect: true,
-            detect_threshold: 3,
+            confidence: 7.0,
+            phrase_words: 3,
+            undo_hotkey: "pause".to_string(),
+            convert_hotkey: "scrolllock".to_string(),
+            convert_selection_hotkey: "shift+scrolllock".to_string(),
+            exceptions_file: String::new(),
+            exceptions_per_app: false,
             forbidden_contexts: ForbiddenContextsConfig::default(),
         }
     }
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Default, Deserialize)]
 #[serde(default, rename_all = "snake_case")]
 pub struct ForbiddenContextsConfig {
     pub blocked_processes: Vec<String>,
@@ -66,16 +93,6 @@ pub struct ForbiddenContextsConfig {
     pub blocked_input_types: Vec<String>,
 }
 
-impl Default for ForbiddenContextsConfig {
-    fn default() -> Self {
-        Self {
-            blocked_processes: Vec::new(),
-            blocked_windows: Vec::new(),
-            blocked_input_types: Vec::new(),
-        }
-    }
-}
-
 #[derive(Debug, Clone, Deserialize)]
 #[serde(default, rename_all = "snake_case")]
 pub struct SpellCheckerConfig {
@@ -114,6 +131,24 @@ impl Default for SpellCheckerApiConfig {
     }
 }
 
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, rename_all = "snake_case")]
+pub struct LayoutsConfig {
+    // Каталог с пользовательскими файлами раскладок (`*.toml`).
+    pub dir: String,
+    // XKB symbols для установленных раскладок Linux; пустая строка — не читать.
+    pub xkb_symbols_dir: String,
+}
+
+impl Default for LayoutsConfig {
+    fn default() -> Self {
+        Self {
+            dir: "layouts.d".to_string(),
+            xkb_symbols_dir: "/usr/share/X11/xkb/symbols".to_string(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Deserialize)]
 #[serde(default, rename_all = "snake_case")]
 pub struct ModulesConfig {
@@ -1,13 +1,67 @@
+use crate::keys::{Key, Modifiers};
+use crate::layout::LayoutId;
+
 #[derive(Debug, Clone)]
 pub enum AppEvent {
     ShutdownRequested,
     Keyboard(KeyboardEvent),
+    // Фокус перешёл в другое окно (от бэкенда, как и нажатия). `window_id` — идентификатор
+    // окна в системе (HWND, X11 window): заголовок меняется, а окно то же.
+    FocusChanged {
+        process: Option<String>,
+        title: String,
+        window_id: u64,
+    },
+    // Нажата кнопка мыши: курсор в тексте мог переместиться, слова перед ним уже не те.
+    PointerActivity,
+
+    // Текст из нажатий (декодер в core), идут сразу за своим `Keyboard`.
+    // Клавиши слова — по US QWERTY (`Key::us_char`).
+    // Набираемое слово изменилось: напечатана буква или стёрта Backspace.
+    WordEdited { raw_keys: String },
+    // Backspace стёр разделитель за словом — курсор снова в конце слова.
+    WordReopened { raw_keys: String },
+    // Backspace стёр символ вне набираемого слова: разделитель или текст,
+    // набранный до того, как декодер начал строку.
+    CharErased,
+    // Разделитель набран без слова перед ним (второй пробел, пробел в начале строки).
+    DelimiterTyped { delimiter: char },
+    // Слово закончено разделителем; `text` — как оно показано в `layout`.
+    // На Enter `delimiter` — '\n'.
+    WordCommitted {
+        raw_keys: String,
+        text: String,
+        layout: LayoutId,
+        delimiter: char,
+    },
+    // Строка закончена Enter.
+    LineCommitted { text: String },
+    // Курсор ушёл (стрелки, Tab, Delete…) — что перед ним, неизвестно.
+    BufferReset,
+    // Модуль сам заменил текст перед курсором: буфер декодера с экраном больше не совпадает.
+    TextReplaced,
 }
 
 #[derive(Debug, Clone)]
 pub struct KeyboardEvent {
-    pub vk_code: u32,
-    pub scan_code: u32,
-    pub flags: u32,
+    pub key: Key,
+    // Состояние модификаторов с учётом этого нажатия.
+    pub modifiers: Modifiers,
+    // Символ, который клавиша печатает в активной раскладке; None — не печатает
+    // или бэкенд этого не знает (evdev, Wayland).
+    pub text: Option<char>,
     pub is_key_down: bool,
+    pub origin: KeyOrigin,
+}
+
+// Откуда нажатие: с клавиатуры или вставлено программой.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum KeyOrigin {
+    // С клавиатуры; бэкенд, который не различает вставленные нажатия, помечает так все.
+    #[default]
+    Physical,
+    // Вставлено самим smart_switcher: исправление, отмена, конвертация, Ctrl+C/Ctrl+V.
+    OwnInjection,
+    // Вставлено другой программой: AutoHotkey, xdotool, ydotool, экранная клавиатура.
+    OtherInjection,
 }
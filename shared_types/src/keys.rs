@@ -0,0 +1,480 @@
+// Физическая клавиша по положению на клавиатуре (имена как у `KeyboardEvent.code` в вебе):
+// `Key::Q` — первая буквенная клавиша верхнего ряда в любой раскладке, будь то Q, Й или A.
+#[rustfmt::skip]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Key {
+    A, B, C, D, E, F, G, H, I, J, K, L, M,
+    N, O, P, Q, R, S, T, U, V, W, X, Y, Z,
+    Digit0, Digit1, Digit2, Digit3, Digit4,
+    Digit5, Digit6, Digit7, Digit8, Digit9,
+    Minus,
+    Equal,
+    BracketLeft,
+    BracketRight,
+    Backslash,
+    Semicolon,
+    Quote,
+    Backquote,
+    Comma,
+    Period,
+    Slash,
+    // Дополнительная клавиша ISO-клавиатур между левым Shift и Z.
+    IntlBackslash,
+    Space,
+    Enter,
+    Backspace,
+    Tab,
+    Escape,
+    CapsLock,
+    ShiftLeft,
+    ShiftRight,
+    ControlLeft,
+    ControlRight,
+    AltLeft,
+    AltRight,
+    MetaLeft,
+    MetaRight,
+    ContextMenu,
+    F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11, F12,
+    Insert,
+    Delete,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    ArrowUp,
+    ArrowDown,
+    ArrowLeft,
+    ArrowRight,
+    PrintScreen,
+    ScrollLock,
+    Pause,
+    NumLock,
+    Numpad0, Numpad1, Numpad2, Numpad3, Numpad4,
+    Numpad5, Numpad6, Numpad7, Numpad8, Numpad9,
+    NumpadAdd,
+    NumpadSubtract,
+    NumpadMultiply,
+    NumpadDivide,
+    NumpadDecimal,
+    NumpadEnter,
+}
+
+#[rustfmt::skip]
+const LETTERS: [Key; 26] = [
+    Key::A, Key::B, Key::C, Key::D, Key::E, Key::F, Key::G, Key::H, Key::I,
+    Key::J, Key::K, Key::L, Key::M, Key::N, Key::O, Key::P, Key::Q, Key::R,
+    Key::S, Key::T, Key::U, Key::V, Key::W, Key::X, Key::Y, Key::Z,
+];
+
+#[rustfmt::skip]
+const DIGITS: [Key; 10] = [
+    Key::Digit0, Key::Digit1, Key::Digit2, Key::Digit3, Key::Digit4,
+    Key::Digit5, Key::Digit6, Key::Digit7, Key::Digit8, Key::Digit9,
+];
+
+#[rustfmt::skip]
+const NUMPAD_DIGITS: [Key; 10] = [
+    Key::Numpad0, Key::Numpad1, Key::Numpad2, Key::Numpad3, Key::Numpad4,
+    Key::Numpad5, Key::Numpad6, Key::Numpad7, Key::Numpad8, Key::Numpad9,
+];
+
+#[rustfmt::skip]
+const FUNCTION_KEYS: [Key; 12] = [
+    Key::F1, Key::F2, Key::F3, Key::F4, Key::F5, Key::F6,
+    Key::F7, Key::F8, Key::F9, Key::F10, Key::F11, Key::F12,
+];
+
+// Клавиши, печатающие символ, и что они печатают на US QWERTY (base, shift).
+#[rustfmt::skip]
+const US_PRINTABLE: [(Key, char, char); 48] = [
+    (Key::Backquote, '`', '~'),
+    (Key::Digit1, '1', '!'), (Key::Digit2, '2', '@'), (Key::Digit3, '3', '#'),
+    (Key::Digit4, '4', '$'), (Key::Digit5, '5', '%'), (Key::Digit6, '6', '^'),
+    (Key::Digit7, '7', '&'), (Key::Digit8, '8', '*'), (Key::Digit9, '9', '('),
+    (Key::Digit0, '0', ')'), (Key::Minus, '-', '_'), (Key::Equal, '=', '+'),
+    (Key::Q, 'q', 'Q'), (Key::W, 'w', 'W'), (Key::E, 'e', 'E'), (Key::R, 'r', 'R'),
+    (Key::T, 't', 'T'), (Key::Y, 'y', 'Y'), (Key::U, 'u', 'U'), (Key::I, 'i', 'I'),
+    (Key::O, 'o', 'O'), (Key::P, 'p', 'P'),
+    (Key::BracketLeft, '[', '{'), (Key::BracketRight, ']', '}'), (Key::Backslash, '\\', '|'),
+    (Key::A, 'a', 'A'), (Key::S, 's', 'S'), (Key::D, 'd', 'D'), (Key::F, 'f', 'F'),
+    (Key::G, 'g', 'G'), (Key::H, 'h', 'H'), (Key::J, 'j', 'J'), (Key::K, 'k', 'K'),
+    (Key::L, 'l', 'L'), (Key::Semicolon, ';', ':'), (Key::Quote, '\'', '"'),
+    (Key::Z, 'z', 'Z'), (Key::X, 'x', 'X'), (Key::C, 'c', 'C'), (Key::V, 'v', 'V'),
+    (Key::B, 'b', 'B'), (Key::N, 'n', 'N'), (Key::M, 'm', 'M'),
+    (Key::Comma, ',', '<'), (Key::Period, '.', '>'), (Key::Slash, '/', '?'),
+    (Key::Space, ' ', ' '),
+];
+
+impl Key {
+    pub fn is_letter(self) -> bool {
+        LETTERS.contains(&self)
+    }
+
+    pub fn is_modifier(self) -> bool {
+        matches!(
+            self,
+            Key::ShiftLeft
+                | Key::ShiftRight
+                | Key::ControlLeft
+                | Key::ControlRight
+                | Key::AltLeft
+                | Key::AltRight
+                | Key::MetaLeft
+                | Key::MetaRight
+        )
+    }
+
+    // Символ этой клавиши на US QWERTY — «латинское имя» физической клавиши.
+    pub fn us_char(self, shift: bool) -> Option<char> {
+        US_PRINTABLE
+            .iter()
+            .find(|&&(key, _, _)| key == self)
+            .map(|&(_, base, shifted)| if shift { shifted } else { base })
+    }
+
+    // Имя `KeyboardEvent.code` из веба ("KeyQ", "Digit1", "BracketLeft") — так клавиши
+    // называются в файлах раскладок. Только клавиши, печатающие символ.
+    pub fn from_code(code: &str) -> Option<Key> {
+        if let Some(letter) = code.strip_prefix("Key") {
+            let &[ch] = letter.as_bytes() else {
+                return None;
+            };
+            return ch
+                .is_ascii_uppercase()
+                .then(|| LETTERS[(ch - b'A') as usize]);
+        }
+        if let Some(digit) = code.strip_prefix("Digit") {
+            let &[ch] = digit.as_bytes() else {
+                return None;
+            };
+            return ch.is_ascii_digit().then(|| DIGITS[(ch - b'0') as usize]);
+        }
+
+        let key = match code {
+            "Minus" => Key::Minus,
+            "Equal" => Key::Equal,
+            "BracketLeft" => Key::BracketLeft,
+            "BracketRight" => Key::BracketRight,
+            "Backslash" => Key::Backslash,
+            "Semicolon" => Key::Semicolon,
+            "Quote" => Key::Quote,
+            "Backquote" => Key::Backquote,
+            "Comma" => Key::Comma,
+            "Period" => Key::Period,
+            "Slash" => Key::Slash,
+            "IntlBackslash" => Key::IntlBackslash,
+            "Space" => Key::Space,
+            _ => return None,
+        };
+        Some(key)
+    }
+
+    // Windows virtual-key code. Для OEM-клавиш VK зависит от раскладки,
+    // здесь — значения US-раскладки, как их отдаёт хук при EN/RU.
+    pub fn from_windows_vk(vk: u32) -> Option<Key> {
+        let key = match vk {
+            0x41..=0x5A => LETTERS[(vk - 0x41) as usize],
+            0x30..=0x39 => DIGITS[(vk - 0x30) as usize],
+            0x60..=0x69 => NUMPAD_DIGITS[(vk - 0x60) as usize],
+            0x70..=0x7B => FUNCTION_KEYS[(vk - 0x70) as usize],
+            0x08 => Key::Backspace,
+            0x09 => Key::Tab,
+            0x0D => Key::Enter,
+            0x10 | 0xA0 => Key::ShiftLeft,
+            0xA1 => Key::ShiftRight,
+            0x11 | 0xA2 => Key::ControlLeft,
+            0xA3 => Key::ControlRight,
+            0x12 | 0xA4 => Key::AltLeft,
+            0xA5 => Key::AltRight,
+            0x13 => Key::Pause,
+            0x14 => Key::CapsLock,
+            0x1B => Key::Escape,
+            0x20 => Key::Space,
+            0x21 => Key::PageUp,
+            0x22 => Key::PageDown,
+            0x23 => Key::End,
+            0x24 => Key::Home,
+            0x25 => Key::ArrowLeft,
+            0x26 => Key::ArrowUp,
+            0x27 => Key::ArrowRight,
+            0x28 => Key::ArrowDown,
+            0x2C => Key::PrintScreen,
+            0x2D => Key::Insert,
+            0x2E => Key::Delete,
+            0x5B => Key::MetaLeft,
+            0x5C => Key::MetaRight,
+            0x5D => Key::ContextMenu,
+            0x6A => Key::NumpadMultiply,
+            0x6B => Key::NumpadAdd,
+            0x6D => Key::NumpadSubtract,
+            0x6E => Key::NumpadDecimal,
+            0x6F => Key::NumpadDivide,
+            0x90 => Key::NumLock,
+            0x91 => Key::ScrollLock,
+            0xBA => Key::Semicolon,
+            0xBB => Key::Equal,
+            0xBC => Key::Comma,
+            0xBD => Key::Minus,
+            0xBE => Key::Period,
+            0xBF => Key::Slash,
+            0xC0 => Key::Backquote,
+            0xDB => Key::BracketLeft,
+            0xDC => Key::Backslash,
+            0xDD => Key::BracketRight,
+            0xDE => Key::Quote,
+            0xE2 => Key::IntlBackslash,
+            _ => return None,
+        };
+        Some(key)
+    }
+
+    // Linux evdev keycode (`KEY_*` из linux/input-event-codes.h).
+    pub fn from_evdev(code: u16) -> Option<Key> {
+        let key = match code {
+            1 => Key::Escape,
+            2..=10 => DIGITS[(code - 1) as usize],
+            11 => Key::Digit0,
+            12 => Key::Minus,
+            13 => Key::Equal,
+            14 => Key::Backspace,
+            15 => Key::Tab,
+            16 => Key::Q,
+            17 => Key::W,
+            18 => Key::E,
+            19 => Key::R,
+            20 => Key::T,
+            21 => Key::Y,
+            22 => Key::U,
+            23 => Key::I,
+            24 => Key::O,
+            25 => Key::P,
+            26 => Key::BracketLeft,
+            27 => Key::BracketRight,
+            28 => Key::Enter,
+            29 => Key::ControlLeft,
+            30 => Key::A,
+            31 => Key::S,
+            32 => Key::D,
+            33 => Key::F,
+            34 => Key::G,
+            35 => Key::H,
+            36 => Key::J,
+            37 => Key::K,
+            38 => Key::L,
+            39 => Key::Semicolon,
+            40 => Key::Quote,
+            41 => Key::Backquote,
+            42 => Key::ShiftLeft,
+            43 => Key::Backslash,
+            44 => Key::Z,
+            45 => Key::X,
+            46 => Key::C,
+            47 => Key::V,
+            48 => Key::B,
+            49 => Key::N,
+            50 => Key::M,
+            51 => Key::Comma,
+            52 => Key::Period,
+            53 => Key::Slash,
+            54 => Key::ShiftRight,
+            55 => Key::NumpadMultiply,
+            56 => Key::AltLeft,
+            57 => Key::Space,
+            58 => Key::CapsLock,
+            59..=68 => FUNCTION_KEYS[(code - 59) as usize],
+            69 => Key::NumLock,
+            70 => Key::ScrollLock,
+            71 => Key::Numpad7,
+            72 => Key::Numpad8,
+            73 => Key::Numpad9,
+            74 => Key::NumpadSubtract,
+            75 => Key::Numpad4,
+            76 => Key::Numpad5,
+            77 => Key::Numpad6,
+            78 => Key::NumpadAdd,
+            79 => Key::Numpad1,
+            80 => Key::Numpad2,
+            81 => Key::Numpad3,
+            82 => Key::Numpad0,
+            83 => Key::NumpadDecimal,
+            86 => Key::IntlBackslash,
+            87 => Key::F11,
+            88 => Key::F12,
+            96 => Key::NumpadEnter,
+            97 => Key::ControlRight,
+            98 => Key::NumpadDivide,
+            99 => Key::PrintScreen,
+            100 => Key::AltRight,
+            102 => Key::Home,
+            103 => Key::ArrowUp,
+            104 => Key::PageUp,
+            105 => Key::ArrowLeft,
+            106 => Key::ArrowRight,
+            107 => Key::End,
+            108 => Key::ArrowDown,
+            109 => Key::PageDown,
+            110 => Key::Insert,
+            111 => Key::Delete,
+            119 => Key::Pause,
+            125 => Key::MetaLeft,
+            126 => Key::MetaRight,
+            127 => Key::ContextMenu,
+            _ => return None,
+        };
+        Some(key)
+    }
+
+    // X11 keysym. Символьные keysym сопоставляются по US QWERTY
+    // (`XK_a` и `XK_A` — `Key::A`), служебные — по своему смыслу.
+    pub fn from_x11_keysym(keysym: u32) -> Option<Key> {
+        let key = match keysym {
+            0xFF08 => Key::Backspace,
+            0xFF09 => Key::Tab,
+            0xFF0D => Key::Enter,
+            0xFF13 => Key::Pause,
+            0xFF14 => Key::ScrollLock,
+            0xFF1B => Key::Escape,
+            0xFF50 => Key::Home,
+            0xFF51 => Key::ArrowLeft,
+            0xFF52 => Key::ArrowUp,
+            0xFF53 => Key::ArrowRight,
+            0xFF54 => Key::ArrowDown,
+            0xFF55 => Key::PageUp,
+            0xFF56 => Key::PageDown,
+            0xFF57 => Key::End,
+            0xFF61 => Key::PrintScreen,
+            0xFF63 => Key::Insert,
+            0xFF67 => Key::ContextMenu,
+            0xFF7F => Key::NumLock,
+            0xFF8D => Key::NumpadEnter,
+            0xFFAA => Key::NumpadMultiply,
+            0xFFAB => Key::NumpadAdd,
+            0xFFAD => Key::NumpadSubtract,
+            0xFFAE => Key::NumpadDecimal,
+            0xFFAF => Key::NumpadDivide,
+            0xFFB0..=0xFFB9 => NUMPAD_DIGITS[(keysym - 0xFFB0) as usize],
+            0xFFBE..=0xFFC9 => FUNCTION_KEYS[(keysym - 0xFFBE) as usize],
+            0xFFE1 => Key::ShiftLeft,
+            0xFFE2 => Key::ShiftRight,
+            0xFFE3 => Key::ControlLeft,
+            0xFFE4 => Key::ControlRight,
+            0xFFE5 => Key::CapsLock,
+            0xFFE9 => Key::AltLeft,
+            0xFFEA | 0xFE03 => Key::AltRight,
+            0xFFEB => Key::MetaLeft,
+            0xFFEC => Key::MetaRight,
+            0xFFFF => Key::Delete,
+            0x20..=0x7E => {
+                let ch = char::from_u32(keysym)?;
+                return US_PRINTABLE
+                    .iter()
+                    .find(|&&(_, base, shifted)| ch == base || ch == shifted)
+                    .map(|&(key, _, _)| key);
+            }
+            _ => return None,
+        };
+        Some(key)
+    }
+}
+
+// Зажатые модификаторы. Левый и правый не различаются.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Modifiers {
+    pub shift: bool,
+    pub ctrl: bool,
+    pub alt: bool,
+    pub meta: bool,
+}
+
+impl Modifiers {
+    // Бэкенды ведут состояние по потоку нажатий и ставят его в каждое событие
+    // уже с учётом текущего нажатия.
+    pub fn update(&mut self, key: Key, is_key_down: bool) {
+        match key {
+            Key::ShiftLeft | Key::ShiftRight => self.shift = is_key_down,
+            Key::ControlLeft | Key::ControlRight => self.ctrl = is_key_down,
+            Key::AltLeft | Key::AltRight => self.alt = is_key_down,
+            Key::MetaLeft | Key::MetaRight => self.meta = is_key_down,
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converters_agree_on_physical_keys() {
+        // VK, evdev, keysym
+        let cases = [
+            (0x51, 16, 0x71, Key::Q),
+            (0x41, 30, 0x61, Key::A),
+            (0xBC, 51, 0x2C, Key::Comma),
+            (0xBE, 52, 0x2E, Key::Period),
+            (0xDB, 26, 0x5B, Key::BracketLeft),
+            (0xC0, 41, 0x60, Key::Backquote),
+            (0x31, 2, 0x31, Key::Digit1),
+            (0x30, 11, 0x30, Key::Digit0),
+            (0x20, 57, 0x20, Key::Space),
+            (0x08, 14, 0xFF08, Key::Backspace),
+            (0x0D, 28, 0xFF0D, Key::Enter),
+            (0xA0, 42, 0xFFE1, Key::ShiftLeft),
+            (0xA5, 100, 0xFFEA, Key::AltRight),
+            (0x7B, 88, 0xFFC9, Key::F12),
+        ];
+
+        for (vk, evdev, keysym, key) in cases {
+            assert_eq!(Key::from_windows_vk(vk), Some(key), "vk 0x{vk:02X}");
+            assert_eq!(Key::from_evdev(evdev), Some(key), "evdev {evdev}");
+            assert_eq!(Key::from_x11_keysym(keysym), Some(key), "keysym 0x{keysym:04X}");
+        }
+
+        assert_eq!(Key::from_x11_keysym('Q' as u32), Some(Key::Q));
+        assert_eq!(Key::from_x11_keysym('<' as u32), Some(Key::Comma));
+        assert_eq!(Key::from_windows_vk(0xE7), None);
+        assert_eq!(Key::from_evdev(0), None);
+    }
+
+    #[test]
+    fn us_char_follows_qwerty() {
+        assert_eq!(Key::Q.us_char(false), Some('q'));
+        assert_eq!(Key::Q.us_char(true), Some('Q'));
+        assert_eq!(Key::Comma.us_char(false), Some(','));
+        assert_eq!(Key::Digit2.us_char(true), Some('@'));
+        assert_eq!(Key::Enter.us_char(false), None);
+        assert!(Key::Z.is_letter());
+        assert!(!Key::Comma.is_letter());
+    }
+
+    #[test]
+    fn from_code_uses_web_names() {
+        assert_eq!(Key::from_code("KeyQ"), Some(Key::Q));
+        assert_eq!(Key::from_code("Digit0"), Some(Key::Digit0));
+        assert_eq!(Key::from_code("BracketLeft"), Some(Key::BracketLeft));
+        assert_eq!(Key::from_code("Keyq"), None);
+        assert_eq!(Key::from_code("Digit10"), None);
+        assert_eq!(Key::from_code("ShiftLeft"), None);
+    }
+
+    #[test]
+    fn modifiers_follow_key_presses() {
+        let mut modifiers = Modifiers::default();
+        modifiers.update(Key::ShiftRight, true);
+        modifiers.update(Key::AltLeft, true);
+        modifiers.update(Key::A, true);
+        assert_eq!(
+            modifiers,
+            Modifiers {
+                shift: true,
+                alt: true,
+                ..Modifiers::default()
+            }
+        );
+
+        modifiers.update(Key::ShiftRight, false);
+        assert!(!modifiers.shift);
+        assert!(modifiers.alt);
+    }
+}
@@ -0,0 +1,152 @@
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Script {
+    Latin,
+    Cyrillic,
+    Other,
+}
+
+// Установленная в системе раскладка. Сравнение — по всем полям,
+// `variant` уникален в пределах платформы.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LayoutId {
+    // BCP 47: "en-US", "ru-RU"; "und" — язык неизвестен.
+    pub language: String,
+    // Имя раскладки у платформы: XKB "us", "ru(phonetic)"; Windows — HKL в hex, "04190419".
+    pub variant: String,
+    pub script: Script,
+}
+
+impl LayoutId {
+    // Windows HKL: младшее слово — LANGID, старшее — конкретная раскладка языка.
+    pub fn from_windows_hkl(hkl: u32) -> LayoutId {
+        let language = windows_language(hkl as u16);
+        LayoutId {
+            language: language.to_string(),
+            variant: format!("{hkl:08X}"),
+            script: script_of(language),
+        }
+    }
+
+    // XKB раскладка с необязательным вариантом: "us", "ru(phonetic)".
+    pub fn from_xkb(layout: &str) -> LayoutId {
+        let layout = layout.trim();
+        let name = layout.split('(').next().unwrap_or_default().trim();
+        let language = xkb_language(name);
+        LayoutId {
+            language: language.to_string(),
+            variant: layout.to_string(),
+            script: script_of(language),
+        }
+    }
+
+    // "ru" для "ru-RU".
+    pub fn primary_language(&self) -> &str {
+        self.language.split('-').next().unwrap_or_default()
+    }
+}
+
+impl fmt::Display for LayoutId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} [{}]", self.language, self.variant)
+    }
+}
+
+fn windows_language(lang_id: u16) -> &'static str {
+    match lang_id {
+        0x0409 => "en-US",
+        0x0809 => "en-GB",
+        0x0419 => "ru-RU",
+        0x0422 => "uk-UA",
+        0x0423 => "be-BY",
+        0x043F => "kk-KZ",
+        0x0402 => "bg-BG",
+        0x0407 => "de-DE",
+        0x040C => "fr-FR",
+        0x0C0A => "es-ES",
+        0x0410 => "it-IT",
+        0x0415 => "pl-PL",
+        0x0405 => "cs-CZ",
+        // Прочие регионы известных языков — по primary language.
+        _ => match lang_id & 0x03FF {
+            0x09 => "en",
+            0x19 => "ru",
+            0x22 => "uk",
+            0x23 => "be",
+            0x3F => "kk",
+            0x02 => "bg",
+            0x07 => "de",
+            0x0C => "fr",
+            0x0A => "es",
+            0x10 => "it",
+            0x15 => "pl",
+            0x05 => "cs",
+            _ => "und",
+        },
+    }
+}
+
+fn xkb_language(layout: &str) -> &'static str {
+    match layout {
+        "us" => "en-US",
+        "gb" => "en-GB",
+        "ru" => "ru-RU",
+        "ua" => "uk-UA",
+        "by" => "be-BY",
+        "kz" => "kk-KZ",
+        "bg" => "bg-BG",
+        "de" => "de-DE",
+        "fr" => "fr-FR",
+        "es" => "es-ES",
+        "it" => "it-IT",
+        "pl" => "pl-PL",
+        "cz" => "cs-CZ",
+        _ => "und",
+    }
+}
+
+fn script_of(language: &str) -> Script {
+    match language.split('-').next().unwrap_or_default() {
+        "ru" | "uk" | "be" | "kk" | "bg" => Script::Cyrillic,
+        "en" | "de" | "fr" | "es" | "it" | "pl" | "cs" => Script::Latin,
+        _ => Script::Other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn windows_hkl_keeps_the_exact_layout() {
+        let ru = LayoutId::from_windows_hkl(0x0419_0419);
+        assert_eq!(ru.language, "ru-RU");
+        assert_eq!(ru.variant, "04190419");
+        assert_eq!(ru.script, Script::Cyrillic);
+
+        // US Dvorak: тот же язык, другая раскладка.
+        let dvorak = LayoutId::from_windows_hkl(0xF002_0409);
+        assert_eq!(dvorak.language, "en-US");
+        assert_ne!(dvorak, LayoutId::from_windows_hkl(0x0409_0409));
+
+        let ru_md = LayoutId::from_windows_hkl(0x0819_0819);
+        assert_eq!(ru_md.language, "ru");
+        assert_eq!(ru_md.script, Script::Cyrillic);
+
+        assert_eq!(LayoutId::from_windows_hkl(0x0411_0411).script, Script::Other);
+    }
+
+    #[test]
+    fn xkb_variant_is_part_of_identity() {
+        let phonetic = LayoutId::from_xkb("ru(phonetic)");
+        assert_eq!(phonetic.language, "ru-RU");
+        assert_eq!(phonetic.primary_language(), "ru");
+        assert_eq!(phonetic.script, Script::Cyrillic);
+        assert_ne!(phonetic, LayoutId::from_xkb("ru"));
+
+        assert_eq!(LayoutId::from_xkb("ua").script, Script::Cyrillic);
+        assert_eq!(LayoutId::from_xkb("us").script, Script::Latin);
+        assert_eq!(LayoutId::from_xkb("fi").language, "und");
+    }
+}
@@ -1,5 +1,9 @@
 pub mod config;
 pub mod events;
+pub mod keys;
+pub mod layout;
 
 pub use config::Config;
-pub use events::{AppEvent, KeyboardEvent};
+pub use events::{AppEvent, KeyOrigin, KeyboardEvent};
+pub use keys::{Key, Modifiers};
+pub use layout::{LayoutId, Script};